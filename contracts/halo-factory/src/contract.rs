@@ -3,28 +3,47 @@ use std::str::FromStr;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Reply, ReplyOn, Response,
-    StdError, StdResult, SubMsg, WasmMsg,
+    from_binary, to_binary, Addr, Api, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Order,
+    Reply, ReplyOn, Response, StdError, StdResult, SubMsg, Timestamp, Uint128, WasmMsg,
 };
 use cw2::set_contract_version;
+use cw20::Cw20ReceiveMsg;
+use cw_storage_plus::Bound;
 use haloswap::querier::{query_balance, query_pair_info_from_pair};
 
 use crate::state::{
-    add_allow_native_token, pair_key, read_pairs, Config, TmpPairInfo, ALLOW_NATIVE_TOKENS, CONFIG,
-    DEFAULT_COMMISSION_RATE, PAIRS, TMP_PAIR_INFO,
+    add_allow_native_token, add_pair, load_pair, load_pair_by_address, load_pair_by_lp_token,
+    next_reply_id, pair_key, pairs, read_pairs, read_pairs_by_asset, remove_pair, Config,
+    OwnershipProposal, PendingCommissionRate, TmpPairInfo, ALLOW_NATIVE_TOKENS, CONFIG,
+    DEFAULT_COMMISSION_RATE, DEFAULT_FLASH_FEE_RATE, DEFAULT_PROTOCOL_FEE_RATE, DEPRECATED_PAIRS,
+    LEGACY_PAIRS, OWNERSHIP_PROPOSAL, PAIR_COUNT, PAIR_PAUSE_STATUS, PAUSE_STATUS,
+    PENDING_COMMISSION_RATES, ROLES, TMP_PAIR_INFOS,
 };
 use bignumber::Decimal256;
 use cw_utils::parse_reply_instantiate_data;
-use haloswap::asset::{AssetInfo, CreatePairRequirements, LPTokenInfo, PairInfo, PairInfoRaw};
+use haloswap::asset::{
+    Asset, AssetInfo, AssetInfoRaw, LPTokenInfo, PairInfo, PairInfoRaw, PairType,
+    DEFAULT_MIN_LIQUIDITY,
+};
+use haloswap::error::FactoryError;
 use haloswap::factory::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, NativeTokenDecimalsResponse,
-    PairsResponse, QueryMsg,
+    AddressRole, ConfigResponse, CreatePairParams, Cw20HookMsg, ExecuteMsg, InstantiateMsg,
+    MigrateMsg, NativeDecimalsMismatchesResponse, NativeTokenDecimalsResponse,
+    OwnershipProposalResponse, PairCountResponse, PairCreationMode, PairDeprecationResponse,
+    PairsResponse, PendingCommissionRateResponse, QueryMsg, Role, RolesResponse,
+};
+use haloswap::pair::{
+    InstantiateMsg as PairInstantiateMsg, MigrateMsg as PairMigrateMsg, PauseStatus,
 };
-use haloswap::pair::{InstantiateMsg as PairInstantiateMsg, MigrateMsg as PairMigrateMsg};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:halo-factory";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+// an ownership proposal expires at most in 7 days
+const MAX_PROPOSAL_TTL: u64 = 604_800;
+// settings for the pagination of the native token pairs
+const MAX_PROPAGATION_LIMIT: u32 = 30;
+const DEFAULT_PROPAGATION_LIMIT: u32 = 10;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -32,13 +51,46 @@ pub fn instantiate(
     _env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, FactoryError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
+    let protocol_fee_rate = match msg.protocol_fee_rate {
+        Some(protocol_fee_rate) => protocol_fee_rate,
+        None => Decimal256::from_str(DEFAULT_PROTOCOL_FEE_RATE)?,
+    };
+    assert_protocol_fee_rate(protocol_fee_rate)?;
+
+    let flash_fee_rate = match msg.flash_fee_rate {
+        Some(flash_fee_rate) => flash_fee_rate,
+        None => Decimal256::from_str(DEFAULT_FLASH_FEE_RATE)?,
+    };
+    assert_flash_fee_rate(flash_fee_rate)?;
+
+    let fee_collector = match msg.fee_collector {
+        Some(fee_collector) => Some(
+            deps.api
+                .addr_canonicalize(deps.api.addr_validate(&fee_collector)?.as_str())?,
+        ),
+        None => None,
+    };
+
+    let pair_creation_mode = msg.pair_creation_mode.unwrap_or_default();
+    assert_pair_creation_mode(deps.as_ref(), &pair_creation_mode)?;
+
+    let min_liquidity = msg
+        .min_liquidity
+        .unwrap_or_else(|| Uint128::from(DEFAULT_MIN_LIQUIDITY));
+    assert_min_liquidity(min_liquidity)?;
+
     let config = Config {
         owner: deps.api.addr_canonicalize(info.sender.as_str())?,
         token_code_id: msg.token_code_id,
         pair_code_id: msg.pair_code_id,
+        protocol_fee_rate,
+        flash_fee_rate,
+        fee_collector,
+        pair_creation_mode,
+        min_liquidity,
     };
 
     CONFIG.save(deps.storage, &config)?;
@@ -47,57 +99,195 @@ pub fn instantiate(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, FactoryError> {
     match msg {
         ExecuteMsg::UpdateConfig {
-            owner,
             token_code_id,
             pair_code_id,
-        } => execute_update_config(deps, env, info, owner, token_code_id, pair_code_id),
-        ExecuteMsg::CreatePair {
-            asset_infos,
-            requirements,
-            commission_rate,
-            lp_token_info,
-        } => execute_create_pair(
+            protocol_fee_rate,
+            flash_fee_rate,
+            fee_collector,
+            pair_creation_mode,
+            min_liquidity,
+        } => execute_update_config(
             deps,
             env,
             info,
+            token_code_id,
+            pair_code_id,
+            protocol_fee_rate,
+            flash_fee_rate,
+            fee_collector,
+            pair_creation_mode,
+            min_liquidity,
+        ),
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
+            execute_propose_new_owner(deps, env, info, owner, expires_in)
+        }
+        ExecuteMsg::DropOwnershipProposal {} => execute_drop_ownership_proposal(deps, env, info),
+        ExecuteMsg::ClaimOwnership {} => execute_claim_ownership(deps, env, info),
+        ExecuteMsg::GrantRole { address, role } => {
+            execute_grant_role(deps, env, info, address, role)
+        }
+        ExecuteMsg::RevokeRole { address } => execute_revoke_role(deps, env, info, address),
+        ExecuteMsg::CreatePair {
             asset_infos,
             requirements,
             commission_rate,
             lp_token_info,
-        ),
+            pair_type,
+        } => {
+            let pairs = vec![CreatePairParams {
+                asset_infos,
+                requirements,
+                commission_rate,
+                lp_token_info,
+                pair_type,
+            }];
+            let paid_assets = native_paid_assets(&info);
+            let is_pair_creator = is_pair_creator(deps.as_ref(), &info.sender)?;
+
+            execute_create_pairs(deps, env, info.sender, is_pair_creator, paid_assets, pairs)
+        }
+        ExecuteMsg::CreatePairs { pairs } => {
+            let paid_assets = native_paid_assets(&info);
+            let is_pair_creator = is_pair_creator(deps.as_ref(), &info.sender)?;
+
+            execute_create_pairs(deps, env, info.sender, is_pair_creator, paid_assets, pairs)
+        }
         ExecuteMsg::AddNativeTokenDecimals { denom, decimals } => {
             execute_add_native_token_decimals(deps, env, info, denom, decimals)
         }
+        ExecuteMsg::PropagateNativeDecimals {
+            denom,
+            start_after,
+            limit,
+        } => execute_propagate_native_decimals(deps, env, info, denom, start_after, limit),
         ExecuteMsg::MigratePair { contract, code_id } => {
             execute_migrate_pair(deps, env, info, contract, code_id)
         }
+        ExecuteMsg::SetPauseStatus {
+            pair,
+            swaps,
+            deposits,
+        } => execute_set_pause_status(deps, env, info, pair, PauseStatus { swaps, deposits }),
+        ExecuteMsg::PropagatePauseStatus { start_after, limit } => {
+            execute_propagate_pause_status(deps, env, info, start_after, limit)
+        }
+        ExecuteMsg::UpdatePairCommissionRate {
+            pair,
+            commission_rate,
+            effective_at,
+        } => execute_update_pair_commission_rate(
+            deps,
+            env,
+            info,
+            pair,
+            commission_rate,
+            effective_at,
+        ),
+        ExecuteMsg::ApplyPairCommissionRate { pair } => {
+            execute_apply_pair_commission_rate(deps, env, info, pair)
+        }
+        ExecuteMsg::DeprecatePair {
+            asset_infos,
+            pair_type,
+            reason,
+        } => execute_deprecate_pair(
+            deps,
+            env,
+            info,
+            asset_infos,
+            pair_type.unwrap_or_default(),
+            reason,
+        ),
+        ExecuteMsg::DeregisterPair {
+            asset_infos,
+            pair_type,
+        } => execute_deregister_pair(deps, env, info, asset_infos, pair_type.unwrap_or_default()),
     }
 }
 
-// Only owner can execute it
+// the native funds sent with the message pay the pair creation fee
+fn native_paid_assets(info: &MessageInfo) -> Vec<Asset> {
+    info.funds
+        .iter()
+        .map(|coin| Asset {
+            info: AssetInfo::NativeToken {
+                denom: coin.denom.clone(),
+            },
+            amount: coin.amount,
+        })
+        .collect()
+}
+
+pub fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, FactoryError> {
+    let pairs = match from_binary(&cw20_msg.msg)? {
+        Cw20HookMsg::CreatePair {
+            asset_infos,
+            requirements,
+            commission_rate,
+            lp_token_info,
+            pair_type,
+        } => vec![CreatePairParams {
+            asset_infos,
+            requirements,
+            commission_rate,
+            lp_token_info,
+            pair_type,
+        }],
+        Cw20HookMsg::CreatePairs { pairs } => pairs,
+    };
+
+    // the received token pays the pair creation fee, the hook sender is not authenticated
+    // so its roles are ignored and the fee is always charged
+    let paid_assets = vec![Asset {
+        info: AssetInfo::Token {
+            contract_addr: info.sender.to_string(),
+        },
+        amount: cw20_msg.amount,
+    }];
+
+    let sender = deps.api.addr_validate(&cw20_msg.sender)?;
+    execute_create_pairs(deps, env, sender, false, paid_assets, pairs)
+}
+
+// Only owner can execute it, the fee manager can only update the fee settings
+#[allow(clippy::too_many_arguments)]
 pub fn execute_update_config(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    owner: Option<String>,
     token_code_id: Option<u64>,
     pair_code_id: Option<u64>,
-) -> StdResult<Response> {
+    protocol_fee_rate: Option<Decimal256>,
+    flash_fee_rate: Option<Decimal256>,
+    fee_collector: Option<String>,
+    pair_creation_mode: Option<PairCreationMode>,
+    min_liquidity: Option<Uint128>,
+) -> Result<Response, FactoryError> {
     let mut config: Config = CONFIG.load(deps.storage)?;
 
     // permission check
-    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
-        return Err(StdError::generic_err("unauthorized"));
-    }
-
-    if let Some(owner) = owner {
-        // validate address format
-        let _ = deps.api.addr_validate(&owner)?;
-
-        config.owner = deps.api.addr_canonicalize(&owner)?;
+    if token_code_id.is_some()
+        || pair_code_id.is_some()
+        || pair_creation_mode.is_some()
+        || min_liquidity.is_some()
+    {
+        assert_owner(deps.as_ref(), &config, &info.sender)?;
+    } else {
+        assert_role(deps.as_ref(), &config, &info.sender, Role::FeeManager)?;
     }
 
     if let Some(token_code_id) = token_code_id {
@@ -108,52 +298,327 @@ pub fn execute_update_config(
         config.pair_code_id = pair_code_id;
     }
 
+    // the new protocol fee rate is only applied to the pairs created afterwards
+    if let Some(protocol_fee_rate) = protocol_fee_rate {
+        assert_protocol_fee_rate(protocol_fee_rate)?;
+        config.protocol_fee_rate = protocol_fee_rate;
+    }
+
+    // the new flash fee rate is only applied to the pairs created afterwards
+    if let Some(flash_fee_rate) = flash_fee_rate {
+        assert_flash_fee_rate(flash_fee_rate)?;
+        config.flash_fee_rate = flash_fee_rate;
+    }
+
+    if let Some(fee_collector) = fee_collector {
+        // validate address format
+        let _ = deps.api.addr_validate(&fee_collector)?;
+
+        config.fee_collector = Some(deps.api.addr_canonicalize(&fee_collector)?);
+    }
+
+    if let Some(pair_creation_mode) = pair_creation_mode {
+        assert_pair_creation_mode(deps.as_ref(), &pair_creation_mode)?;
+        config.pair_creation_mode = pair_creation_mode;
+    }
+
+    // the new minimum liquidity is only applied to the pairs created afterwards
+    if let Some(min_liquidity) = min_liquidity {
+        assert_min_liquidity(min_liquidity)?;
+        config.min_liquidity = min_liquidity;
+    }
+
     CONFIG.save(deps.storage, &config)?;
 
     Ok(Response::new().add_attribute("action", "update_config"))
 }
 
-// Only owner of the factory can execute it to create swap pair
-pub fn execute_create_pair(
+// Only owner can execute it
+pub fn execute_propose_new_owner(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    asset_infos: [AssetInfo; 2],
-    requirements: CreatePairRequirements,
-    commission_rate: Option<Decimal256>,
-    lp_token_info: LPTokenInfo,
-) -> StdResult<Response> {
+    owner: String,
+    expires_in: u64,
+) -> Result<Response, FactoryError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    assert_owner(deps.as_ref(), &config, &info.sender)?;
+
+    let new_owner = deps.api.addr_validate(&owner)?;
+    if deps.api.addr_canonicalize(new_owner.as_str())? == config.owner {
+        return Err(FactoryError::NewOwnerIsCurrentOwner {});
+    }
+
+    if expires_in > MAX_PROPOSAL_TTL {
+        return Err(FactoryError::InvalidOwnershipProposalExpiry {
+            max_expires_in: MAX_PROPOSAL_TTL,
+        });
+    }
+
+    let expires_at = env.block.time.plus_seconds(expires_in);
+    OWNERSHIP_PROPOSAL.save(
+        deps.storage,
+        &OwnershipProposal {
+            owner: new_owner.clone(),
+            expires_at,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "propose_new_owner"),
+        ("new_owner", new_owner.as_str()),
+        ("expires_at", &expires_at.seconds().to_string()),
+    ]))
+}
+
+// Only owner can execute it
+pub fn execute_drop_ownership_proposal(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, FactoryError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    assert_owner(deps.as_ref(), &config, &info.sender)?;
+
+    OWNERSHIP_PROPOSAL.remove(deps.storage);
+
+    Ok(Response::new().add_attribute("action", "drop_ownership_proposal"))
+}
+
+// Only the proposed owner can execute it before the proposal expires
+pub fn execute_claim_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, FactoryError> {
+    let proposal = OWNERSHIP_PROPOSAL
+        .may_load(deps.storage)?
+        .ok_or(FactoryError::OwnershipProposalNotFound {})?;
+
+    // permission check
+    if info.sender != proposal.owner {
+        return Err(FactoryError::Unauthorized {});
+    }
+
+    if env.block.time > proposal.expires_at {
+        return Err(FactoryError::OwnershipProposalExpired {});
+    }
+
+    CONFIG.update(deps.storage, |mut config| -> StdResult<_> {
+        config.owner = deps.api.addr_canonicalize(proposal.owner.as_str())?;
+        Ok(config)
+    })?;
+    OWNERSHIP_PROPOSAL.remove(deps.storage);
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "claim_ownership"),
+        ("new_owner", proposal.owner.as_str()),
+    ]))
+}
+
+// Only owner can execute it
+pub fn execute_grant_role(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    address: String,
+    role: Role,
+) -> Result<Response, FactoryError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    assert_owner(deps.as_ref(), &config, &info.sender)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    ROLES.save(deps.storage, &address, &role)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "grant_role"),
+        ("address", address.as_str()),
+        ("role", &role.to_string()),
+    ]))
+}
+
+// Only owner can execute it
+pub fn execute_revoke_role(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, FactoryError> {
     let config: Config = CONFIG.load(deps.storage)?;
 
     // permission check
-    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
-        return Err(StdError::generic_err("unauthorized"));
+    assert_owner(deps.as_ref(), &config, &info.sender)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    ROLES.remove(deps.storage, &address);
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "revoke_role"),
+        ("address", address.as_str()),
+    ]))
+}
+
+fn assert_owner(deps: Deps, config: &Config, sender: &Addr) -> Result<(), FactoryError> {
+    if deps.api.addr_canonicalize(sender.as_str())? != config.owner {
+        return Err(FactoryError::Unauthorized {});
+    }
+
+    Ok(())
+}
+
+// the owner is allowed to do everything the roles are
+fn assert_role(deps: Deps, config: &Config, sender: &Addr, role: Role) -> Result<(), FactoryError> {
+    if assert_owner(deps, config, sender).is_ok()
+        || ROLES.may_load(deps.storage, sender)? == Some(role)
+    {
+        return Ok(());
+    }
+
+    Err(FactoryError::Unauthorized {})
+}
+
+// the owner of the factory has every role
+fn is_pair_creator(deps: Deps, sender: &Addr) -> Result<bool, FactoryError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    Ok(assert_role(deps, &config, sender, Role::PairCreator).is_ok())
+}
+
+// Only owner of the factory or the pair creators can execute it to create swap pairs,
+// anyone else can in the permissionless mode by paying the pair creation fee of each pair
+pub fn execute_create_pairs(
+    mut deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    is_pair_creator: bool,
+    paid_assets: Vec<Asset>,
+    pairs: Vec<CreatePairParams>,
+) -> Result<Response, FactoryError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    if pairs.is_empty() {
+        return Err(FactoryError::NoPairs {});
+    }
+
+    // permission check, the funds must pay the exact fee so that none are kept by the factory
+    let mut messages: Vec<CosmosMsg> = vec![];
+    match &config.pair_creation_mode {
+        _ if is_pair_creator => {
+            if !paid_assets.is_empty() {
+                return Err(FactoryError::UnexpectedFunds {});
+            }
+        }
+        PairCreationMode::OwnerOnly => {
+            return Err(FactoryError::Unauthorized {});
+        }
+        PairCreationMode::Permissionless { fee } => {
+            let fee = Asset {
+                info: fee.info.clone(),
+                amount: fee
+                    .amount
+                    .checked_mul(Uint128::from(pairs.len() as u128))
+                    .map_err(StdError::from)?,
+            };
+            if paid_assets.len() != 1
+                || !paid_assets[0].info.equal(&fee.info)
+                || paid_assets[0].amount != fee.amount
+            {
+                return Err(FactoryError::InvalidPairCreationFee {
+                    fee: fee.to_string(),
+                });
+            }
+
+            let fee_collector = match &config.fee_collector {
+                Some(fee_collector) => deps.api.addr_humanize(fee_collector)?,
+                None => return Err(FactoryError::FeeCollectorNotSet {}),
+            };
+            messages.push(fee.into_msg(fee_collector)?);
+        }
+    }
+
+    let mut response = Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "create_pair");
+    for pair in pairs {
+        response = response.add_attributes(vec![
+            (
+                "pair",
+                format!("{}-{}", pair.asset_infos[0], pair.asset_infos[1]),
+            ),
+            (
+                "pair_type",
+                pair.pair_type.clone().unwrap_or_default().to_string(),
+            ),
+        ]);
+        let msg = create_pair_msg(deps.branch(), &env, &config, &sender, pair)?;
+        response = response.add_submessage(msg);
+    }
+
+    Ok(response)
+}
+
+// the pair is kept under the reply id of its instantiation until it is registered
+fn create_pair_msg(
+    deps: DepsMut,
+    env: &Env,
+    config: &Config,
+    sender: &Addr,
+    pair: CreatePairParams,
+) -> Result<SubMsg, FactoryError> {
+    let CreatePairParams {
+        asset_infos,
+        mut requirements,
+        commission_rate,
+        lp_token_info,
+        pair_type,
+    } = pair;
+    let pair_type = pair_type.unwrap_or_default();
+
+    // only the creator can provide the first liquidity by default
+    if let PairCreationMode::Permissionless { .. } = config.pair_creation_mode {
+        if requirements.whitelist.is_empty() {
+            requirements.whitelist = vec![sender.clone()];
+        }
     }
 
     // don't allow to create pair with same token
     if asset_infos[0] == asset_infos[1] {
-        return Err(StdError::generic_err("same asset"));
+        return Err(FactoryError::IdenticalAssets {});
     }
 
-    // commission rate must be between 0 and 1 equivalents to 0% to 100%
-    if let Some(commission_rate) = commission_rate {
-        if commission_rate > Decimal256::one() {
-            return Err(StdError::generic_err(
-                "commission rate must be between 0 and 1 (equivalents to 0% to 100%)",
-            ));
-        }
+    // commission rate must be less than 1 equivalents to 0% up to 100%, which would take the whole return
+    let commission_rate = match commission_rate {
+        Some(commission_rate) => commission_rate,
+        None => Decimal256::from_str(DEFAULT_COMMISSION_RATE)?,
+    };
+    if commission_rate >= Decimal256::one() {
+        return Err(FactoryError::InvalidCommissionRate {});
     }
 
     let asset_1_decimal =
         match asset_infos[0].query_decimals(env.contract.address.clone(), &deps.querier) {
             Ok(decimal) => decimal,
-            Err(_) => return Err(StdError::generic_err("asset1 is invalid")),
+            Err(_) => {
+                return Err(FactoryError::InvalidAsset {
+                    asset: asset_infos[0].to_string(),
+                })
+            }
         };
 
     let asset_2_decimal =
         match asset_infos[1].query_decimals(env.contract.address.clone(), &deps.querier) {
             Ok(decimal) => decimal,
-            Err(_) => return Err(StdError::generic_err("asset2 is invalid")),
+            Err(_) => {
+                return Err(FactoryError::InvalidAsset {
+                    asset: asset_infos[1].to_string(),
+                })
+            }
         };
 
     let raw_infos = [
@@ -163,49 +628,101 @@ pub fn execute_create_pair(
 
     let asset_decimals = [asset_1_decimal, asset_2_decimal];
 
-    let pair_key = pair_key(&raw_infos);
-    if let Ok(Some(_)) = PAIRS.may_load(deps.storage, &pair_key) {
-        return Err(StdError::generic_err("Pair already exists"));
+    // the pair must be neither registered nor being created
+    let pair_key = pair_key(&raw_infos, &pair_type);
+    if load_pair(deps.storage, &pair_key)?.is_some() {
+        return Err(FactoryError::PairAlreadyExists {});
+    }
+    for item in TMP_PAIR_INFOS.range(deps.storage, None, None, Order::Ascending) {
+        let (_, tmp_pair_info) = item?;
+        if tmp_pair_info.pair_key == pair_key {
+            return Err(FactoryError::PairAlreadyExists {});
+        }
     }
 
-    TMP_PAIR_INFO.save(
+    let reply_id = next_reply_id(deps.storage)?;
+    TMP_PAIR_INFOS.save(
         deps.storage,
+        reply_id,
         &TmpPairInfo {
             pair_key,
             asset_infos: raw_infos,
             asset_decimals,
+            pair_type: pair_type.clone(),
         },
     )?;
 
-    Ok(Response::new()
-        .add_attributes(vec![
-            ("action", "create_pair"),
-            ("pair", &format!("{}-{}", asset_infos[0], asset_infos[1])),
-        ])
-        .add_submessage(SubMsg {
-            id: 1,
-            gas_limit: None,
-            msg: CosmosMsg::Wasm(WasmMsg::Instantiate {
-                code_id: config.pair_code_id,
-                funds: vec![],
-                admin: Some(env.contract.address.to_string()),
-                label: "pair".to_string(),
-                msg: to_binary(&PairInstantiateMsg {
-                    asset_infos,
-                    token_code_id: config.token_code_id,
-                    asset_decimals,
-                    requirements,
-                    commission_rate: commission_rate
-                        .unwrap_or_else(|| Decimal256::from_str(DEFAULT_COMMISSION_RATE).unwrap()),
-                    lp_token_info: LPTokenInfo {
-                        lp_token_name: lp_token_info.lp_token_name,
-                        lp_token_symbol: lp_token_info.lp_token_symbol,
-                        lp_token_decimals: lp_token_info.lp_token_decimals,
-                    },
-                })?,
-            }),
-            reply_on: ReplyOn::Success,
-        }))
+    Ok(SubMsg {
+        id: reply_id,
+        gas_limit: None,
+        msg: CosmosMsg::Wasm(WasmMsg::Instantiate {
+            code_id: config.pair_code_id,
+            funds: vec![],
+            admin: Some(env.contract.address.to_string()),
+            label: "pair".to_string(),
+            msg: to_binary(&PairInstantiateMsg {
+                asset_infos,
+                token_code_id: config.token_code_id,
+                asset_decimals,
+                requirements,
+                commission_rate,
+                protocol_fee_rate: config.protocol_fee_rate,
+                flash_fee_rate: config.flash_fee_rate,
+                lp_token_info: LPTokenInfo {
+                    lp_token_name: lp_token_info.lp_token_name,
+                    lp_token_symbol: lp_token_info.lp_token_symbol,
+                    lp_token_decimals: lp_token_info.lp_token_decimals,
+                },
+                pair_type,
+                min_liquidity: config.min_liquidity,
+            })?,
+        }),
+        reply_on: ReplyOn::Success,
+    })
+}
+// the pair creation fee must be a valid asset
+fn assert_pair_creation_mode(
+    deps: Deps,
+    pair_creation_mode: &PairCreationMode,
+) -> Result<(), FactoryError> {
+    if let PairCreationMode::Permissionless { fee } = pair_creation_mode {
+        if fee.amount.is_zero() {
+            return Err(FactoryError::ZeroPairCreationFee {});
+        }
+
+        if let AssetInfo::Token { contract_addr } = &fee.info {
+            deps.api.addr_validate(contract_addr)?;
+        }
+    }
+
+    Ok(())
+}
+
+// some LP token must be locked to keep the share price from being inflated
+fn assert_min_liquidity(min_liquidity: Uint128) -> Result<(), FactoryError> {
+    if min_liquidity.is_zero() {
+        return Err(FactoryError::ZeroMinLiquidity {});
+    }
+
+    Ok(())
+}
+
+// the flash fee is taken out of the amounts paid back, so it must be lower than 1
+fn assert_flash_fee_rate(flash_fee_rate: Decimal256) -> Result<(), FactoryError> {
+    if flash_fee_rate >= Decimal256::one() {
+        return Err(FactoryError::InvalidFlashFeeRate {});
+    }
+
+    Ok(())
+}
+
+// protocol fee rate is a share of the commission, so it must be between 0 and 1
+fn assert_protocol_fee_rate(protocol_fee_rate: Decimal256) -> Result<(), FactoryError> {
+    if protocol_fee_rate > Decimal256::one() {
+        return Err(FactoryError::InvalidProtocolFeeRate {});
+    }
+
+    Ok(())
 }
 
 pub fn execute_add_native_token_decimals(
@@ -214,109 +731,168 @@ pub fn execute_add_native_token_decimals(
     info: MessageInfo,
     denom: String,
     decimals: u8,
-) -> StdResult<Response> {
+) -> Result<Response, FactoryError> {
     let config: Config = CONFIG.load(deps.storage)?;
-    let mut res: Response = Response::new();
-    let is_native_exist: bool = ALLOW_NATIVE_TOKENS
-        .may_load(deps.storage, denom.as_bytes())?
-        .is_some();
 
     // permission check
-    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
-        return Err(StdError::generic_err("unauthorized"));
-    }
+    assert_role(deps.as_ref(), &config, &info.sender, Role::PairCreator)?;
 
     let balance = query_balance(&deps.querier, env.contract.address, denom.to_string())?;
     if balance.is_zero() {
-        return Err(StdError::generic_err(
-            "a balance greater than zero is required by the factory for verification",
-        ));
+        return Err(FactoryError::NativeTokenBalanceRequired {});
     }
 
     // Add the native token decimals to the allow list
     add_allow_native_token(deps.storage, denom.to_string(), decimals)?;
 
-    // Update the native token decimals for the existing pairs
-    let pair_infos = read_pairs(deps.storage, deps.api, None, None)?;
-
-    // If the native token is already exist, then update the decimals for the existing pairs
-    if is_native_exist {
-        // Messages to update the native token decimals for the existing pairs
-        let mut messages: Vec<CosmosMsg> = vec![];
-
-        for pair_info in pair_infos {
-            // Get the pair key from the pair info
-            let pair_key = pair_key(&[
-                pair_info.asset_infos[0].to_raw(deps.api)?,
-                pair_info.asset_infos[1].to_raw(deps.api)?,
-            ]);
-
-            // Get raw pair info from the pair key
-            let pair_info_raw: PairInfoRaw = PAIRS.load(deps.storage, &pair_key)?;
-
-            if pair_info.asset_infos[0].is_native_token()
-                && pair_info.asset_infos[0]
-                    .query_denom_of_native_token()
-                    .unwrap()
-                    == denom
-            {
-                PAIRS.save(
-                    deps.storage,
-                    &pair_key,
-                    &PairInfoRaw {
-                        asset_decimals: [decimals, pair_info_raw.asset_decimals[1]],
-                        ..pair_info_raw.clone()
-                    },
-                )?;
-                // Update the pair contract by calling the update_native_token_decimals msg
-                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                    contract_addr: deps
-                        .api
-                        .addr_humanize(&pair_info_raw.contract_addr)?
-                        .to_string(),
-                    msg: to_binary(&haloswap::pair::ExecuteMsg::UpdateNativeTokenDecimals {
-                        denom: denom.clone(),
-                        asset_decimals: [decimals, pair_info_raw.asset_decimals[1]],
-                    })?,
-                    funds: vec![],
-                }));
-            }
-            if pair_info.asset_infos[1].is_native_token()
-                && pair_info.asset_infos[1]
-                    .query_denom_of_native_token()
-                    .unwrap()
-                    == denom
-            {
-                PAIRS.save(
-                    deps.storage,
-                    &pair_key,
-                    &PairInfoRaw {
-                        asset_decimals: [pair_info_raw.asset_decimals[0], decimals],
-                        ..pair_info_raw.clone()
-                    },
-                )?;
-                // Update the pair contract by calling the update_native_token_decimals msg
-                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                    contract_addr: deps
-                        .api
-                        .addr_humanize(&pair_info_raw.contract_addr)?
-                        .to_string(),
-                    msg: to_binary(&haloswap::pair::ExecuteMsg::UpdateNativeTokenDecimals {
-                        denom: denom.clone(),
-                        asset_decimals: [pair_info_raw.asset_decimals[0], decimals],
-                    })?,
-                    funds: vec![],
-                }));
-            }
-        }
-        res = res.add_messages(messages);
-    }
+    // Update the native token decimals for the first existing pairs,
+    // the rest are updated by PropagateNativeDecimals
+    let (messages, next_start_after) =
+        propagate_native_decimals(deps, &denom, decimals, None, None)?;
 
-    Ok(res.add_attributes(vec![
+    let mut res = Response::new().add_messages(messages).add_attributes(vec![
         ("action", "add_allow_native_token"),
         ("denom", &denom),
         ("decimals", &decimals.to_string()),
-    ]))
+    ]);
+    if let Some(next_start_after) = next_start_after {
+        res = res.add_attribute("next_start_after", next_start_after);
+    }
+
+    Ok(res)
+}
+
+// Anyone can execute it to update the pairs to the registered decimals
+pub fn execute_propagate_native_decimals(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    denom: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Response, FactoryError> {
+    let decimals = ALLOW_NATIVE_TOKENS
+        .may_load(deps.storage, denom.as_bytes())?
+        .ok_or_else(|| FactoryError::NativeTokenDecimalsNotFound {
+            denom: denom.clone(),
+        })?;
+
+    let start_after = start_after_pair_id(deps.as_ref(), start_after)?;
+    let (messages, next_start_after) =
+        propagate_native_decimals(deps, &denom, decimals, start_after, limit)?;
+
+    let mut res = Response::new().add_attributes(vec![
+        ("action", "propagate_native_decimals"),
+        ("denom", &denom),
+        ("decimals", &decimals.to_string()),
+        ("updated_pairs", &messages.len().to_string()),
+    ]);
+    if let Some(next_start_after) = next_start_after {
+        res = res.add_attribute("next_start_after", next_start_after);
+    }
+
+    Ok(res.add_messages(messages))
+}
+
+// update the stored pair infos and the pairs of the native token whose decimals differ,
+// returns the messages updating the pairs and the pair to start after for the next page
+fn propagate_native_decimals(
+    deps: DepsMut,
+    denom: &str,
+    decimals: u8,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<(Vec<CosmosMsg>, Option<String>), FactoryError> {
+    let limit = limit
+        .unwrap_or(DEFAULT_PROPAGATION_LIMIT)
+        .min(MAX_PROPAGATION_LIMIT);
+    let native_token_pairs = read_pairs_by_asset(
+        deps.storage,
+        &AssetInfoRaw::NativeToken {
+            denom: denom.to_string(),
+        },
+        start_after,
+        Some(limit),
+        true,
+    )?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for (pair_id, pair_info_raw) in native_token_pairs.iter() {
+        let asset_decimals = native_decimals(pair_info_raw, denom, decimals);
+        if asset_decimals == pair_info_raw.asset_decimals {
+            continue;
+        }
+
+        pairs().save(
+            deps.storage,
+            *pair_id,
+            &PairInfoRaw {
+                asset_decimals,
+                ..pair_info_raw.clone()
+            },
+        )?;
+
+        // Update the pair contract by calling the update_native_token_decimals msg
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps
+                .api
+                .addr_humanize(&pair_info_raw.contract_addr)?
+                .to_string(),
+            msg: to_binary(&haloswap::pair::ExecuteMsg::UpdateNativeTokenDecimals {
+                denom: denom.to_string(),
+                asset_decimals,
+            })?,
+            funds: vec![],
+        }));
+    }
+
+    let next_start_after = next_start_after(deps.api, &native_token_pairs, limit)?;
+
+    Ok((messages, next_start_after))
+}
+
+// a full page may be followed by more pairs, which are after the last pair of the page
+fn next_start_after(
+    api: &dyn Api,
+    pairs: &[(u64, PairInfoRaw)],
+    limit: u32,
+) -> StdResult<Option<String>> {
+    match pairs.last() {
+        Some((_, pair_info)) if pairs.len() == limit as usize => Ok(Some(
+            api.addr_humanize(&pair_info.contract_addr)?.to_string(),
+        )),
+        _ => Ok(None),
+    }
+}
+
+// the id of the pair to start after, the pair must be registered
+fn start_after_pair_id(
+    deps: Deps,
+    start_after: Option<String>,
+) -> Result<Option<u64>, FactoryError> {
+    match start_after {
+        Some(start_after) => {
+            let pair_addr = deps.api.addr_canonicalize(&start_after)?;
+            match load_pair_by_address(deps.storage, &pair_addr)? {
+                Some((pair_id, _)) => Ok(Some(pair_id)),
+                None => Err(FactoryError::PairNotRegistered {}),
+            }
+        }
+        None => Ok(None),
+    }
+}
+
+// the decimals of the pair with the registered decimals of the native token
+fn native_decimals(pair_info: &PairInfoRaw, denom: &str, decimals: u8) -> [u8; 2] {
+    let mut asset_decimals = pair_info.asset_decimals;
+    for (i, asset_info) in pair_info.asset_infos.iter().enumerate() {
+        if matches!(asset_info, AssetInfoRaw::NativeToken { denom: pair_denom } if pair_denom == denom)
+        {
+            asset_decimals[i] = decimals;
+        }
+    }
+
+    asset_decimals
 }
 
 pub fn execute_migrate_pair(
@@ -325,13 +901,11 @@ pub fn execute_migrate_pair(
     info: MessageInfo,
     contract: String,
     code_id: Option<u64>,
-) -> StdResult<Response> {
+) -> Result<Response, FactoryError> {
     let config: Config = CONFIG.load(deps.storage)?;
 
     // permission check
-    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
-        return Err(StdError::generic_err("unauthorized"));
-    }
+    assert_owner(deps.as_ref(), &config, &info.sender)?;
 
     let code_id = code_id.unwrap_or(config.pair_code_id);
 
@@ -344,12 +918,343 @@ pub fn execute_migrate_pair(
     )
 }
 
+// Only owner or guardian can execute it
+pub fn execute_set_pause_status(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    pair: Option<String>,
+    pause_status: PauseStatus,
+) -> Result<Response, FactoryError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    assert_role(deps.as_ref(), &config, &info.sender, Role::Guardian)?;
+
+    // a pair is paused by its own status or by the global one
+    let (pair, messages, next_start_after) = if let Some(pair) = pair {
+        let pair_addr = deps.api.addr_validate(&pair)?;
+        let pair_id = registered_pair_id(deps.as_ref(), &pair_addr)?;
+
+        // the deprecated pairs stay paused
+        if DEPRECATED_PAIRS.has(deps.storage, pair_id) {
+            return Err(FactoryError::PairDeprecated {});
+        }
+
+        PAIR_PAUSE_STATUS.save(deps.storage, &pair_addr, &pause_status)?;
+        let global_status = PAUSE_STATUS.may_load(deps.storage)?.unwrap_or_default();
+        let message = set_pause_msg(&pair_addr, &global_status, &pause_status)?;
+        (pair, vec![message], None)
+    } else {
+        // the first pairs are updated, the rest by PropagatePauseStatus
+        PAUSE_STATUS.save(deps.storage, &pause_status)?;
+        let (messages, next_start_after) =
+            propagate_pause_status(deps.as_ref(), &pause_status, None, None)?;
+        ("all".to_string(), messages, next_start_after)
+    };
+
+    let mut res = Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "set_pause_status"),
+        ("pair", &pair),
+        ("swaps", &pause_status.swaps.to_string()),
+        ("deposits", &pause_status.deposits.to_string()),
+    ]);
+    if let Some(next_start_after) = next_start_after {
+        res = res.add_attribute("next_start_after", next_start_after);
+    }
+
+    Ok(res)
+}
+
+// Anyone can execute it to update the pairs to the global pause status
+pub fn execute_propagate_pause_status(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Response, FactoryError> {
+    let global_status = PAUSE_STATUS.may_load(deps.storage)?.unwrap_or_default();
+
+    let start_after = start_after_pair_id(deps.as_ref(), start_after)?;
+    let (messages, next_start_after) =
+        propagate_pause_status(deps.as_ref(), &global_status, start_after, limit)?;
+
+    let mut res = Response::new().add_attributes(vec![
+        ("action", "propagate_pause_status"),
+        ("swaps", &global_status.swaps.to_string()),
+        ("deposits", &global_status.deposits.to_string()),
+        ("updated_pairs", &messages.len().to_string()),
+    ]);
+    if let Some(next_start_after) = next_start_after {
+        res = res.add_attribute("next_start_after", next_start_after);
+    }
+
+    Ok(res.add_messages(messages))
+}
+
+// returns the messages pausing the pairs after `start_after` by their own status or by the
+// global one and the pair to start after for the next page, the deprecated pairs stay paused
+fn propagate_pause_status(
+    deps: Deps,
+    global_status: &PauseStatus,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<(Vec<CosmosMsg>, Option<String>), FactoryError> {
+    let limit = limit
+        .unwrap_or(DEFAULT_PROPAGATION_LIMIT)
+        .min(MAX_PROPAGATION_LIMIT);
+    let page = pairs()
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit as usize)
+        .collect::<StdResult<Vec<(u64, PairInfoRaw)>>>()?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for (pair_id, pair_info) in page.iter() {
+        if DEPRECATED_PAIRS.has(deps.storage, *pair_id) {
+            continue;
+        }
+
+        let pair_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
+        let pair_status = PAIR_PAUSE_STATUS
+            .may_load(deps.storage, &pair_addr)?
+            .unwrap_or_default();
+        messages.push(set_pause_msg(&pair_addr, global_status, &pair_status)?);
+    }
+
+    let next_start_after = next_start_after(deps.api, &page, limit)?;
+
+    Ok((messages, next_start_after))
+}
+
+// Only owner or fee manager can execute it
+pub fn execute_update_pair_commission_rate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pair: String,
+    commission_rate: Decimal256,
+    effective_at: Option<Timestamp>,
+) -> Result<Response, FactoryError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    assert_role(deps.as_ref(), &config, &info.sender, Role::FeeManager)?;
+
+    // commission rate must be less than 1 equivalents to 0% up to 100%, which would take the whole return
+    if commission_rate >= Decimal256::one() {
+        return Err(FactoryError::InvalidCommissionRate {});
+    }
+
+    let pair_addr = deps.api.addr_validate(&pair)?;
+    let pair_id = registered_pair_id(deps.as_ref(), &pair_addr)?;
+
+    // a new update replaces the pending one
+    PENDING_COMMISSION_RATES.remove(deps.storage, &pair_addr);
+
+    match effective_at {
+        Some(effective_at) => {
+            if effective_at <= env.block.time {
+                return Err(FactoryError::InvalidEffectiveTime {});
+            }
+
+            PENDING_COMMISSION_RATES.save(
+                deps.storage,
+                &pair_addr,
+                &PendingCommissionRate {
+                    commission_rate,
+                    effective_at,
+                },
+            )?;
+
+            Ok(Response::new().add_attributes(vec![
+                ("action", "update_pair_commission_rate"),
+                ("pair", pair.as_str()),
+                ("commission_rate", &commission_rate.to_string()),
+                ("effective_at", &effective_at.seconds().to_string()),
+            ]))
+        }
+        None => {
+            let msg = update_pair_commission_rate(deps, &pair_addr, pair_id, commission_rate)?;
+
+            Ok(Response::new().add_message(msg).add_attributes(vec![
+                ("action", "update_pair_commission_rate"),
+                ("pair", pair.as_str()),
+                ("commission_rate", &commission_rate.to_string()),
+                ("effective_at", &env.block.time.seconds().to_string()),
+            ]))
+        }
+    }
+}
+
+// Anyone can execute it once the pending commission rate is effective
+pub fn execute_apply_pair_commission_rate(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    pair: String,
+) -> Result<Response, FactoryError> {
+    let pair_addr = deps.api.addr_validate(&pair)?;
+    let pending = PENDING_COMMISSION_RATES
+        .may_load(deps.storage, &pair_addr)?
+        .ok_or(FactoryError::PendingCommissionRateNotFound {})?;
+
+    if env.block.time < pending.effective_at {
+        return Err(FactoryError::CommissionRateNotEffective {});
+    }
+
+    let pair_id = registered_pair_id(deps.as_ref(), &pair_addr)?;
+    PENDING_COMMISSION_RATES.remove(deps.storage, &pair_addr);
+    let msg = update_pair_commission_rate(deps, &pair_addr, pair_id, pending.commission_rate)?;
+
+    Ok(Response::new().add_message(msg).add_attributes(vec![
+        ("action", "apply_pair_commission_rate"),
+        ("pair", pair.as_str()),
+        ("commission_rate", &pending.commission_rate.to_string()),
+    ]))
+}
+
+// Only owner can execute it, the deposits and the swaps of the pair are paused for good
+pub fn execute_deprecate_pair(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    asset_infos: [AssetInfo; 2],
+    pair_type: PairType,
+    reason: String,
+) -> Result<Response, FactoryError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    assert_owner(deps.as_ref(), &config, &info.sender)?;
+
+    let (pair_id, pair_info) = registered_pair(deps.as_ref(), &asset_infos, &pair_type)?;
+    if DEPRECATED_PAIRS.has(deps.storage, pair_id) {
+        return Err(FactoryError::PairDeprecated {});
+    }
+    DEPRECATED_PAIRS.save(deps.storage, pair_id, &reason)?;
+
+    let pair_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
+    let pause_status = PauseStatus {
+        swaps: true,
+        deposits: true,
+    };
+
+    Ok(Response::new()
+        .add_message(set_pause_msg(&pair_addr, &pause_status, &pause_status)?)
+        .add_attributes(vec![
+            ("action", "deprecate_pair"),
+            ("pair", pair_addr.as_str()),
+            ("reason", reason.as_str()),
+        ]))
+}
+
+// Only owner can execute it once the pair is deprecated
+pub fn execute_deregister_pair(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    asset_infos: [AssetInfo; 2],
+    pair_type: PairType,
+) -> Result<Response, FactoryError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    assert_owner(deps.as_ref(), &config, &info.sender)?;
+
+    let (pair_id, pair_info) = registered_pair(deps.as_ref(), &asset_infos, &pair_type)?;
+    if !DEPRECATED_PAIRS.has(deps.storage, pair_id) {
+        return Err(FactoryError::PairNotDeprecated {});
+    }
+
+    let pair_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
+    remove_pair(deps.storage, pair_id)?;
+    PAIR_PAUSE_STATUS.remove(deps.storage, &pair_addr);
+    PENDING_COMMISSION_RATES.remove(deps.storage, &pair_addr);
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "deregister_pair"),
+        ("pair", pair_addr.as_str()),
+    ]))
+}
+
+// the pair of the assets must be registered, returns its id and info
+fn registered_pair(
+    deps: Deps,
+    asset_infos: &[AssetInfo; 2],
+    pair_type: &PairType,
+) -> Result<(u64, PairInfoRaw), FactoryError> {
+    let pair_key = pair_key(
+        &[
+            asset_infos[0].to_raw(deps.api)?,
+            asset_infos[1].to_raw(deps.api)?,
+        ],
+        pair_type,
+    );
+
+    load_pair(deps.storage, &pair_key)?.ok_or(FactoryError::PairNotRegistered {})
+}
+
+// update the stored pair info, then the pair itself
+fn update_pair_commission_rate(
+    deps: DepsMut,
+    pair_addr: &Addr,
+    pair_id: u64,
+    commission_rate: Decimal256,
+) -> Result<CosmosMsg, FactoryError> {
+    pairs().update(
+        deps.storage,
+        pair_id,
+        |pair_info| -> Result<_, FactoryError> {
+            let mut pair_info = pair_info.ok_or(FactoryError::PairNotRegistered {})?;
+            pair_info.commission_rate = commission_rate;
+            Ok(pair_info)
+        },
+    )?;
+
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: pair_addr.to_string(),
+        msg: to_binary(&haloswap::pair::ExecuteMsg::UpdateCommissionRate { commission_rate })?,
+        funds: vec![],
+    }))
+}
+
+// the pair must be created by this factory, returns its id
+fn registered_pair_id(deps: Deps, pair_addr: &Addr) -> Result<u64, FactoryError> {
+    let pair_addr = deps.api.addr_canonicalize(pair_addr.as_str())?;
+    match load_pair_by_address(deps.storage, &pair_addr)? {
+        Some((pair_id, _)) => Ok(pair_id),
+        None => Err(FactoryError::PairNotRegistered {}),
+    }
+}
+
+fn set_pause_msg(
+    pair_addr: &Addr,
+    global_status: &PauseStatus,
+    pair_status: &PauseStatus,
+) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: pair_addr.to_string(),
+        msg: to_binary(&haloswap::pair::ExecuteMsg::SetPause {
+            swaps: global_status.swaps || pair_status.swaps,
+            deposits: global_status.deposits || pair_status.deposits,
+        })?,
+        funds: vec![],
+    }))
+}
+
 /// This just stores the result for future query
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
-    let tmp_pair_info = TMP_PAIR_INFO.load(deps.storage)?;
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, FactoryError> {
+    let tmp_pair_info = TMP_PAIR_INFOS.load(deps.storage, msg.id)?;
+    TMP_PAIR_INFOS.remove(deps.storage, msg.id);
 
-    let reply = parse_reply_instantiate_data(msg).unwrap();
+    let reply = parse_reply_instantiate_data(msg)?;
 
     // let res: MsgInstantiateContractResponse =
     //     Message::parse_from_bytes(msg.result.unwrap().data.unwrap().as_slice()).map_err(|_| {
@@ -359,35 +1264,93 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
     let pair_contract = &reply.contract_address;
     let pair_info = query_pair_info_from_pair(&deps.querier, Addr::unchecked(pair_contract))?;
 
-    PAIRS.save(
-        deps.storage,
-        &tmp_pair_info.pair_key,
-        &PairInfoRaw {
-            liquidity_token: deps.api.addr_canonicalize(&pair_info.liquidity_token)?,
-            contract_addr: deps.api.addr_canonicalize(pair_contract)?,
-            asset_infos: tmp_pair_info.asset_infos,
-            asset_decimals: tmp_pair_info.asset_decimals,
-            requirements: pair_info.requirements,
-            commission_rate: Decimal256::from_str(&pair_info.commission_rate.to_string()).unwrap(),
-        },
-    )?;
+    let pair_info_raw = PairInfoRaw {
+        liquidity_token: deps.api.addr_canonicalize(&pair_info.liquidity_token)?,
+        contract_addr: deps.api.addr_canonicalize(pair_contract)?,
+        asset_infos: tmp_pair_info.asset_infos,
+        asset_decimals: tmp_pair_info.asset_decimals,
+        requirements: pair_info.requirements,
+        commission_rate: pair_info.commission_rate,
+        pair_type: tmp_pair_info.pair_type,
+    };
+    let pair_id = add_pair(deps.storage, &pair_info_raw)?;
 
-    Ok(Response::new().add_attributes(vec![
+    // the pairs created during a global pause start paused
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let global_status = PAUSE_STATUS.may_load(deps.storage)?.unwrap_or_default();
+    if global_status.swaps || global_status.deposits {
+        messages.push(set_pause_msg(
+            &Addr::unchecked(pair_contract),
+            &global_status,
+            &PauseStatus::default(),
+        )?);
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("pair_id", pair_id.to_string().as_str()),
         ("pair_contract_addr", pair_contract),
         ("liquidity_token_addr", &pair_info.liquidity_token),
     ]))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, FactoryError> {
     match msg {
-        QueryMsg::Config {} => to_binary(&query_config(deps)?),
-        QueryMsg::Pair { asset_infos } => to_binary(&query_pair(deps, asset_infos)?),
-        QueryMsg::Pairs { start_after, limit } => {
-            to_binary(&query_pairs(deps, start_after, limit)?)
+        QueryMsg::Config {} => Ok(to_binary(&query_config(deps)?)?),
+        QueryMsg::Pair {
+            asset_infos,
+            pair_type,
+        } => Ok(to_binary(&query_pair(deps, asset_infos, pair_type)?)?),
+        QueryMsg::Pairs {
+            start_after,
+            start_after_pair_type,
+            limit,
+            include_deprecated,
+        } => Ok(to_binary(&query_pairs(
+            deps,
+            start_after,
+            start_after_pair_type,
+            limit,
+            include_deprecated.unwrap_or_default(),
+        )?)?),
+        QueryMsg::PairsByAsset {
+            asset_info,
+            start_after,
+            limit,
+            include_deprecated,
+        } => Ok(to_binary(&query_pairs_by_asset(
+            deps,
+            asset_info,
+            start_after,
+            limit,
+            include_deprecated.unwrap_or_default(),
+        )?)?),
+        QueryMsg::PairDeprecation { pair } => Ok(to_binary(&query_pair_deprecation(deps, pair)?)?),
+        QueryMsg::PairByAddress { address } => {
+            Ok(to_binary(&query_pair_by_address(deps, address)?)?)
+        }
+        QueryMsg::PairByLpToken { lp_token } => {
+            Ok(to_binary(&query_pair_by_lp_token(deps, lp_token)?)?)
         }
+        QueryMsg::PairCount {} => Ok(to_binary(&query_pair_count(deps)?)?),
         QueryMsg::NativeTokenDecimals { denom } => {
-            to_binary(&query_native_token_decimal(deps, denom)?)
+            Ok(to_binary(&query_native_token_decimal(deps, denom)?)?)
+        }
+        QueryMsg::NativeDecimalsMismatches {
+            denom,
+            start_after,
+            limit,
+        } => Ok(to_binary(&query_native_decimals_mismatches(
+            deps,
+            denom,
+            start_after,
+            limit,
+        )?)?),
+        QueryMsg::PauseStatus { pair } => Ok(to_binary(&query_pause_status(deps, pair)?)?),
+        QueryMsg::Roles {} => Ok(to_binary(&query_roles(deps)?)?),
+        QueryMsg::OwnershipProposal {} => Ok(to_binary(&query_ownership_proposal(deps)?)?),
+        QueryMsg::PendingCommissionRate { pair } => {
+            Ok(to_binary(&query_pending_commission_rate(deps, pair)?)?)
         }
     }
 }
@@ -398,24 +1361,156 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         owner: deps.api.addr_humanize(&state.owner)?.to_string(),
         token_code_id: state.token_code_id,
         pair_code_id: state.pair_code_id,
+        protocol_fee_rate: state.protocol_fee_rate,
+        flash_fee_rate: state.flash_fee_rate,
+        fee_collector: match state.fee_collector {
+            Some(fee_collector) => Some(deps.api.addr_humanize(&fee_collector)?.to_string()),
+            None => None,
+        },
+        pair_creation_mode: state.pair_creation_mode,
+        min_liquidity: state.min_liquidity,
     };
 
     Ok(resp)
 }
 
-pub fn query_pair(deps: Deps, asset_infos: [AssetInfo; 2]) -> StdResult<PairInfo> {
-    let pair_key = pair_key(&[
-        asset_infos[0].to_raw(deps.api)?,
-        asset_infos[1].to_raw(deps.api)?,
-    ]);
-    let pair_info: PairInfoRaw = PAIRS.load(deps.storage, &pair_key)?;
-    pair_info.to_normal(deps.api)
+pub fn query_pause_status(deps: Deps, pair: Option<String>) -> StdResult<PauseStatus> {
+    let pause_status = match pair {
+        Some(pair) => PAIR_PAUSE_STATUS.may_load(deps.storage, &deps.api.addr_validate(&pair)?)?,
+        None => PAUSE_STATUS.may_load(deps.storage)?,
+    };
+
+    Ok(pause_status.unwrap_or_default())
+}
+
+pub fn query_roles(deps: Deps) -> StdResult<RolesResponse> {
+    let roles = ROLES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (address, role) = item?;
+            Ok(AddressRole {
+                address: address.to_string(),
+                role,
+            })
+        })
+        .collect::<StdResult<Vec<AddressRole>>>()?;
+
+    Ok(RolesResponse { roles })
+}
+
+pub fn query_ownership_proposal(deps: Deps) -> Result<OwnershipProposalResponse, FactoryError> {
+    let proposal = OWNERSHIP_PROPOSAL
+        .may_load(deps.storage)?
+        .ok_or(FactoryError::OwnershipProposalNotFound {})?;
+
+    Ok(OwnershipProposalResponse {
+        owner: proposal.owner.to_string(),
+        expires_at: proposal.expires_at,
+    })
+}
+
+pub fn query_pending_commission_rate(
+    deps: Deps,
+    pair: String,
+) -> Result<PendingCommissionRateResponse, FactoryError> {
+    let pending = PENDING_COMMISSION_RATES
+        .may_load(deps.storage, &deps.api.addr_validate(&pair)?)?
+        .ok_or(FactoryError::PendingCommissionRateNotFound {})?;
+
+    Ok(PendingCommissionRateResponse {
+        commission_rate: pending.commission_rate,
+        effective_at: pending.effective_at,
+    })
+}
+
+pub fn query_pair(
+    deps: Deps,
+    asset_infos: [AssetInfo; 2],
+    pair_type: Option<PairType>,
+) -> Result<PairInfo, FactoryError> {
+    let pair_key = pair_key(
+        &[
+            asset_infos[0].to_raw(deps.api)?,
+            asset_infos[1].to_raw(deps.api)?,
+        ],
+        &pair_type.unwrap_or_default(),
+    );
+    match load_pair(deps.storage, &pair_key)? {
+        // the deprecated pairs are not resolved, so that the router does not use them
+        Some((pair_id, _)) if DEPRECATED_PAIRS.has(deps.storage, pair_id) => {
+            Err(FactoryError::PairDeprecated {})
+        }
+        Some((_, pair_info)) => Ok(pair_info.to_normal(deps.api)?),
+        None => Err(FactoryError::PairNotRegistered {}),
+    }
+}
+
+pub fn query_pairs_by_asset(
+    deps: Deps,
+    asset_info: AssetInfo,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    include_deprecated: bool,
+) -> Result<PairsResponse, FactoryError> {
+    let start_after = start_after_pair_id(deps, start_after)?;
+    let pairs = read_pairs_by_asset(
+        deps.storage,
+        &asset_info.to_raw(deps.api)?,
+        start_after,
+        limit,
+        include_deprecated,
+    )?
+    .into_iter()
+    .map(|(_, pair_info)| pair_info.to_normal(deps.api))
+    .collect::<StdResult<Vec<PairInfo>>>()?;
+
+    Ok(PairsResponse { pairs })
+}
+
+pub fn query_pair_deprecation(
+    deps: Deps,
+    pair: String,
+) -> Result<PairDeprecationResponse, FactoryError> {
+    let contract_addr = deps
+        .api
+        .addr_canonicalize(deps.api.addr_validate(&pair)?.as_str())?;
+    let (pair_id, _) = load_pair_by_address(deps.storage, &contract_addr)?
+        .ok_or(FactoryError::PairNotRegistered {})?;
+    let reason = DEPRECATED_PAIRS
+        .may_load(deps.storage, pair_id)?
+        .ok_or(FactoryError::PairNotDeprecated {})?;
+
+    Ok(PairDeprecationResponse { reason })
+}
+
+pub fn query_pair_by_address(deps: Deps, address: String) -> Result<PairInfo, FactoryError> {
+    let contract_addr = deps.api.addr_canonicalize(&address)?;
+    match load_pair_by_address(deps.storage, &contract_addr)? {
+        Some((_, pair_info)) => Ok(pair_info.to_normal(deps.api)?),
+        None => Err(FactoryError::PairNotRegistered {}),
+    }
+}
+
+pub fn query_pair_by_lp_token(deps: Deps, lp_token: String) -> Result<PairInfo, FactoryError> {
+    let liquidity_token = deps.api.addr_canonicalize(&lp_token)?;
+    match load_pair_by_lp_token(deps.storage, &liquidity_token)? {
+        Some((_, pair_info)) => Ok(pair_info.to_normal(deps.api)?),
+        None => Err(FactoryError::PairNotRegistered {}),
+    }
+}
+
+pub fn query_pair_count(deps: Deps) -> StdResult<PairCountResponse> {
+    let count = PAIR_COUNT.may_load(deps.storage)?.unwrap_or_default();
+
+    Ok(PairCountResponse { count })
 }
 
 pub fn query_pairs(
     deps: Deps,
     start_after: Option<[AssetInfo; 2]>,
+    start_after_pair_type: Option<PairType>,
     limit: Option<u32>,
+    include_deprecated: bool,
 ) -> StdResult<PairsResponse> {
     let start_after = if let Some(start_after) = start_after {
         Some([
@@ -426,7 +1521,14 @@ pub fn query_pairs(
         None
     };
 
-    let pairs: Vec<PairInfo> = read_pairs(deps.storage, deps.api, start_after, limit)?;
+    let pairs: Vec<PairInfo> = read_pairs(
+        deps.storage,
+        deps.api,
+        start_after,
+        start_after_pair_type,
+        limit,
+        include_deprecated,
+    )?;
     let resp = PairsResponse { pairs };
 
     Ok(resp)
@@ -441,9 +1543,54 @@ pub fn query_native_token_decimal(
     Ok(NativeTokenDecimalsResponse { decimals })
 }
 
+pub fn query_native_decimals_mismatches(
+    deps: Deps,
+    denom: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<NativeDecimalsMismatchesResponse, FactoryError> {
+    let decimals = ALLOW_NATIVE_TOKENS.load(deps.storage, denom.as_bytes())?;
+    let start_after = start_after_pair_id(deps, start_after)?;
+    let limit = limit
+        .unwrap_or(DEFAULT_PROPAGATION_LIMIT)
+        .min(MAX_PROPAGATION_LIMIT);
+    let native_token_pairs = read_pairs_by_asset(
+        deps.storage,
+        &AssetInfoRaw::NativeToken {
+            denom: denom.clone(),
+        },
+        start_after,
+        Some(limit),
+        true,
+    )?;
+
+    let mut pairs: Vec<PairInfo> = vec![];
+    for (_, pair_info_raw) in native_token_pairs.iter() {
+        if native_decimals(pair_info_raw, &denom, decimals) != pair_info_raw.asset_decimals {
+            pairs.push(pair_info_raw.to_normal(deps.api)?);
+        }
+    }
+
+    let next_start_after = next_start_after(deps.api, &native_token_pairs, limit)?;
+
+    Ok(NativeDecimalsMismatchesResponse {
+        pairs,
+        next_start_after,
+    })
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
+    // the pairs created before the pairs were indexed get their ids in the order of their keys
+    let legacy_pairs = LEGACY_PAIRS
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Vec<u8>, PairInfoRaw)>>>()?;
+    for (pair_key, pair_info) in legacy_pairs.iter() {
+        add_pair(deps.storage, pair_info)?;
+        LEGACY_PAIRS.remove(deps.storage, pair_key);
+    }
+
     Ok(Response::default())
 }
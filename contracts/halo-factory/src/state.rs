@@ -1,35 +1,242 @@
+use bignumber::Decimal256;
 use cosmwasm_schema::cw_serde;
+use std::str::FromStr;
 
-use cosmwasm_std::{Api, CanonicalAddr, Order, StdResult, Storage};
-use cw_storage_plus::{Bound, Item, Map};
-use haloswap::asset::{AssetInfoRaw, PairInfo, PairInfoRaw};
+use cosmwasm_std::{Addr, Api, CanonicalAddr, Order, StdResult, Storage, Timestamp, Uint128};
+use cw_storage_plus::{
+    Bound, Index, IndexList, IndexedMap, Item, KeyDeserialize, Map, MultiIndex, UniqueIndex,
+};
+use haloswap::asset::{AssetInfoRaw, PairInfo, PairInfoRaw, PairType, DEFAULT_MIN_LIQUIDITY};
+use haloswap::factory::{PairCreationMode, Role};
+use haloswap::pair::PauseStatus;
 
 #[cw_serde]
 pub struct Config {
     pub owner: CanonicalAddr,
     pub pair_code_id: u64,
     pub token_code_id: u64,
+    /// Share of the commission of new pairs which is accrued for the protocol
+    #[serde(default)]
+    pub protocol_fee_rate: Decimal256,
+    /// Fee rate of the flash swaps of new pairs
+    #[serde(default = "default_flash_fee_rate")]
+    pub flash_fee_rate: Decimal256,
+    /// Receiver of the protocol fees collected by the pairs and of the pair creation fees
+    pub fee_collector: Option<CanonicalAddr>,
+    /// Who can create pairs
+    #[serde(default)]
+    pub pair_creation_mode: PairCreationMode,
+    /// Amount of LP token locked by the first provision of new pairs
+    #[serde(default = "default_min_liquidity")]
+    pub min_liquidity: Uint128,
+}
+
+fn default_min_liquidity() -> Uint128 {
+    Uint128::from(DEFAULT_MIN_LIQUIDITY)
+}
+
+fn default_flash_fee_rate() -> Decimal256 {
+    Decimal256::from_str(DEFAULT_FLASH_FEE_RATE).unwrap()
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
+
+#[cw_serde]
+pub struct OwnershipProposal {
+    pub owner: Addr,
+    pub expires_at: Timestamp,
+}
+
+pub const OWNERSHIP_PROPOSAL: Item<OwnershipProposal> = Item::new("ownership_proposal");
+
+// key : address / value: the role granted by the owner
+pub const ROLES: Map<&Addr, Role> = Map::new("roles");
+
+#[cw_serde]
+pub struct PendingCommissionRate {
+    pub commission_rate: Decimal256,
+    pub effective_at: Timestamp,
+}
+
+// key : pair contract address / value: the commission rate scheduled for the pair
+pub const PENDING_COMMISSION_RATES: Map<&Addr, PendingCommissionRate> =
+    Map::new("pending_commission_rates");
+
+// the pause status applied to all pairs
+pub const PAUSE_STATUS: Item<PauseStatus> = Item::new("pause_status");
+// key : pair contract address / value: the pause status applied to the pair only
+pub const PAIR_PAUSE_STATUS: Map<&Addr, PauseStatus> = Map::new("pair_pause_status");
 /// Default commission rate == 0.3%
 pub const DEFAULT_COMMISSION_RATE: &str = "0.003";
+/// Default protocol fee rate == 0% of the commission
+pub const DEFAULT_PROTOCOL_FEE_RATE: &str = "0";
+/// Default flash fee rate == 0.3% of the amounts paid back
+pub const DEFAULT_FLASH_FEE_RATE: &str = "0.003";
 
 #[cw_serde]
 pub struct TmpPairInfo {
     pub pair_key: Vec<u8>,
     pub asset_infos: [AssetInfoRaw; 2],
     pub asset_decimals: [u8; 2],
+    pub pair_type: PairType,
 }
 
-pub const TMP_PAIR_INFO: Item<TmpPairInfo> = Item::new("tmp_pair_info");
-pub const PAIRS: Map<&[u8], PairInfoRaw> = Map::new("pair_info");
+// key : reply id of the pair instantiation / value: the pair which is being created
+pub const TMP_PAIR_INFOS: Map<u64, TmpPairInfo> = Map::new("tmp_pair_infos");
+pub const LAST_REPLY_ID: Item<u64> = Item::new("last_reply_id");
+
+// the reply id of the next pair instantiation
+pub fn next_reply_id(storage: &mut dyn Storage) -> StdResult<u64> {
+    let reply_id = LAST_REPLY_ID.may_load(storage)?.unwrap_or_default() + 1;
+    LAST_REPLY_ID.save(storage, &reply_id)?;
 
-pub fn pair_key(asset_infos: &[AssetInfoRaw; 2]) -> Vec<u8> {
+    Ok(reply_id)
+}
+
+// the pairs keyed by the pair key, before they were indexed
+pub const LEGACY_PAIRS: Map<&[u8], PairInfoRaw> = Map::new("pair_info");
+
+// the number of registered pairs and the id of the last created pair
+pub const PAIR_COUNT: Item<u64> = Item::new("pair_count");
+pub const LAST_PAIR_ID: Item<u64> = Item::new("last_pair_id");
+
+pub struct PairIndexes<'a> {
+    // key : first asset of the pair
+    pub asset_0: MultiIndex<'a, Vec<u8>, PairInfoRaw, u64>,
+    // key : second asset of the pair
+    pub asset_1: MultiIndex<'a, Vec<u8>, PairInfoRaw, u64>,
+    // key : pair key
+    pub pair_key: UniqueIndex<'a, Vec<u8>, PairInfoRaw, u64>,
+    // key : pair contract address
+    pub contract_addr: UniqueIndex<'a, Vec<u8>, PairInfoRaw, u64>,
+    // key : liquidity token address
+    pub liquidity_token: UniqueIndex<'a, Vec<u8>, PairInfoRaw, u64>,
+}
+
+impl<'a> IndexList<PairInfoRaw> for PairIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<PairInfoRaw>> + '_> {
+        let v: Vec<&dyn Index<PairInfoRaw>> = vec![
+            &self.asset_0,
+            &self.asset_1,
+            &self.pair_key,
+            &self.contract_addr,
+            &self.liquidity_token,
+        ];
+        Box::new(v.into_iter())
+    }
+}
+
+// key : pair id / value: pair info
+pub fn pairs<'a>() -> IndexedMap<'a, u64, PairInfoRaw, PairIndexes<'a>> {
+    let indexes = PairIndexes {
+        asset_0: MultiIndex::new(
+            |_, pair_info| pair_info.asset_infos[0].as_bytes().to_vec(),
+            "pairs",
+            "pairs__asset_0",
+        ),
+        asset_1: MultiIndex::new(
+            |_, pair_info| pair_info.asset_infos[1].as_bytes().to_vec(),
+            "pairs",
+            "pairs__asset_1",
+        ),
+        pair_key: UniqueIndex::new(
+            |pair_info| pair_key(&pair_info.asset_infos, &pair_info.pair_type),
+            "pairs__pair_key",
+        ),
+        contract_addr: UniqueIndex::new(
+            |pair_info| pair_info.contract_addr.as_slice().to_vec(),
+            "pairs__contract_addr",
+        ),
+        liquidity_token: UniqueIndex::new(
+            |pair_info| pair_info.liquidity_token.as_slice().to_vec(),
+            "pairs__liquidity_token",
+        ),
+    };
+
+    IndexedMap::new("pairs", indexes)
+}
+
+// registers the pair under the next pair id
+pub fn add_pair(storage: &mut dyn Storage, pair_info: &PairInfoRaw) -> StdResult<u64> {
+    let pair_id = LAST_PAIR_ID.may_load(storage)?.unwrap_or_default() + 1;
+    pairs().save(storage, pair_id, pair_info)?;
+
+    LAST_PAIR_ID.save(storage, &pair_id)?;
+    let pair_count = PAIR_COUNT.may_load(storage)?.unwrap_or_default();
+    PAIR_COUNT.save(storage, &(pair_count + 1))?;
+
+    Ok(pair_id)
+}
+
+// unregisters the pair, the pair id is not reused
+pub fn remove_pair(storage: &mut dyn Storage, pair_id: u64) -> StdResult<()> {
+    pairs().remove(storage, pair_id)?;
+    DEPRECATED_PAIRS.remove(storage, pair_id);
+
+    let pair_count = PAIR_COUNT.may_load(storage)?.unwrap_or_default();
+    PAIR_COUNT.save(storage, &pair_count.saturating_sub(1))?;
+
+    Ok(())
+}
+
+// key : pair id / value: the reason why the pair is deprecated
+pub const DEPRECATED_PAIRS: Map<u64, String> = Map::new("deprecated_pairs");
+
+// returns the id and the info of the pair of the pair key
+pub fn load_pair(storage: &dyn Storage, pair_key: &[u8]) -> StdResult<Option<(u64, PairInfoRaw)>> {
+    load_unique_pair(&pairs().idx.pair_key, storage, pair_key)
+}
+
+// returns the id and the info of the pair of the pair contract
+pub fn load_pair_by_address(
+    storage: &dyn Storage,
+    contract_addr: &CanonicalAddr,
+) -> StdResult<Option<(u64, PairInfoRaw)>> {
+    load_unique_pair(
+        &pairs().idx.contract_addr,
+        storage,
+        contract_addr.as_slice(),
+    )
+}
+
+// returns the id and the info of the pair of the liquidity token
+pub fn load_pair_by_lp_token(
+    storage: &dyn Storage,
+    liquidity_token: &CanonicalAddr,
+) -> StdResult<Option<(u64, PairInfoRaw)>> {
+    load_unique_pair(
+        &pairs().idx.liquidity_token,
+        storage,
+        liquidity_token.as_slice(),
+    )
+}
+
+fn load_unique_pair(
+    index: &UniqueIndex<Vec<u8>, PairInfoRaw, u64>,
+    storage: &dyn Storage,
+    key: &[u8],
+) -> StdResult<Option<(u64, PairInfoRaw)>> {
+    match index.item(storage, key.to_vec())? {
+        Some((pair_id, pair_info)) => Ok(Some((u64::from_vec(pair_id)?, pair_info))),
+        None => Ok(None),
+    }
+}
+
+// the same assets can be paired once per pair type
+pub fn pair_key(asset_infos: &[AssetInfoRaw; 2], pair_type: &PairType) -> Vec<u8> {
     let mut asset_infos = asset_infos.to_vec();
     asset_infos.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
 
-    [asset_infos[0].as_bytes(), asset_infos[1].as_bytes()].concat()
+    match pair_type {
+        // constant product pairs keep the key they had before the pair types were introduced
+        PairType::Xyk => [asset_infos[0].as_bytes(), asset_infos[1].as_bytes()].concat(),
+        PairType::Stable { .. } => [
+            asset_infos[0].as_bytes(),
+            asset_infos[1].as_bytes(),
+            pair_type.to_string().as_bytes(),
+        ]
+        .concat(),
+    }
 }
 
 // settings for pagination
@@ -39,31 +246,77 @@ pub fn read_pairs(
     storage: &dyn Storage,
     api: &dyn Api,
     start_after: Option<[AssetInfoRaw; 2]>,
+    start_after_pair_type: Option<PairType>,
     limit: Option<u32>,
+    include_deprecated: bool,
 ) -> StdResult<Vec<PairInfo>> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let start = calc_range_start(start_after).map(Bound::ExclusiveRaw);
+    let start = calc_range_start(start_after, start_after_pair_type.unwrap_or_default())
+        .map(Bound::ExclusiveRaw);
+
+    let mut pair_infos: Vec<PairInfo> = vec![];
+    for item in pairs()
+        .idx
+        .pair_key
+        .range_raw(storage, start, None, Order::Ascending)
+    {
+        if pair_infos.len() == limit {
+            break;
+        }
+
+        let (pair_id, pair_info) = item?;
+        if include_deprecated || !DEPRECATED_PAIRS.has(storage, u64::from_vec(pair_id)?) {
+            pair_infos.push(pair_info.to_normal(api)?);
+        }
+    }
+
+    Ok(pair_infos)
+}
+
+// returns the ids and the infos of the pairs of the asset, ordered by pair id
+pub fn read_pairs_by_asset(
+    storage: &dyn Storage,
+    asset_info: &AssetInfoRaw,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    include_deprecated: bool,
+) -> StdResult<Vec<(u64, PairInfoRaw)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let pairs = pairs();
 
-    PAIRS
-        .range(storage, start, None, Order::Ascending)
-        .take(limit)
-        .map(|item| {
-            let (_, v) = item?;
-            v.to_normal(api)
-        })
-        .collect::<StdResult<Vec<PairInfo>>>()
+    // the asset is either the first or the second asset of the pairs
+    let mut asset_pairs: Vec<(u64, PairInfoRaw)> = vec![];
+    for index in [&pairs.idx.asset_0, &pairs.idx.asset_1] {
+        for item in index
+            .prefix(asset_info.as_bytes().to_vec())
+            .range(
+                storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .filter(|item| match item {
+                Ok((pair_id, _)) => include_deprecated || !DEPRECATED_PAIRS.has(storage, *pair_id),
+                Err(_) => true,
+            })
+            .take(limit)
+        {
+            asset_pairs.push(item?);
+        }
+    }
+    asset_pairs.sort_by_key(|(pair_id, _)| *pair_id);
+    asset_pairs.truncate(limit);
+
+    Ok(asset_pairs)
 }
 
 // this will set the first key after the provided key, by appending a 1 byte
-fn calc_range_start(start_after: Option<[AssetInfoRaw; 2]>) -> Option<Vec<u8>> {
+fn calc_range_start(
+    start_after: Option<[AssetInfoRaw; 2]>,
+    pair_type: PairType,
+) -> Option<Vec<u8>> {
     start_after.map(|asset_infos| {
-        let mut asset_infos = asset_infos.to_vec();
-        asset_infos.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
-
-        let mut v = [asset_infos[0].as_bytes(), asset_infos[1].as_bytes()]
-            .concat()
-            .as_slice()
-            .to_vec();
+        let mut v = pair_key(&asset_infos, &pair_type);
         v.push(1);
         v
     })
@@ -1,26 +1,36 @@
-use crate::contract::{execute, instantiate, query, reply};
+use crate::contract::{execute, instantiate, migrate, query, reply};
 use bignumber::Decimal256;
 use haloswap::mock_querier::{mock_dependencies, WasmMockQuerier};
 
 use crate::state::{
-    add_allow_native_token, pair_key, read_pairs, TmpPairInfo, ALLOW_NATIVE_TOKENS, PAIRS,
-    TMP_PAIR_INFO,
+    add_allow_native_token, add_pair, pair_key, read_pairs, TmpPairInfo, ALLOW_NATIVE_TOKENS,
+    DEFAULT_FLASH_FEE_RATE, PAUSE_STATUS, TMP_PAIR_INFOS,
 };
+use cosmwasm_schema::cw_serde;
 use cosmwasm_std::testing::{
     mock_dependencies_with_balance, mock_env, mock_info, MockApi, MockStorage, MOCK_CONTRACT_ADDR,
 };
 use cosmwasm_std::{
-    attr, coin, from_binary, to_binary, Addr, Api, CosmosMsg, OwnedDeps, Reply, ReplyOn, Response,
-    StdError, SubMsg, SubMsgResponse, SubMsgResult, Uint128, WasmMsg,
+    attr, coin, from_binary, to_binary, Addr, Api, BankMsg, CanonicalAddr, CosmosMsg, OwnedDeps,
+    Reply, ReplyOn, Response, SubMsg, SubMsgResponse, SubMsgResult, Uint128, WasmMsg,
 };
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw_storage_plus::Item;
 use haloswap::asset::{
-    AssetInfo, AssetInfoRaw, CreatePairRequirements, LPTokenInfo, PairInfo, PairInfoRaw,
+    Asset, AssetInfo, AssetInfoRaw, CreatePairRequirements, LPTokenInfo, PairInfo, PairInfoRaw,
+    PairType, DEFAULT_MIN_LIQUIDITY,
 };
+use haloswap::error::FactoryError;
 use haloswap::factory::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, NativeTokenDecimalsResponse, PairsResponse,
-    QueryMsg,
+    AddressRole, ConfigResponse, CreatePairParams, Cw20HookMsg, ExecuteMsg, InstantiateMsg,
+    MigrateMsg, NativeDecimalsMismatchesResponse, NativeTokenDecimalsResponse,
+    OwnershipProposalResponse, PairCountResponse, PairCreationMode, PairDeprecationResponse,
+    PairsResponse, PendingCommissionRateResponse, QueryMsg, Role, RolesResponse,
+};
+use haloswap::pair::{
+    ExecuteMsg as PairExecuteMsg, InstantiateMsg as PairInstantiateMsg,
+    MigrateMsg as PairMigrateMsg, PauseStatus,
 };
-use haloswap::pair::{InstantiateMsg as PairInstantiateMsg, MigrateMsg as PairMigrateMsg};
 
 use std::str::FromStr;
 #[test]
@@ -30,6 +40,11 @@ fn proper_initialization() {
     let msg = InstantiateMsg {
         pair_code_id: 321u64,
         token_code_id: 123u64,
+        protocol_fee_rate: None,
+        flash_fee_rate: None,
+        fee_collector: None,
+        pair_creation_mode: None,
+        min_liquidity: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -51,6 +66,11 @@ fn update_config() {
     let msg = InstantiateMsg {
         pair_code_id: 321u64,
         token_code_id: 123u64,
+        protocol_fee_rate: None,
+        flash_fee_rate: None,
+        fee_collector: None,
+        pair_creation_mode: None,
+        min_liquidity: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -60,15 +80,24 @@ fn update_config() {
 
     // update owner
     let info = mock_info("addr0000", &[]);
-    let msg = ExecuteMsg::UpdateConfig {
-        owner: Some("addr0001".to_string()),
-        pair_code_id: None,
-        token_code_id: None,
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "addr0001".to_string(),
+        expires_in: 100u64,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(0, res.messages.len());
 
+    let info = mock_info("addr0001", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::ClaimOwnership {},
+    )
+    .unwrap();
+    assert_eq!(0, res.messages.len());
+
     // it worked, let's query the state
     let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
     let config_res: ConfigResponse = from_binary(&query_res).unwrap();
@@ -80,9 +109,13 @@ fn update_config() {
     let env = mock_env();
     let info = mock_info("addr0001", &[]);
     let msg = ExecuteMsg::UpdateConfig {
-        owner: None,
         pair_code_id: Some(100u64),
         token_code_id: Some(200u64),
+        protocol_fee_rate: None,
+        flash_fee_rate: None,
+        fee_collector: None,
+        pair_creation_mode: None,
+        min_liquidity: None,
     };
 
     let res = execute(deps.as_mut(), env, info, msg).unwrap();
@@ -95,28 +128,164 @@ fn update_config() {
     assert_eq!(100u64, config_res.pair_code_id);
     assert_eq!("addr0001".to_string(), config_res.owner);
 
+    // update min liquidity
+    let msg = ExecuteMsg::UpdateConfig {
+        pair_code_id: None,
+        token_code_id: None,
+        protocol_fee_rate: None,
+        flash_fee_rate: None,
+        fee_collector: None,
+        pair_creation_mode: None,
+        min_liquidity: Some(Uint128::zero()),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg);
+    match res {
+        Err(FactoryError::ZeroMinLiquidity {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateConfig {
+        pair_code_id: None,
+        token_code_id: None,
+        protocol_fee_rate: None,
+        flash_fee_rate: None,
+        fee_collector: None,
+        pair_creation_mode: None,
+        min_liquidity: Some(Uint128::from(10u128)),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg).unwrap();
+
+    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&query_res).unwrap();
+    assert_eq!(Uint128::from(10u128), config_res.min_liquidity);
+
     // Unauthorized err
     let env = mock_env();
     let info = mock_info("addr0000", &[]);
     let msg = ExecuteMsg::UpdateConfig {
-        owner: None,
         pair_code_id: None,
         token_code_id: None,
+        protocol_fee_rate: None,
+        flash_fee_rate: None,
+        fee_collector: None,
+        pair_creation_mode: None,
+        min_liquidity: None,
     };
 
     let res = execute(deps.as_mut(), env, info, msg);
     match res {
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        Err(FactoryError::Unauthorized {}) => {}
         _ => panic!("Must return unauthorized error"),
     }
 }
 
+#[test]
+fn update_protocol_fee_config() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        pair_code_id: 321u64,
+        token_code_id: 123u64,
+        protocol_fee_rate: Some(Decimal256::from_str("0.1").unwrap()),
+        flash_fee_rate: None,
+        fee_collector: Some("collector0000".to_string()),
+        pair_creation_mode: None,
+        min_liquidity: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&query_res).unwrap();
+    assert_eq!(
+        Decimal256::from_str("0.1").unwrap(),
+        config_res.protocol_fee_rate
+    );
+    assert_eq!(Some("collector0000".to_string()), config_res.fee_collector);
+    // the flash fee rate defaults to 0.3%
+    assert_eq!(
+        Decimal256::from_str("0.003").unwrap(),
+        config_res.flash_fee_rate
+    );
+
+    // update protocol fee rate and fee collector
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        pair_code_id: None,
+        token_code_id: None,
+        protocol_fee_rate: Some(Decimal256::from_str("0.25").unwrap()),
+        flash_fee_rate: Some(Decimal256::from_str("0.001").unwrap()),
+        fee_collector: Some("collector0001".to_string()),
+        pair_creation_mode: None,
+        min_liquidity: None,
+    };
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(0, res.messages.len());
+
+    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&query_res).unwrap();
+    assert_eq!(
+        Decimal256::from_str("0.25").unwrap(),
+        config_res.protocol_fee_rate
+    );
+    assert_eq!(
+        Decimal256::from_str("0.001").unwrap(),
+        config_res.flash_fee_rate
+    );
+    assert_eq!(Some("collector0001".to_string()), config_res.fee_collector);
+
+    // protocol fee rate greater than 100% of the commission
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        pair_code_id: None,
+        token_code_id: None,
+        protocol_fee_rate: Some(Decimal256::from_str("1.1").unwrap()),
+        flash_fee_rate: None,
+        fee_collector: None,
+        pair_creation_mode: None,
+        min_liquidity: None,
+    };
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(FactoryError::InvalidProtocolFeeRate {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // flash fee rate of 100% of the paid back amounts
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        pair_code_id: None,
+        token_code_id: None,
+        protocol_fee_rate: None,
+        flash_fee_rate: Some(Decimal256::one()),
+        fee_collector: None,
+        pair_creation_mode: None,
+        min_liquidity: None,
+    };
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(FactoryError::InvalidFlashFeeRate {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
 fn init(
     mut deps: OwnedDeps<MockStorage, MockApi, WasmMockQuerier>,
 ) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
     let msg = InstantiateMsg {
         pair_code_id: 321u64,
         token_code_id: 123u64,
+        protocol_fee_rate: None,
+        flash_fee_rate: None,
+        fee_collector: None,
+        pair_creation_mode: None,
+        min_liquidity: None,
     };
 
     let env = mock_env();
@@ -161,6 +330,7 @@ fn create_pair() {
             lp_token_symbol: "uusd_mAAPL_LP".to_string(),
             lp_token_decimals: None,
         },
+        pair_type: None,
     };
 
     let env = mock_env();
@@ -170,7 +340,8 @@ fn create_pair() {
         res.attributes,
         vec![
             attr("action", "create_pair"),
-            attr("pair", "uusd-asset0001")
+            attr("pair", "uusd-asset0001"),
+            attr("pair_type", "xyk"),
         ]
     );
     assert_eq!(
@@ -190,11 +361,15 @@ fn create_pair() {
                         second_asset_minimum: Uint128::zero(),
                     },
                     commission_rate: Decimal256::from_str("0.003").unwrap(),
+                    protocol_fee_rate: Decimal256::zero(),
+                    flash_fee_rate: Decimal256::from_str(DEFAULT_FLASH_FEE_RATE).unwrap(),
                     lp_token_info: LPTokenInfo {
                         lp_token_name: "uusd_mAAPL_LP".to_string(),
                         lp_token_symbol: "uusd_mAAPL_LP".to_string(),
                         lp_token_decimals: None,
                     },
+                    pair_type: PairType::Xyk,
+                    min_liquidity: Uint128::from(DEFAULT_MIN_LIQUIDITY),
                 })
                 .unwrap(),
                 code_id: 321u64,
@@ -212,11 +387,12 @@ fn create_pair() {
     ];
 
     assert_eq!(
-        TMP_PAIR_INFO.load(&deps.storage).unwrap(),
+        TMP_PAIR_INFOS.load(&deps.storage, 1u64).unwrap(),
         TmpPairInfo {
             asset_infos: raw_infos.clone(),
-            pair_key: pair_key(&raw_infos),
-            asset_decimals: [6u8, 8u8]
+            pair_key: pair_key(&raw_infos, &PairType::Xyk),
+            asset_decimals: [6u8, 8u8],
+            pair_type: PairType::Xyk,
         }
     );
 
@@ -243,13 +419,14 @@ fn create_pair() {
             lp_token_symbol: "uusd_mAAPL_LP".to_string(),
             lp_token_decimals: None,
         },
+        pair_type: None,
     };
 
     let env = mock_env();
     let info = mock_info("addr0000", &[]);
     let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
     match res {
-        StdError::GenericErr { msg, .. } => assert_eq!(msg, "asset2 is invalid"),
+        FactoryError::InvalidAsset { asset } => assert_eq!(asset, "asset0002"),
         _ => panic!("DO NOT ENTER HERE"),
     }
 
@@ -276,16 +453,14 @@ fn create_pair() {
             lp_token_symbol: "uusd_mAAPL_LP".to_string(),
             lp_token_decimals: None,
         },
+        pair_type: None,
     };
 
     let env = mock_env();
     let info = mock_info("addr0000", &[]);
     let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
     match res {
-        StdError::GenericErr { msg, .. } => assert_eq!(
-            msg,
-            "commission rate must be between 0 and 1 (equivalents to 0% to 100%)"
-        ),
+        FactoryError::InvalidCommissionRate {} => {}
         _ => panic!("DO NOT ENTER HERE"),
     }
 
@@ -312,13 +487,14 @@ fn create_pair() {
             lp_token_symbol: "uusd_mAAPL_LP".to_string(),
             lp_token_decimals: None,
         },
+        pair_type: None,
     };
 
     let env = mock_env();
     let info = mock_info("addr0001", &[]);
     let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
     match res {
-        StdError::GenericErr { msg, .. } => assert_eq!(msg, "unauthorized"),
+        FactoryError::Unauthorized {} => {}
         _ => panic!("DO NOT ENTER HERE"),
     }
 }
@@ -357,6 +533,7 @@ fn create_pair_native_token_and_ibc_token() {
             lp_token_symbol: "uusd_ibc/HASH_LP".to_string(),
             lp_token_decimals: None,
         },
+        pair_type: None,
     };
 
     let env = mock_env();
@@ -364,7 +541,11 @@ fn create_pair_native_token_and_ibc_token() {
     let res = execute(deps.as_mut(), env, info, msg).unwrap();
     assert_eq!(
         res.attributes,
-        vec![attr("action", "create_pair"), attr("pair", "uusd-ibc/HASH")]
+        vec![
+            attr("action", "create_pair"),
+            attr("pair", "uusd-ibc/HASH"),
+            attr("pair_type", "xyk"),
+        ]
     );
     assert_eq!(
         res.messages,
@@ -383,11 +564,15 @@ fn create_pair_native_token_and_ibc_token() {
                         second_asset_minimum: Uint128::zero(),
                     },
                     commission_rate: Decimal256::from_str("0.003").unwrap(),
+                    protocol_fee_rate: Decimal256::zero(),
+                    flash_fee_rate: Decimal256::from_str(DEFAULT_FLASH_FEE_RATE).unwrap(),
                     lp_token_info: LPTokenInfo {
                         lp_token_name: "uusd_ibc/HASH_LP".to_string(),
                         lp_token_symbol: "uusd_ibc/HASH_LP".to_string(),
                         lp_token_decimals: None,
                     },
+                    pair_type: PairType::Xyk,
+                    min_liquidity: Uint128::from(DEFAULT_MIN_LIQUIDITY),
                 })
                 .unwrap(),
                 code_id: 321u64,
@@ -405,11 +590,12 @@ fn create_pair_native_token_and_ibc_token() {
     ];
 
     assert_eq!(
-        TMP_PAIR_INFO.load(&deps.storage).unwrap(),
+        TMP_PAIR_INFOS.load(&deps.storage, 1u64).unwrap(),
         TmpPairInfo {
             asset_infos: raw_infos.clone(),
-            pair_key: pair_key(&raw_infos),
-            asset_decimals: [6u8, 6u8]
+            pair_key: pair_key(&raw_infos, &PairType::Xyk),
+            asset_decimals: [6u8, 6u8],
+            pair_type: PairType::Xyk,
         }
     );
 }
@@ -443,6 +629,7 @@ fn fail_to_create_same_pair() {
             lp_token_symbol: "uusd_uusd_LP".to_string(),
             lp_token_decimals: None,
         },
+        pair_type: None,
     };
 
     let env = mock_env();
@@ -479,6 +666,7 @@ fn fail_to_create_pair_with_unactive_denoms() {
             lp_token_symbol: "uusd_uxxx_LP".to_string(),
             lp_token_decimals: None,
         },
+        pair_type: None,
     };
 
     let env = mock_env();
@@ -515,6 +703,7 @@ fn fail_to_create_pair_with_invalid_denom() {
             lp_token_symbol: "uluna_xxx_LP".to_string(),
             lp_token_decimals: None,
         },
+        pair_type: None,
     };
 
     let env = mock_env();
@@ -529,6 +718,11 @@ fn fail_to_create_pair_with_unknown_token() {
     let msg = InstantiateMsg {
         pair_code_id: 321u64,
         token_code_id: 123u64,
+        protocol_fee_rate: None,
+        flash_fee_rate: None,
+        fee_collector: None,
+        pair_creation_mode: None,
+        min_liquidity: None,
     };
 
     let env = mock_env();
@@ -561,6 +755,7 @@ fn fail_to_create_pair_with_unknown_token() {
             lp_token_symbol: "uluna_xxx_LP".to_string(),
             lp_token_decimals: None,
         },
+        pair_type: None,
     };
 
     let env = mock_env();
@@ -575,6 +770,11 @@ fn fail_to_create_pair_with_unknown_ibc_token() {
     let msg = InstantiateMsg {
         pair_code_id: 321u64,
         token_code_id: 123u64,
+        protocol_fee_rate: None,
+        flash_fee_rate: None,
+        fee_collector: None,
+        pair_creation_mode: None,
+        min_liquidity: None,
     };
 
     let env = mock_env();
@@ -607,6 +807,7 @@ fn fail_to_create_pair_with_unknown_ibc_token() {
             lp_token_symbol: "uluna_ibc/HA_LP".to_string(),
             lp_token_decimals: None,
         },
+        pair_type: None,
     };
 
     let env = mock_env();
@@ -640,14 +841,16 @@ fn reply_test() {
         asset_infos[1].to_raw(deps.as_ref().api).unwrap(),
     ];
 
-    let pair_key = pair_key(&raw_infos);
-    TMP_PAIR_INFO
+    let pair_key = pair_key(&raw_infos, &PairType::Xyk);
+    TMP_PAIR_INFOS
         .save(
             &mut deps.storage,
+            1u64,
             &TmpPairInfo {
                 asset_infos: raw_infos,
                 pair_key,
                 asset_decimals: [8u8, 8u8],
+                pair_type: PairType::Xyk,
             },
         )
         .unwrap();
@@ -682,18 +885,43 @@ fn reply_test() {
                     second_asset_minimum: Uint128::zero(),
                 },
                 commission_rate: Decimal256::from_str("0.003").unwrap(),
+                pair_type: PairType::Xyk,
             },
         )],
         &[],
     );
 
-    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    // the pairs created during a global pause start paused
+    PAUSE_STATUS
+        .save(
+            &mut deps.storage,
+            &PauseStatus {
+                swaps: true,
+                deposits: false,
+            },
+        )
+        .unwrap();
+
+    let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "0000".to_string(),
+            msg: to_binary(&PairExecuteMsg::SetPause {
+                swaps: true,
+                deposits: false,
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
 
     let query_res = query(
         deps.as_ref(),
         mock_env(),
         QueryMsg::Pair {
             asset_infos: asset_infos.clone(),
+            pair_type: None,
         },
     )
     .unwrap();
@@ -712,6 +940,7 @@ fn reply_test() {
                 second_asset_minimum: Uint128::zero(),
             },
             commission_rate: Decimal256::from_str("0.003").unwrap(),
+            pair_type: PairType::Xyk,
         }
     );
 
@@ -721,6 +950,8 @@ fn reply_test() {
         QueryMsg::Pairs {
             start_after: None,
             limit: None,
+            start_after_pair_type: None,
+            include_deprecated: None,
         },
     )
     .unwrap();
@@ -740,6 +971,7 @@ fn reply_test() {
                     second_asset_minimum: Uint128::zero(),
                 },
                 commission_rate: Decimal256::from_str("0.003").unwrap(),
+                pair_type: PairType::Xyk,
             }],
         }
     );
@@ -792,7 +1024,7 @@ fn failed_add_allow_native_token_with_non_admin() {
 
     assert_eq!(
         execute(deps.as_mut(), mock_env(), info, msg),
-        Err(StdError::generic_err("unauthorized"))
+        Err(FactoryError::Unauthorized {})
     );
 }
 
@@ -810,9 +1042,7 @@ fn failed_add_allow_native_token_with_zero_factory_balance() {
 
     assert_eq!(
         execute(deps.as_mut(), mock_env(), info, msg),
-        Err(StdError::generic_err(
-            "a balance greater than zero is required by the factory for verification",
-        ))
+        Err(FactoryError::NativeTokenBalanceRequired {})
     );
 }
 
@@ -861,6 +1091,40 @@ fn append_add_allow_native_token_with_already_exist_token() {
     assert_eq!(7u8, res.decimals)
 }
 
+#[test]
+fn migrate_legacy_config() {
+    // the config before the protocol fee and the pair creation settings were added
+    #[cw_serde]
+    struct LegacyConfig {
+        owner: CanonicalAddr,
+        pair_code_id: u64,
+        token_code_id: u64,
+    }
+
+    let mut deps = mock_dependencies(&[]);
+    let owner = deps.api.addr_canonicalize("addr0000").unwrap();
+    Item::<LegacyConfig>::new("config")
+        .save(
+            deps.as_mut().storage,
+            &LegacyConfig {
+                owner,
+                pair_code_id: 321u64,
+                token_code_id: 123u64,
+            },
+        )
+        .unwrap();
+
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.owner, "addr0000");
+    assert_eq!(config.protocol_fee_rate, Decimal256::zero());
+    assert_eq!(config.fee_collector, None);
+    assert_eq!(config.pair_creation_mode, PairCreationMode::default());
+    assert_eq!(config.min_liquidity, Uint128::from(DEFAULT_MIN_LIQUIDITY));
+}
+
 #[test]
 fn normal_migrate_pair() {
     let mut deps = mock_dependencies(&[coin(1u128, "uluna".to_string())]);
@@ -919,7 +1183,7 @@ fn failed_migrate_pair_with_no_admin() {
 
     assert_eq!(
         execute(deps.as_mut(), mock_env(), info, msg),
-        Err(StdError::generic_err("unauthorized")),
+        Err(FactoryError::Unauthorized {}),
     );
 }
 
@@ -989,25 +1253,23 @@ mod setting_pagination {
                 denom: "uatom".to_string(),
             },
         ];
-        let pair_key1 = pair_key(&asset_infos);
-        PAIRS
-            .save(
-                deps.as_mut().storage,
-                pair_key1.as_slice(),
-                &PairInfoRaw {
-                    contract_addr: api.addr_canonicalize("pair1").unwrap(),
-                    liquidity_token: api.addr_canonicalize("lp1").unwrap(),
-                    asset_infos,
-                    asset_decimals: [6u8, 6u8],
-                    requirements: CreatePairRequirements {
-                        whitelist: vec![],
-                        first_asset_minimum: Uint128::zero(),
-                        second_asset_minimum: Uint128::zero(),
-                    },
-                    commission_rate: Decimal256::from_str("0.003").unwrap(),
+        add_pair(
+            deps.as_mut().storage,
+            &PairInfoRaw {
+                contract_addr: api.addr_canonicalize("pair1").unwrap(),
+                liquidity_token: api.addr_canonicalize("lp1").unwrap(),
+                asset_infos,
+                asset_decimals: [6u8, 6u8],
+                requirements: CreatePairRequirements {
+                    whitelist: vec![],
+                    first_asset_minimum: Uint128::zero(),
+                    second_asset_minimum: Uint128::zero(),
                 },
-            )
-            .unwrap();
+                commission_rate: Decimal256::from_str("0.003").unwrap(),
+                pair_type: PairType::Xyk,
+            },
+        )
+        .unwrap();
 
         let asset_infos = [
             AssetInfoRaw::NativeToken {
@@ -1018,25 +1280,23 @@ mod setting_pagination {
             },
         ];
 
-        let pair_key2 = pair_key(&asset_infos);
-        PAIRS
-            .save(
-                deps.as_mut().storage,
-                pair_key2.as_slice(),
-                &PairInfoRaw {
-                    contract_addr: api.addr_canonicalize("pair2").unwrap(),
-                    liquidity_token: api.addr_canonicalize("lp2").unwrap(),
-                    asset_infos,
-                    asset_decimals: [6u8, 6u8],
-                    requirements: CreatePairRequirements {
-                        whitelist: vec![],
-                        first_asset_minimum: Uint128::zero(),
-                        second_asset_minimum: Uint128::zero(),
-                    },
-                    commission_rate: Decimal256::from_str("0.003").unwrap(),
+        add_pair(
+            deps.as_mut().storage,
+            &PairInfoRaw {
+                contract_addr: api.addr_canonicalize("pair2").unwrap(),
+                liquidity_token: api.addr_canonicalize("lp2").unwrap(),
+                asset_infos,
+                asset_decimals: [6u8, 6u8],
+                requirements: CreatePairRequirements {
+                    whitelist: vec![],
+                    first_asset_minimum: Uint128::zero(),
+                    second_asset_minimum: Uint128::zero(),
                 },
-            )
-            .unwrap();
+                commission_rate: Decimal256::from_str("0.003").unwrap(),
+                pair_type: PairType::Xyk,
+            },
+        )
+        .unwrap();
 
         let asset_infos = [
             AssetInfoRaw::NativeToken {
@@ -1046,27 +1306,33 @@ mod setting_pagination {
                 denom: "uaura".to_string(),
             },
         ];
-        let pair_key3 = pair_key(&asset_infos);
-        PAIRS
-            .save(
-                deps.as_mut().storage,
-                pair_key3.as_slice(),
-                &PairInfoRaw {
-                    contract_addr: api.addr_canonicalize("pair3").unwrap(),
-                    liquidity_token: api.addr_canonicalize("lp3").unwrap(),
-                    asset_infos,
-                    asset_decimals: [6u8, 6u8],
-                    requirements: CreatePairRequirements {
-                        whitelist: vec![],
-                        first_asset_minimum: Uint128::zero(),
-                        second_asset_minimum: Uint128::zero(),
-                    },
-                    commission_rate: Decimal256::from_str("0.003").unwrap(),
+        add_pair(
+            deps.as_mut().storage,
+            &PairInfoRaw {
+                contract_addr: api.addr_canonicalize("pair3").unwrap(),
+                liquidity_token: api.addr_canonicalize("lp3").unwrap(),
+                asset_infos,
+                asset_decimals: [6u8, 6u8],
+                requirements: CreatePairRequirements {
+                    whitelist: vec![],
+                    first_asset_minimum: Uint128::zero(),
+                    second_asset_minimum: Uint128::zero(),
                 },
-            )
-            .unwrap();
+                commission_rate: Decimal256::from_str("0.003").unwrap(),
+                pair_type: PairType::Xyk,
+            },
+        )
+        .unwrap();
 
-        let pairs = read_pairs(deps.as_ref().storage, deps.as_ref().api, None, None).unwrap();
+        let pairs = read_pairs(
+            deps.as_ref().storage,
+            deps.as_ref().api,
+            None,
+            None,
+            None,
+            false,
+        )
+        .unwrap();
         assert_eq!(pairs.len(), 3);
 
         let pairs = read_pairs(
@@ -1081,6 +1347,8 @@ mod setting_pagination {
                 },
             ]),
             None,
+            None,
+            false,
         )
         .unwrap();
         assert_eq!(pairs.len(), 2);
@@ -1096,9 +1364,1840 @@ mod setting_pagination {
                     denom: "uatom".to_string(),
                 },
             ]),
+            None,
             Some(1),
+            false,
         )
         .unwrap();
         assert_eq!(pairs.len(), 1);
     }
 }
+
+#[test]
+fn create_stable_pair_next_to_constant_product_pair() {
+    let mut deps = mock_dependencies(&[coin(10u128, "uusd".to_string())]);
+    deps = init(deps);
+    deps.querier
+        .with_halo_factory(&[], &[("uusd".to_string(), 6u8)]);
+    let asset_infos = [
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        AssetInfo::Token {
+            contract_addr: "asset0001".to_string(),
+        },
+    ];
+    let raw_infos = [
+        asset_infos[0].to_raw(deps.as_ref().api).unwrap(),
+        asset_infos[1].to_raw(deps.as_ref().api).unwrap(),
+    ];
+
+    // the constant product pair of the assets already exists
+    let xyk_pair_info = PairInfoRaw {
+        asset_infos: raw_infos.clone(),
+        contract_addr: deps.api.addr_canonicalize("pair0000").unwrap(),
+        liquidity_token: deps.api.addr_canonicalize("liquidity0000").unwrap(),
+        asset_decimals: [6u8, 8u8],
+        requirements: CreatePairRequirements {
+            whitelist: vec![Addr::unchecked("deployer")],
+            first_asset_minimum: Uint128::zero(),
+            second_asset_minimum: Uint128::zero(),
+        },
+        commission_rate: Decimal256::from_str("0.003").unwrap(),
+        pair_type: PairType::Xyk,
+    };
+    add_pair(&mut deps.storage, &xyk_pair_info).unwrap();
+
+    let create_pair_msg = |pair_type: Option<PairType>| ExecuteMsg::CreatePair {
+        asset_infos: asset_infos.clone(),
+        requirements: CreatePairRequirements {
+            whitelist: vec![Addr::unchecked("deployer")],
+            first_asset_minimum: Uint128::zero(),
+            second_asset_minimum: Uint128::zero(),
+        },
+        commission_rate: Some(Decimal256::from_str("0.0005").unwrap()),
+        lp_token_info: LPTokenInfo {
+            lp_token_name: "uusd_asset0001_LP".to_string(),
+            lp_token_symbol: "uusd_asset0001_LP".to_string(),
+            lp_token_decimals: None,
+        },
+        pair_type,
+    };
+
+    // the same assets can not be paired twice with the same pair type
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        create_pair_msg(None),
+    );
+    match res {
+        Err(FactoryError::PairAlreadyExists {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // but they can be paired once more as a stable pair
+    let stable_pair_type = PairType::Stable { amp: 100 };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        create_pair_msg(Some(stable_pair_type.clone())),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "create_pair"),
+            attr("pair", "uusd-asset0001"),
+            attr("pair_type", "stable"),
+        ]
+    );
+
+    let tmp_pair_info = TMP_PAIR_INFOS.load(&deps.storage, 1u64).unwrap();
+    assert_eq!(tmp_pair_info.pair_type, stable_pair_type);
+    assert_ne!(tmp_pair_info.pair_key, pair_key(&raw_infos, &PairType::Xyk));
+
+    // the pairs are queried by their pair type
+    add_pair(
+        &mut deps.storage,
+        &PairInfoRaw {
+            contract_addr: deps.api.addr_canonicalize("pair0001").unwrap(),
+            liquidity_token: deps.api.addr_canonicalize("liquidity0001").unwrap(),
+            commission_rate: Decimal256::from_str("0.0005").unwrap(),
+            pair_type: stable_pair_type.clone(),
+            ..xyk_pair_info
+        },
+    )
+    .unwrap();
+
+    let pair_res: PairInfo = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Pair {
+                asset_infos: asset_infos.clone(),
+                pair_type: Some(stable_pair_type.clone()),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(pair_res.contract_addr, "pair0001");
+
+    let pair_res: PairInfo = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Pair {
+                asset_infos: asset_infos.clone(),
+                pair_type: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(pair_res.contract_addr, "pair0000");
+
+    // the stable pair is listed after the constant product pair of the same assets
+    let pairs_res: PairsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Pairs {
+                start_after: Some(asset_infos.clone()),
+                start_after_pair_type: None,
+                limit: None,
+                include_deprecated: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(pairs_res.pairs.len(), 1);
+    assert_eq!(pairs_res.pairs[0].pair_type, stable_pair_type);
+
+    let pairs_res: PairsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Pairs {
+                start_after: Some(asset_infos),
+                start_after_pair_type: Some(stable_pair_type),
+                limit: None,
+                include_deprecated: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(pairs_res.pairs.is_empty());
+}
+
+#[test]
+fn set_pause_status() {
+    let mut deps = mock_dependencies(&[]);
+    deps = init(deps);
+
+    // the pair answered by the mock querier and another pair
+    let luna_infos = [
+        AssetInfoRaw::NativeToken {
+            denom: "uluna".to_string(),
+        },
+        AssetInfoRaw::NativeToken {
+            denom: "uluna".to_string(),
+        },
+    ];
+    let usd_infos = [
+        AssetInfoRaw::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        AssetInfoRaw::NativeToken {
+            denom: "uluna".to_string(),
+        },
+    ];
+    for (asset_infos, pair, liquidity_token) in [
+        (&luna_infos, "pair0000", "liquidity0000"),
+        (&usd_infos, "pair0001", "liquidity0001"),
+    ] {
+        add_pair(
+            &mut deps.storage,
+            &PairInfoRaw {
+                asset_infos: asset_infos.clone(),
+                contract_addr: deps.api.addr_canonicalize(pair).unwrap(),
+                liquidity_token: deps.api.addr_canonicalize(liquidity_token).unwrap(),
+                asset_decimals: [6u8, 6u8],
+                requirements: CreatePairRequirements {
+                    whitelist: vec![Addr::unchecked("deployer")],
+                    first_asset_minimum: Uint128::zero(),
+                    second_asset_minimum: Uint128::zero(),
+                },
+                commission_rate: Decimal256::from_str("0.003").unwrap(),
+                pair_type: PairType::Xyk,
+            },
+        )
+        .unwrap();
+    }
+
+    let set_pause_msg = |pair: &str, swaps: bool, deposits: bool| {
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: pair.to_string(),
+            msg: to_binary(&PairExecuteMsg::SetPause { swaps, deposits }).unwrap(),
+            funds: vec![],
+        }))
+    };
+
+    // only the owner or the guardian can pause
+    let msg = ExecuteMsg::SetPauseStatus {
+        pair: Some("pair0000".to_string()),
+        swaps: true,
+        deposits: false,
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("guardian0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(FactoryError::Unauthorized {}) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let grant_role_msg = ExecuteMsg::GrantRole {
+        address: "guardian0000".to_string(),
+        role: Role::Guardian,
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        grant_role_msg,
+    )
+    .unwrap();
+
+    // the guardian pauses the swaps of a pair
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("guardian0000", &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(res.messages, vec![set_pause_msg("pair0000", true, false)]);
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "set_pause_status"),
+            attr("pair", "pair0000"),
+            attr("swaps", "true"),
+            attr("deposits", "false"),
+        ]
+    );
+
+    // the owner pauses the deposits of all pairs, the swaps of the pair stay paused
+    let msg = ExecuteMsg::SetPauseStatus {
+        pair: None,
+        swaps: false,
+        deposits: true,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert!(res
+        .messages
+        .contains(&set_pause_msg("pair0000", true, true)));
+    assert!(res
+        .messages
+        .contains(&set_pause_msg("pair0001", false, true)));
+
+    let pause_status: PauseStatus = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PauseStatus { pair: None },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        pause_status,
+        PauseStatus {
+            swaps: false,
+            deposits: true,
+        }
+    );
+    let pause_status: PauseStatus = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PauseStatus {
+                pair: Some("pair0000".to_string()),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        pause_status,
+        PauseStatus {
+            swaps: true,
+            deposits: false,
+        }
+    );
+
+    // anyone can propagate the global status to the next pages
+    let msg = ExecuteMsg::PropagatePauseStatus {
+        start_after: None,
+        limit: Some(1),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+    assert_eq!(res.messages, vec![set_pause_msg("pair0000", true, true)]);
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "propagate_pause_status"),
+            attr("swaps", "false"),
+            attr("deposits", "true"),
+            attr("updated_pairs", "1"),
+            attr("next_start_after", "pair0000"),
+        ]
+    );
+
+    let msg = ExecuteMsg::PropagatePauseStatus {
+        start_after: Some("pair0000".to_string()),
+        limit: Some(1),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+    assert_eq!(res.messages, vec![set_pause_msg("pair0001", false, true)]);
+
+    let msg = ExecuteMsg::PropagatePauseStatus {
+        start_after: Some("pair0001".to_string()),
+        limit: Some(1),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(res.attributes.last(), Some(&attr("updated_pairs", "0")));
+
+    // only the pairs created by the factory can be paused
+    let msg = ExecuteMsg::SetPauseStatus {
+        pair: Some("pair0002".to_string()),
+        swaps: true,
+        deposits: true,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(FactoryError::PairNotRegistered {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn two_step_ownership_transfer() {
+    let mut deps = mock_dependencies(&[]);
+    deps = init(deps);
+
+    // only the owner can propose a new owner
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "addr0001".to_string(),
+        expires_in: 100u64,
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(FactoryError::Unauthorized {}) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "propose_new_owner"),
+            attr("new_owner", "addr0001"),
+            attr(
+                "expires_at",
+                mock_env()
+                    .block
+                    .time
+                    .plus_seconds(100)
+                    .seconds()
+                    .to_string()
+            ),
+        ]
+    );
+    let proposal_res: OwnershipProposalResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::OwnershipProposal {}).unwrap())
+            .unwrap();
+    assert_eq!(proposal_res.owner, "addr0001".to_string());
+
+    // only the proposed owner can claim the ownership
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0002", &[]),
+        ExecuteMsg::ClaimOwnership {},
+    );
+    match res {
+        Err(FactoryError::Unauthorized {}) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // the proposal expires
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(101);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("addr0001", &[]),
+        ExecuteMsg::ClaimOwnership {},
+    );
+    match res {
+        Err(FactoryError::OwnershipProposalExpired {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the dropped proposal can not be claimed
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::DropOwnershipProposal {},
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::ClaimOwnership {},
+    );
+    match res {
+        Err(FactoryError::OwnershipProposalNotFound {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the ownership is claimed within the expiry
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "addr0001".to_string(),
+        expires_in: 100u64,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::ClaimOwnership {},
+    )
+    .unwrap();
+
+    let config_res: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config_res.owner, "addr0001".to_string());
+    assert!(query(deps.as_ref(), mock_env(), QueryMsg::OwnershipProposal {}).is_err());
+}
+
+#[test]
+fn roles() {
+    let mut deps = mock_dependencies(&[coin(10u128, "uusd".to_string())]);
+    deps = init(deps);
+
+    // only the owner can grant the roles
+    let msg = ExecuteMsg::GrantRole {
+        address: "creator0000".to_string(),
+        role: Role::PairCreator,
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(FactoryError::Unauthorized {}) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    let msg = ExecuteMsg::GrantRole {
+        address: "fee0000".to_string(),
+        role: Role::FeeManager,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let roles_res: RolesResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Roles {}).unwrap()).unwrap();
+    assert_eq!(
+        roles_res.roles,
+        vec![
+            AddressRole {
+                address: "creator0000".to_string(),
+                role: Role::PairCreator,
+            },
+            AddressRole {
+                address: "fee0000".to_string(),
+                role: Role::FeeManager,
+            },
+        ]
+    );
+
+    // the pair creator registers native tokens but can not update the fees
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator0000", &[]),
+        ExecuteMsg::AddNativeTokenDecimals {
+            denom: "uusd".to_string(),
+            decimals: 6u8,
+        },
+    )
+    .unwrap();
+
+    let fee_msg = ExecuteMsg::UpdateConfig {
+        token_code_id: None,
+        pair_code_id: None,
+        protocol_fee_rate: Some(Decimal256::from_str("0.1").unwrap()),
+        flash_fee_rate: None,
+        fee_collector: Some("collector0000".to_string()),
+        pair_creation_mode: None,
+        min_liquidity: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator0000", &[]),
+        fee_msg.clone(),
+    );
+    match res {
+        Err(FactoryError::Unauthorized {}) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // the fee manager updates the fees but not the code ids
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("fee0000", &[]),
+        fee_msg,
+    )
+    .unwrap();
+    let config_res: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(
+        config_res.protocol_fee_rate,
+        Decimal256::from_str("0.1").unwrap()
+    );
+    assert_eq!(config_res.fee_collector, Some("collector0000".to_string()));
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("fee0000", &[]),
+        ExecuteMsg::UpdateConfig {
+            token_code_id: Some(1u64),
+            pair_code_id: None,
+            protocol_fee_rate: None,
+            flash_fee_rate: None,
+            fee_collector: None,
+            pair_creation_mode: None,
+            min_liquidity: None,
+        },
+    );
+    match res {
+        Err(FactoryError::Unauthorized {}) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // the revoked pair creator can not register native tokens anymore
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::RevokeRole {
+            address: "creator0000".to_string(),
+        },
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator0000", &[]),
+        ExecuteMsg::AddNativeTokenDecimals {
+            denom: "uusd".to_string(),
+            decimals: 6u8,
+        },
+    );
+    match res {
+        Err(FactoryError::Unauthorized {}) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+}
+
+#[test]
+fn create_pair_permissionless_with_creation_fee() {
+    let mut deps = mock_dependencies(&[coin(10u128, "uusd".to_string())]);
+    deps = init(deps);
+    deps.querier
+        .with_halo_factory(&[], &[("uusd".to_string(), 6u8)]);
+
+    let asset_infos = [
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        AssetInfo::Token {
+            contract_addr: "asset0001".to_string(),
+        },
+    ];
+    let create_pair_msg = ExecuteMsg::CreatePair {
+        asset_infos: asset_infos.clone(),
+        requirements: CreatePairRequirements {
+            whitelist: vec![],
+            first_asset_minimum: Uint128::zero(),
+            second_asset_minimum: Uint128::zero(),
+        },
+        commission_rate: None,
+        lp_token_info: LPTokenInfo {
+            lp_token_name: "uusd_asset0001_LP".to_string(),
+            lp_token_symbol: "uusd_asset0001_LP".to_string(),
+            lp_token_decimals: None,
+        },
+        pair_type: None,
+    };
+
+    // only the owner can create pairs by default
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator0000", &[]),
+        create_pair_msg.clone(),
+    );
+    match res {
+        Err(FactoryError::Unauthorized {}) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // the sender of a token hook is not trusted to be the owner
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("fake0000", &[]),
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "addr0000".to_string(),
+            amount: Uint128::zero(),
+            msg: to_binary(&Cw20HookMsg::CreatePair {
+                asset_infos: asset_infos.clone(),
+                requirements: CreatePairRequirements {
+                    whitelist: vec![],
+                    first_asset_minimum: Uint128::zero(),
+                    second_asset_minimum: Uint128::zero(),
+                },
+                commission_rate: None,
+                lp_token_info: LPTokenInfo {
+                    lp_token_name: "uusd_asset0001_LP".to_string(),
+                    lp_token_symbol: "uusd_asset0001_LP".to_string(),
+                    lp_token_decimals: None,
+                },
+                pair_type: None,
+            })
+            .unwrap(),
+        }),
+    );
+    match res {
+        Err(FactoryError::Unauthorized {}) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // the funds are not kept when no fee is charged
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[coin(100u128, "uusd")]),
+        create_pair_msg.clone(),
+    );
+    match res {
+        Err(FactoryError::UnexpectedFunds {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // anyone can create pairs by paying 100uusd
+    let fee = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: Uint128::from(100u128),
+    };
+    let msg = ExecuteMsg::UpdateConfig {
+        token_code_id: None,
+        pair_code_id: None,
+        protocol_fee_rate: None,
+        flash_fee_rate: None,
+        fee_collector: Some("collector0000".to_string()),
+        pair_creation_mode: Some(PairCreationMode::Permissionless { fee: fee.clone() }),
+        min_liquidity: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    let config_res: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(
+        config_res.pair_creation_mode,
+        PairCreationMode::Permissionless { fee }
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator0000", &[coin(99u128, "uusd")]),
+        create_pair_msg.clone(),
+    );
+    match res {
+        Err(FactoryError::InvalidPairCreationFee { fee }) => assert_eq!(fee, "100uusd"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the funds must be the fee only
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(
+            "creator0000",
+            &[coin(100u128, "uusd"), coin(100u128, "uluna")],
+        ),
+        create_pair_msg.clone(),
+    );
+    match res {
+        Err(FactoryError::InvalidPairCreationFee { fee }) => assert_eq!(fee, "100uusd"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the fee is sent to the fee collector and the creator is whitelisted
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator0000", &[coin(100u128, "uusd")]),
+        create_pair_msg,
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "collector0000".to_string(),
+            amount: vec![coin(100u128, "uusd")],
+        }))
+    );
+    match &res.messages[1].msg {
+        CosmosMsg::Wasm(WasmMsg::Instantiate { msg, .. }) => {
+            let pair_instantiate_msg: PairInstantiateMsg = from_binary(msg).unwrap();
+            assert_eq!(
+                pair_instantiate_msg.requirements.whitelist,
+                vec![Addr::unchecked("creator0000")]
+            );
+        }
+        _ => panic!("Must instantiate the pair"),
+    }
+
+    // the pair is not created until the reply, as if the transaction was reverted
+    TMP_PAIR_INFOS.clear(&mut deps.storage);
+
+    // the fee can be paid with a token as well
+    let msg = ExecuteMsg::UpdateConfig {
+        token_code_id: None,
+        pair_code_id: None,
+        protocol_fee_rate: None,
+        flash_fee_rate: None,
+        fee_collector: None,
+        pair_creation_mode: Some(PairCreationMode::Permissionless {
+            fee: Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "fee0000".to_string(),
+                },
+                amount: Uint128::from(50u128),
+            },
+        }),
+        min_liquidity: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let receive_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "creator0000".to_string(),
+        amount: Uint128::from(50u128),
+        msg: to_binary(&Cw20HookMsg::CreatePair {
+            asset_infos,
+            requirements: CreatePairRequirements {
+                whitelist: vec![Addr::unchecked("lp0000")],
+                first_asset_minimum: Uint128::zero(),
+                second_asset_minimum: Uint128::zero(),
+            },
+            commission_rate: None,
+            lp_token_info: LPTokenInfo {
+                lp_token_name: "uusd_asset0001_LP".to_string(),
+                lp_token_symbol: "uusd_asset0001_LP".to_string(),
+                lp_token_decimals: None,
+            },
+            pair_type: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("fee0001", &[]),
+        receive_msg.clone(),
+    );
+    match res {
+        Err(FactoryError::InvalidPairCreationFee { fee }) => assert_eq!(fee, "50fee0000"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the owner claimed by a token hook must pay the fee as well
+    let mut owner_receive_msg = receive_msg.clone();
+    if let ExecuteMsg::Receive(cw20_msg) = &mut owner_receive_msg {
+        cw20_msg.sender = "addr0000".to_string();
+        cw20_msg.amount = Uint128::zero();
+    }
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("fee0000", &[]),
+        owner_receive_msg,
+    );
+    match res {
+        Err(FactoryError::InvalidPairCreationFee { fee }) => assert_eq!(fee, "50fee0000"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("fee0000", &[]),
+        receive_msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "fee0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "collector0000".to_string(),
+                amount: Uint128::from(50u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    );
+    match &res.messages[1].msg {
+        CosmosMsg::Wasm(WasmMsg::Instantiate { msg, .. }) => {
+            let pair_instantiate_msg: PairInstantiateMsg = from_binary(msg).unwrap();
+            assert_eq!(
+                pair_instantiate_msg.requirements.whitelist,
+                vec![Addr::unchecked("lp0000")]
+            );
+        }
+        _ => panic!("Must instantiate the pair"),
+    }
+}
+
+#[test]
+fn update_pair_commission_rate() {
+    let mut deps = mock_dependencies(&[]);
+    deps = init(deps);
+
+    // the pair answered by the mock querier
+    let asset_infos = [
+        AssetInfoRaw::NativeToken {
+            denom: "uluna".to_string(),
+        },
+        AssetInfoRaw::NativeToken {
+            denom: "uluna".to_string(),
+        },
+    ];
+    add_pair(
+        &mut deps.storage,
+        &PairInfoRaw {
+            asset_infos,
+            contract_addr: deps.api.addr_canonicalize("pair0000").unwrap(),
+            liquidity_token: deps.api.addr_canonicalize("liquidity0000").unwrap(),
+            asset_decimals: [6u8, 6u8],
+            requirements: CreatePairRequirements {
+                whitelist: vec![Addr::unchecked("deployer")],
+                first_asset_minimum: Uint128::zero(),
+                second_asset_minimum: Uint128::zero(),
+            },
+            commission_rate: Decimal256::from_str("0.003").unwrap(),
+            pair_type: PairType::Xyk,
+        },
+    )
+    .unwrap();
+
+    let query_commission_rate = |deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>| {
+        let pair_info: PairInfo = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Pair {
+                    asset_infos: [
+                        AssetInfo::NativeToken {
+                            denom: "uluna".to_string(),
+                        },
+                        AssetInfo::NativeToken {
+                            denom: "uluna".to_string(),
+                        },
+                    ],
+                    pair_type: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        pair_info.commission_rate
+    };
+    let update_commission_rate_msg = |commission_rate: Decimal256| {
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "pair0000".to_string(),
+            msg: to_binary(&PairExecuteMsg::UpdateCommissionRate { commission_rate }).unwrap(),
+            funds: vec![],
+        }))
+    };
+
+    // only the owner or the fee manager can update the commission rate
+    let msg = ExecuteMsg::UpdatePairCommissionRate {
+        pair: "pair0000".to_string(),
+        commission_rate: Decimal256::from_str("0.001").unwrap(),
+        effective_at: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("manager0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(FactoryError::Unauthorized {}) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let msg_grant = ExecuteMsg::GrantRole {
+        address: "manager0000".to_string(),
+        role: Role::FeeManager,
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg_grant,
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("manager0000", &[]),
+        ExecuteMsg::UpdatePairCommissionRate {
+            pair: "pair0000".to_string(),
+            commission_rate: Decimal256::from_str("1.1").unwrap(),
+            effective_at: None,
+        },
+    );
+    match res {
+        Err(FactoryError::InvalidCommissionRate {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // a commission of 100% would take the whole return of the swaps
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("manager0000", &[]),
+        ExecuteMsg::UpdatePairCommissionRate {
+            pair: "pair0000".to_string(),
+            commission_rate: Decimal256::one(),
+            effective_at: None,
+        },
+    );
+    match res {
+        Err(FactoryError::InvalidCommissionRate {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the pair must be registered
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("manager0000", &[]),
+        ExecuteMsg::UpdatePairCommissionRate {
+            pair: "pair0001".to_string(),
+            commission_rate: Decimal256::from_str("0.001").unwrap(),
+            effective_at: None,
+        },
+    );
+    match res {
+        Err(FactoryError::PairNotRegistered {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the commission rate is updated immediately without an effective time
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("manager0000", &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![update_commission_rate_msg(
+            Decimal256::from_str("0.001").unwrap()
+        )]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_pair_commission_rate"),
+            attr("pair", "pair0000"),
+            attr("commission_rate", "0.001"),
+            attr("effective_at", mock_env().block.time.seconds().to_string()),
+        ]
+    );
+    assert_eq!(
+        query_commission_rate(&deps),
+        Decimal256::from_str("0.001").unwrap()
+    );
+
+    // the effective time must be in the future
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("manager0000", &[]),
+        ExecuteMsg::UpdatePairCommissionRate {
+            pair: "pair0000".to_string(),
+            commission_rate: Decimal256::from_str("0.005").unwrap(),
+            effective_at: Some(mock_env().block.time),
+        },
+    );
+    match res {
+        Err(FactoryError::InvalidEffectiveTime {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the scheduled commission rate is pending until the effective time
+    let effective_at = mock_env().block.time.plus_seconds(86_400);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("manager0000", &[]),
+        ExecuteMsg::UpdatePairCommissionRate {
+            pair: "pair0000".to_string(),
+            commission_rate: Decimal256::from_str("0.005").unwrap(),
+            effective_at: Some(effective_at),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    let pending: PendingCommissionRateResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PendingCommissionRate {
+                pair: "pair0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        pending,
+        PendingCommissionRateResponse {
+            commission_rate: Decimal256::from_str("0.005").unwrap(),
+            effective_at,
+        }
+    );
+    assert_eq!(
+        query_commission_rate(&deps),
+        Decimal256::from_str("0.001").unwrap()
+    );
+
+    let msg = ExecuteMsg::ApplyPairCommissionRate {
+        pair: "pair0000".to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(FactoryError::CommissionRateNotEffective {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // anyone can apply it once it is effective
+    let mut env = mock_env();
+    env.block.time = effective_at;
+    let res = execute(deps.as_mut(), env, mock_info("addr0001", &[]), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![update_commission_rate_msg(
+            Decimal256::from_str("0.005").unwrap()
+        )]
+    );
+    assert_eq!(
+        query_commission_rate(&deps),
+        Decimal256::from_str("0.005").unwrap()
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PendingCommissionRate {
+            pair: "pair0000".to_string(),
+        },
+    );
+    match res {
+        Err(FactoryError::PendingCommissionRateNotFound {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn propagate_native_decimals() {
+    let mut deps = mock_dependencies(&[coin(1u128, "uluna".to_string())]);
+    deps = init(deps);
+
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::AddNativeTokenDecimals {
+        denom: "uluna".to_string(),
+        decimals: 6u8,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // more pairs of the native token than a page
+    for i in 0..12 {
+        let asset_infos = [
+            AssetInfoRaw::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            AssetInfoRaw::Token {
+                contract_addr: deps
+                    .api
+                    .addr_canonicalize(&format!("asset{:04}", i))
+                    .unwrap(),
+            },
+        ];
+        let pair_info = PairInfoRaw {
+            asset_infos,
+            contract_addr: deps
+                .api
+                .addr_canonicalize(&format!("pair{:04}", i))
+                .unwrap(),
+            liquidity_token: deps
+                .api
+                .addr_canonicalize(&format!("liquidity{:04}", i))
+                .unwrap(),
+            asset_decimals: [6u8, 8u8],
+            requirements: CreatePairRequirements {
+                whitelist: vec![Addr::unchecked("deployer")],
+                first_asset_minimum: Uint128::zero(),
+                second_asset_minimum: Uint128::zero(),
+            },
+            commission_rate: Decimal256::from_str("0.003").unwrap(),
+            pair_type: PairType::Xyk,
+        };
+        add_pair(&mut deps.storage, &pair_info).unwrap();
+    }
+
+    let query_mismatches = |deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>,
+                            start_after: Option<String>| {
+        let res: NativeDecimalsMismatchesResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::NativeDecimalsMismatches {
+                    denom: "uluna".to_string(),
+                    start_after,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        res
+    };
+    let res = query_mismatches(&deps, None);
+    assert!(res.pairs.is_empty());
+    assert_eq!(res.next_start_after, Some("pair0009".to_string()));
+
+    // the first page of the pairs is updated with the new decimals
+    let msg = ExecuteMsg::AddNativeTokenDecimals {
+        denom: "uluna".to_string(),
+        decimals: 8u8,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages.len(), 10);
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "pair0000".to_string(),
+            msg: to_binary(&PairExecuteMsg::UpdateNativeTokenDecimals {
+                denom: "uluna".to_string(),
+                asset_decimals: [8u8, 8u8],
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    );
+    assert!(res
+        .attributes
+        .contains(&attr("next_start_after", "pair0009")));
+
+    // the rest of the pairs still disagree with the registered decimals
+    let res = query_mismatches(&deps, None);
+    assert!(res.pairs.is_empty());
+    let res = query_mismatches(&deps, res.next_start_after);
+    assert_eq!(
+        res.pairs
+            .iter()
+            .map(|pair| pair.contract_addr.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["pair0010", "pair0011"]
+    );
+    assert_eq!(res.pairs[0].asset_decimals, [6u8, 8u8]);
+    assert_eq!(res.next_start_after, None);
+
+    // anyone can resume the propagation
+    let msg = ExecuteMsg::PropagateNativeDecimals {
+        denom: "uluna".to_string(),
+        start_after: Some("pair0009".to_string()),
+        limit: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "propagate_native_decimals"),
+            attr("denom", "uluna"),
+            attr("decimals", "8"),
+            attr("updated_pairs", "2"),
+        ]
+    );
+    let res = query_mismatches(&deps, Some("pair0009".to_string()));
+    assert!(res.pairs.is_empty());
+
+    // the pairs which already agree are not updated again
+    let msg = ExecuteMsg::PropagateNativeDecimals {
+        denom: "uluna".to_string(),
+        start_after: None,
+        limit: Some(5),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg).unwrap();
+    assert!(res.messages.is_empty());
+    assert!(res
+        .attributes
+        .contains(&attr("next_start_after", "pair0004")));
+
+    let msg = ExecuteMsg::PropagateNativeDecimals {
+        denom: "uatom".to_string(),
+        start_after: None,
+        limit: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg);
+    assert_eq!(
+        res,
+        Err(FactoryError::NativeTokenDecimalsNotFound {
+            denom: "uatom".to_string()
+        })
+    );
+}
+
+#[test]
+fn query_pairs_by_asset_and_address() {
+    let mut deps = mock_dependencies(&[]);
+    deps = init(deps);
+
+    let native = |denom: &str| AssetInfoRaw::NativeToken {
+        denom: denom.to_string(),
+    };
+    for (i, asset_infos) in [
+        [native("uaura"), native("uatom")],
+        [native("uatom"), native("uusd")],
+        [native("uusd"), native("uaura")],
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        add_pair(
+            &mut deps.storage,
+            &PairInfoRaw {
+                asset_infos,
+                contract_addr: deps
+                    .api
+                    .addr_canonicalize(&format!("pair{:04}", i))
+                    .unwrap(),
+                liquidity_token: deps
+                    .api
+                    .addr_canonicalize(&format!("liquidity{:04}", i))
+                    .unwrap(),
+                asset_decimals: [6u8, 6u8],
+                requirements: CreatePairRequirements {
+                    whitelist: vec![Addr::unchecked("deployer")],
+                    first_asset_minimum: Uint128::zero(),
+                    second_asset_minimum: Uint128::zero(),
+                },
+                commission_rate: Decimal256::from_str("0.003").unwrap(),
+                pair_type: PairType::Xyk,
+            },
+        )
+        .unwrap();
+    }
+
+    let pair_count: PairCountResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PairCount {}).unwrap()).unwrap();
+    assert_eq!(pair_count.count, 3u64);
+
+    // the pairs containing an asset are returned in the order they were created
+    let query_pairs_by_asset = |start_after: Option<String>, limit: Option<u32>| {
+        let res: PairsResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::PairsByAsset {
+                    asset_info: AssetInfo::NativeToken {
+                        denom: "uaura".to_string(),
+                    },
+                    start_after,
+                    limit,
+                    include_deprecated: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        res.pairs
+            .into_iter()
+            .map(|pair| pair.contract_addr)
+            .collect::<Vec<String>>()
+    };
+    assert_eq!(
+        query_pairs_by_asset(None, None),
+        vec!["pair0000", "pair0002"]
+    );
+    assert_eq!(query_pairs_by_asset(None, Some(1)), vec!["pair0000"]);
+    assert_eq!(
+        query_pairs_by_asset(Some("pair0000".to_string()), None),
+        vec!["pair0002"]
+    );
+
+    let pair_res: PairInfo = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PairByAddress {
+                address: "pair0001".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(pair_res.liquidity_token, "liquidity0001");
+
+    let pair_res: PairInfo = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PairByLpToken {
+                lp_token: "liquidity0002".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(pair_res.contract_addr, "pair0002");
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PairByAddress {
+            address: "pair0003".to_string(),
+        },
+    );
+    match res {
+        Err(FactoryError::PairNotRegistered {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn deprecate_and_deregister_pair() {
+    let mut deps = mock_dependencies(&[coin(10u128, "uusd".to_string())]);
+    deps = init(deps);
+    deps.querier
+        .with_halo_factory(&[], &[("uusd".to_string(), 6u8)]);
+
+    let asset_infos = [
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        AssetInfo::Token {
+            contract_addr: "asset0001".to_string(),
+        },
+    ];
+    let other_asset_infos = [
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        AssetInfo::Token {
+            contract_addr: "asset0002".to_string(),
+        },
+    ];
+    for (i, asset_infos) in [&asset_infos, &other_asset_infos].into_iter().enumerate() {
+        add_pair(
+            &mut deps.storage,
+            &PairInfoRaw {
+                asset_infos: [
+                    asset_infos[0].to_raw(&deps.api).unwrap(),
+                    asset_infos[1].to_raw(&deps.api).unwrap(),
+                ],
+                contract_addr: deps
+                    .api
+                    .addr_canonicalize(&format!("pair{:04}", i))
+                    .unwrap(),
+                liquidity_token: deps
+                    .api
+                    .addr_canonicalize(&format!("liquidity{:04}", i))
+                    .unwrap(),
+                asset_decimals: [6u8, 8u8],
+                requirements: CreatePairRequirements {
+                    whitelist: vec![Addr::unchecked("deployer")],
+                    first_asset_minimum: Uint128::zero(),
+                    second_asset_minimum: Uint128::zero(),
+                },
+                commission_rate: Decimal256::from_str("0.003").unwrap(),
+                pair_type: PairType::Xyk,
+            },
+        )
+        .unwrap();
+    }
+
+    let deprecate_msg = ExecuteMsg::DeprecatePair {
+        asset_infos: asset_infos.clone(),
+        pair_type: None,
+        reason: "migrated to a new pool".to_string(),
+    };
+    let deregister_msg = ExecuteMsg::DeregisterPair {
+        asset_infos: asset_infos.clone(),
+        pair_type: None,
+    };
+
+    // only the owner can deprecate a pair
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        deprecate_msg.clone(),
+    );
+    match res {
+        Err(FactoryError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // a pair must be deprecated before it is deregistered
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        deregister_msg.clone(),
+    );
+    match res {
+        Err(FactoryError::PairNotDeprecated {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the swaps and the deposits of the deprecated pair are paused
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        deprecate_msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "pair0000".to_string(),
+            msg: to_binary(&PairExecuteMsg::SetPause {
+                swaps: true,
+                deposits: true,
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "deprecate_pair"),
+            attr("pair", "pair0000"),
+            attr("reason", "migrated to a new pool"),
+        ]
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        deprecate_msg,
+    );
+    match res {
+        Err(FactoryError::PairDeprecated {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let deprecation: PairDeprecationResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PairDeprecation {
+                pair: "pair0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(deprecation.reason, "migrated to a new pool");
+
+    // the deprecated pair is not resolved by its assets anymore
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Pair {
+            asset_infos: asset_infos.clone(),
+            pair_type: None,
+        },
+    );
+    match res {
+        Err(FactoryError::PairDeprecated {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // and it is only listed on demand
+    let query_pairs = |deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>,
+                       include_deprecated: Option<bool>| {
+        let res: PairsResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Pairs {
+                    start_after: None,
+                    start_after_pair_type: None,
+                    limit: None,
+                    include_deprecated,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        res.pairs
+            .into_iter()
+            .map(|pair| pair.contract_addr)
+            .collect::<Vec<String>>()
+    };
+    assert_eq!(query_pairs(&deps, None), vec!["pair0001"]);
+    assert_eq!(query_pairs(&deps, Some(true)), vec!["pair0000", "pair0001"]);
+
+    let pairs_res: PairsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PairsByAsset {
+                asset_info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                start_after: None,
+                limit: None,
+                include_deprecated: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(pairs_res.pairs.len(), 1);
+    assert_eq!(pairs_res.pairs[0].contract_addr, "pair0001");
+
+    // the deprecated pair can not be resumed
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::SetPauseStatus {
+            pair: Some("pair0000".to_string()),
+            swaps: false,
+            deposits: false,
+        },
+    );
+    match res {
+        Err(FactoryError::PairDeprecated {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::SetPauseStatus {
+            pair: None,
+            swaps: false,
+            deposits: false,
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "pair0001".to_string(),
+            msg: to_binary(&PairExecuteMsg::SetPause {
+                swaps: false,
+                deposits: false,
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    // once deregistered, the assets can be paired again
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        deregister_msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "deregister_pair"), attr("pair", "pair0000")]
+    );
+
+    let pair_count: PairCountResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PairCount {}).unwrap()).unwrap();
+    assert_eq!(pair_count.count, 1u64);
+    assert_eq!(query_pairs(&deps, Some(true)), vec!["pair0001"]);
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::CreatePair {
+            asset_infos,
+            requirements: CreatePairRequirements {
+                whitelist: vec![Addr::unchecked("deployer")],
+                first_asset_minimum: Uint128::zero(),
+                second_asset_minimum: Uint128::zero(),
+            },
+            commission_rate: None,
+            lp_token_info: LPTokenInfo {
+                lp_token_name: "uusd_asset0001_LP".to_string(),
+                lp_token_symbol: "uusd_asset0001_LP".to_string(),
+                lp_token_decimals: None,
+            },
+            pair_type: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+}
+
+#[test]
+fn create_pairs() {
+    let mut deps = mock_dependencies(&[]);
+    deps = init(deps);
+    deps.querier.with_token_balances(&[
+        (
+            &"asset0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::zero())],
+        ),
+        (
+            &"asset0001".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::zero())],
+        ),
+    ]);
+
+    let asset_infos = |i: u32| {
+        [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: format!("asset{:04}", i),
+            },
+        ]
+    };
+    let requirements = CreatePairRequirements {
+        whitelist: vec![Addr::unchecked("deployer")],
+        first_asset_minimum: Uint128::zero(),
+        second_asset_minimum: Uint128::zero(),
+    };
+    let pair_infos: Vec<PairInfo> = (0..2)
+        .map(|i| PairInfo {
+            asset_infos: asset_infos(i),
+            contract_addr: format!("pair{:04}", i),
+            liquidity_token: format!("liquidity{:04}", i),
+            asset_decimals: [6u8, 8u8],
+            requirements: requirements.clone(),
+            commission_rate: Decimal256::from_str("0.003").unwrap(),
+            pair_type: PairType::Xyk,
+        })
+        .collect();
+    deps.querier.with_halo_factory(
+        &[
+            (&pair_infos[0].contract_addr, &pair_infos[0]),
+            (&pair_infos[1].contract_addr, &pair_infos[1]),
+        ],
+        &[("uusd".to_string(), 6u8)],
+    );
+
+    let create_pairs_msg = |assets: &[u32]| ExecuteMsg::CreatePairs {
+        pairs: assets
+            .iter()
+            .map(|i| CreatePairParams {
+                asset_infos: asset_infos(*i),
+                requirements: requirements.clone(),
+                commission_rate: None,
+                lp_token_info: LPTokenInfo {
+                    lp_token_name: format!("uusd_asset{:04}_LP", i),
+                    lp_token_symbol: format!("uusd_asset{:04}_LP", i),
+                    lp_token_decimals: None,
+                },
+                pair_type: None,
+            })
+            .collect(),
+    };
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        create_pairs_msg(&[]),
+    );
+    match res {
+        Err(FactoryError::NoPairs {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the same pair can not be created twice in a batch
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        create_pairs_msg(&[0, 0]),
+    );
+    match res {
+        Err(FactoryError::PairAlreadyExists {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    // as if the transaction was reverted
+    TMP_PAIR_INFOS.clear(&mut deps.storage);
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        create_pairs_msg(&[0, 1]),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "create_pair"),
+            attr("pair", "uusd-asset0000"),
+            attr("pair_type", "xyk"),
+            attr("pair", "uusd-asset0001"),
+            attr("pair_type", "xyk"),
+        ]
+    );
+
+    // each pair is kept under the reply id of its instantiation
+    let reply_ids: Vec<u64> = res.messages.iter().map(|msg| msg.id).collect();
+    assert_eq!(reply_ids.len(), 2);
+    assert_ne!(reply_ids[0], reply_ids[1]);
+    for (reply_id, pair_info) in reply_ids.iter().zip(pair_infos.iter()) {
+        let tmp_pair_info = TMP_PAIR_INFOS.load(&deps.storage, *reply_id).unwrap();
+        assert_eq!(
+            tmp_pair_info.asset_infos[1],
+            pair_info.asset_infos[1].to_raw(&deps.api).unwrap()
+        );
+    }
+
+    // the replies register the right pairs whatever their order
+    for (reply_id, pair_info) in reply_ids.iter().zip(pair_infos.iter()).rev() {
+        let mut data = vec![10u8, pair_info.contract_addr.len() as u8];
+        data.extend(pair_info.contract_addr.as_bytes());
+        let reply_msg = Reply {
+            id: *reply_id,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(data.into()),
+            }),
+        };
+        reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    }
+    assert!(TMP_PAIR_INFOS.is_empty(&deps.storage));
+
+    for pair_info in pair_infos {
+        let pair_res: PairInfo = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Pair {
+                    asset_infos: pair_info.asset_infos.clone(),
+                    pair_type: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(pair_res, pair_info);
+    }
+}
@@ -0,0 +1,136 @@
+use crate::state::{AmpConfig, AMP_CONFIG, CONFIG, PAIR_INFO};
+
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, StdResult, Storage};
+use haloswap::asset::{PairInfoRaw, PairType};
+use haloswap::error::ContractError;
+use haloswap::querier::query_factory_config;
+
+/// Bounds of the amplification coefficient
+pub const MIN_AMP: u64 = 1;
+pub const MAX_AMP: u64 = 1_000_000;
+/// Maximum factor between the current and the next amplification coefficient
+pub const MAX_AMP_CHANGE: u64 = 10;
+/// Minimum duration of a ramp in seconds
+pub const MIN_AMP_CHANGING_TIME: u64 = 86_400;
+
+pub fn assert_amp(amp: u64) -> Result<(), ContractError> {
+    if !(MIN_AMP..=MAX_AMP).contains(&amp) {
+        return Err(ContractError::InvalidAmp {
+            min_amp: MIN_AMP,
+            max_amp: MAX_AMP,
+        });
+    }
+
+    Ok(())
+}
+
+/// Starts a stable pair with a constant amplification coefficient
+pub fn initialize_amp(storage: &mut dyn Storage, env: &Env, amp: u64) -> Result<(), ContractError> {
+    assert_amp(amp)?;
+
+    let block_time = env.block.time.seconds();
+    AMP_CONFIG.save(
+        storage,
+        &AmpConfig {
+            init_amp: amp,
+            init_amp_time: block_time,
+            next_amp: amp,
+            next_amp_time: block_time,
+        },
+    )?;
+
+    Ok(())
+}
+
+/// Returns the amplification coefficient of a stable pair at the current block
+pub fn query_current_amp(storage: &dyn Storage, env: &Env) -> StdResult<u64> {
+    Ok(compute_current_amp(&AMP_CONFIG.load(storage)?, env))
+}
+
+fn compute_current_amp(amp_config: &AmpConfig, env: &Env) -> u64 {
+    let block_time = env.block.time.seconds();
+    if block_time >= amp_config.next_amp_time {
+        return amp_config.next_amp;
+    }
+
+    let elapsed = block_time - amp_config.init_amp_time;
+    let duration = amp_config.next_amp_time - amp_config.init_amp_time;
+    if amp_config.next_amp > amp_config.init_amp {
+        amp_config.init_amp + (amp_config.next_amp - amp_config.init_amp) * elapsed / duration
+    } else {
+        amp_config.init_amp - (amp_config.init_amp - amp_config.next_amp) * elapsed / duration
+    }
+}
+
+// Only owner of the factory can change the amplification coefficient of a stable pair
+fn assert_stable_pair_owner(deps: &DepsMut, info: &MessageInfo) -> Result<(), ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if query_factory_config(&deps.querier, config.halo_factory)?.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    if !matches!(pair_info.pair_type, PairType::Stable { .. }) {
+        return Err(ContractError::NotStablePair {});
+    }
+
+    Ok(())
+}
+
+pub fn start_changing_amp(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    next_amp: u64,
+    next_amp_time: u64,
+) -> Result<Response, ContractError> {
+    assert_stable_pair_owner(&deps, &info)?;
+    assert_amp(next_amp)?;
+
+    let block_time = env.block.time.seconds();
+    if next_amp_time < block_time + MIN_AMP_CHANGING_TIME {
+        return Err(ContractError::AmpChangeTooFast {
+            min_time: MIN_AMP_CHANGING_TIME,
+        });
+    }
+
+    let current_amp = query_current_amp(deps.storage, &env)?;
+    if next_amp > current_amp * MAX_AMP_CHANGE || next_amp * MAX_AMP_CHANGE < current_amp {
+        return Err(ContractError::AmpChangeTooLarge {
+            max_change: MAX_AMP_CHANGE,
+        });
+    }
+
+    AMP_CONFIG.save(
+        deps.storage,
+        &AmpConfig {
+            init_amp: current_amp,
+            init_amp_time: block_time,
+            next_amp,
+            next_amp_time,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "start_changing_amp"),
+        ("init_amp", &current_amp.to_string()),
+        ("next_amp", &next_amp.to_string()),
+        ("next_amp_time", &next_amp_time.to_string()),
+    ]))
+}
+
+pub fn stop_changing_amp(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    assert_stable_pair_owner(&deps, &info)?;
+
+    let current_amp = query_current_amp(deps.storage, &env)?;
+    initialize_amp(deps.storage, &env, current_amp)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "stop_changing_amp"),
+        ("current_amp", &current_amp.to_string()),
+    ]))
+}
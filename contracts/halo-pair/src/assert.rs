@@ -1,11 +1,14 @@
-use cosmwasm_std::{Decimal, StdError, Uint128};
+use cosmwasm_std::{Decimal, Env, StdResult, Storage, Timestamp, Uint128};
 
 use bignumber::{Decimal256, Uint256};
 use haloswap::asset::Asset;
 use haloswap::error::ContractError;
 use haloswap::formulas::{calc_price_drop, calc_slippage_tolerance};
+use haloswap::pair::PauseStatus;
 use std::cmp::Ordering;
 
+use crate::state::{FLASH_SWAP, PAUSE_STATUS};
+
 /// If `belief_price` and `max_spread` both are given,
 /// we compute new spread else we just use halo
 /// spread to check `max_spread`
@@ -88,7 +91,7 @@ pub fn assert_slippage_tolerance(
         let slippage_tolerance: Decimal256 = slippage_tolerance.into();
         // the slippage tolerance cannot be greater than 100%
         if slippage_tolerance > Decimal256::one() {
-            return Err(StdError::generic_err("slippage_tolerance cannot bigger than 1").into());
+            return Err(ContractError::InvalidSlippageTolerance {});
         }
 
         let one_minus_slippage_tolerance = Decimal256::one() - slippage_tolerance;
@@ -106,3 +109,45 @@ pub fn assert_slippage_tolerance(
     }
     Ok(())
 }
+
+/// Transactions are rejected once the block time is after their deadline
+pub fn assert_deadline(env: &Env, deadline: Option<Timestamp>) -> Result<(), ContractError> {
+    if let Some(deadline) = deadline {
+        if env.block.time > deadline {
+            return Err(ContractError::DeadlineExceeded {});
+        }
+    }
+
+    Ok(())
+}
+
+/// Swaps are rejected while they are paused by the factory
+pub fn assert_swaps_not_paused(storage: &dyn Storage) -> Result<(), ContractError> {
+    if load_pause_status(storage)?.swaps {
+        return Err(ContractError::Paused {});
+    }
+
+    Ok(())
+}
+
+/// Deposits are rejected while they are paused by the factory, withdrawals never are
+pub fn assert_deposits_not_paused(storage: &dyn Storage) -> Result<(), ContractError> {
+    if load_pause_status(storage)?.deposits {
+        return Err(ContractError::Paused {});
+    }
+
+    Ok(())
+}
+
+/// The pool is locked while the callback of a flash swap is executed
+pub fn assert_no_flash_swap(storage: &dyn Storage) -> Result<(), ContractError> {
+    if FLASH_SWAP.may_load(storage)?.is_some() {
+        return Err(ContractError::FlashSwapInProgress {});
+    }
+
+    Ok(())
+}
+
+fn load_pause_status(storage: &dyn Storage) -> StdResult<PauseStatus> {
+    Ok(PAUSE_STATUS.may_load(storage)?.unwrap_or_default())
+}
@@ -1,26 +1,42 @@
-use crate::assert::{assert_max_spread, assert_slippage_tolerance};
-use crate::state::{Config, COMMISSION_RATE_INFO, CONFIG, PAIR_INFO};
+use crate::amp::{initialize_amp, query_current_amp, start_changing_amp, stop_changing_amp};
+use crate::assert::{
+    assert_deadline, assert_deposits_not_paused, assert_max_spread, assert_no_flash_swap,
+    assert_slippage_tolerance, assert_swaps_not_paused,
+};
+use crate::oracle::{
+    initialize_price_accumulators, query_cumulative_prices, query_twap, update_price_accumulators,
+};
+use crate::state::{
+    Config, FlashSwap, COMMISSION_RATE_INFO, CONFIG, FLASH_FEE_RATE, FLASH_SWAP, MIN_LIQUIDITY,
+    PAIR_INFO, PAUSE_STATUS, PENDING_DEPOSITS, PENDING_DEPOSIT_AMOUNTS, PRICE_CUMULATIVE,
+    PROTOCOL_FEES, PROTOCOL_FEE_RATE, RESERVES,
+};
 
 use bignumber::{Decimal256, Uint256};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
     from_binary, to_binary, Addr, Binary, CanonicalAddr, CosmosMsg, Decimal, Deps, DepsMut, Env,
-    MessageInfo, Reply, ReplyOn, Response, StdResult, SubMsg, Uint128, WasmMsg,
+    MessageInfo, Reply, ReplyOn, Response, StdResult, Storage, SubMsg, Uint128, WasmMsg,
 };
 use cw2::set_contract_version;
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
 use cw_utils::parse_reply_instantiate_data;
-use haloswap::asset::{
-    Asset, AssetInfo, AssetInfoRaw, PairInfo, PairInfoRaw, LP_TOKEN_RESERVED_AMOUNT,
-};
+use haloswap::asset::{Asset, AssetInfo, AssetInfoRaw, PairInfo, PairInfoRaw, PairType};
 use haloswap::error::ContractError;
-use haloswap::formulas::{calculate_lp_token_amount_to_user, compute_offer_amount, compute_swap};
+use haloswap::formulas::{
+    calculate_lp_token_amount_to_user, compute_offer_amount, compute_protocol_fee, compute_share,
+    compute_single_sided_swap_amount, compute_stable_invariant, compute_stable_offer_amount,
+    compute_stable_single_sided_swap_amount, compute_stable_swap, compute_swap,
+    compute_used_deposits,
+};
 use haloswap::pair::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, PoolResponse, QueryMsg,
-    ReverseSimulationResponse, SimulationResponse,
+    Cw20HookMsg, ExecuteMsg, FlashFeeRateResponse, FlashSwapCallbackMsg, InstantiateMsg,
+    MigrateMsg, PauseStatus, PendingDepositResponse, PoolResponse, ProtocolFeesResponse, QueryMsg,
+    ReverseSimulationResponse, SimulateProvideLiquidityResponse, SimulateWithdrawLiquidityResponse,
+    SimulationResponse,
 };
-use haloswap::querier::query_token_info;
+use haloswap::querier::{query_factory_config, query_token_info};
 use haloswap::token::InstantiateMsg as TokenInstantiateMsg;
 
 // version info for migration info
@@ -34,9 +50,19 @@ pub fn instantiate(
     env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
+    // commission rate must be less than 1 equivalents to 0% up to 100%, which would take the whole return
+    if msg.commission_rate >= Decimal256::one() {
+        return Err(ContractError::InvalidCommissionRate {});
+    }
+
+    // flash fee rate must be less than 1, a flash swap could not be paid back otherwise
+    if msg.flash_fee_rate >= Decimal256::one() {
+        return Err(ContractError::InvalidFlashFeeRate {});
+    }
+
     let pair_info: &PairInfoRaw = &PairInfoRaw {
         contract_addr: deps.api.addr_canonicalize(env.contract.address.as_str())?,
         liquidity_token: CanonicalAddr::from(vec![]),
@@ -47,6 +73,7 @@ pub fn instantiate(
         asset_decimals: msg.asset_decimals,
         requirements: msg.requirements,
         commission_rate: msg.commission_rate,
+        pair_type: msg.pair_type.clone(),
     };
 
     // Store factory contract address which is used to create pair contract
@@ -59,8 +86,22 @@ pub fn instantiate(
 
     PAIR_INFO.save(deps.storage, pair_info)?;
 
+    if let PairType::Stable { amp } = msg.pair_type {
+        initialize_amp(deps.storage, &env, amp)?;
+    }
+
     COMMISSION_RATE_INFO.save(deps.storage, &msg.commission_rate)?;
 
+    PROTOCOL_FEE_RATE.save(deps.storage, &msg.protocol_fee_rate)?;
+    PROTOCOL_FEES.save(deps.storage, &[Uint128::zero(), Uint128::zero()])?;
+    FLASH_FEE_RATE.save(deps.storage, &msg.flash_fee_rate)?;
+
+    RESERVES.save(deps.storage, &[Uint128::zero(), Uint128::zero()])?;
+    PENDING_DEPOSIT_AMOUNTS.save(deps.storage, &[Uint128::zero(), Uint128::zero()])?;
+    MIN_LIQUIDITY.save(deps.storage, &msg.min_liquidity)?;
+
+    initialize_price_accumulators(deps.storage, &env)?;
+
     Ok(Response::new().add_submessage(SubMsg {
         // Create LP token
         msg: WasmMsg::Instantiate {
@@ -99,13 +140,19 @@ pub fn execute(
             assets,
             slippage_tolerance,
             receiver,
-        } => provide_liquidity(deps, env, info, assets, slippage_tolerance, receiver),
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+            provide_liquidity(deps, env, info, assets, slippage_tolerance, receiver, None)
+        }
         ExecuteMsg::Swap {
             offer_asset,
             belief_price,
             max_spread,
             to,
+            deadline,
         } => {
+            assert_deadline(&env, deadline)?;
             if !offer_asset.is_native_token() {
                 return Err(ContractError::Unauthorized {});
             }
@@ -126,10 +173,92 @@ pub fn execute(
                 to_addr,
             )
         }
+        ExecuteMsg::SwapExactOut {
+            ask_asset,
+            max_offer_amount,
+            to,
+        } => {
+            // the offer asset is the other asset of the pair, it must be a native token
+            let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+            let pools: [Asset; 2] = load_pools(deps.as_ref(), &pair_info)?;
+            let offer_info = if ask_asset.info.equal(&pools[0].info) {
+                pools[1].info.clone()
+            } else if ask_asset.info.equal(&pools[1].info) {
+                pools[0].info.clone()
+            } else {
+                return Err(ContractError::AssetMismatch {});
+            };
+            let denom = match &offer_info {
+                AssetInfo::NativeToken { denom } => denom,
+                AssetInfo::Token { .. } => return Err(ContractError::Unauthorized {}),
+            };
+            // any other coin sent with the message would be stuck in the pair
+            let offer_amount = match info.funds.as_slice() {
+                [coin] if &coin.denom == denom => coin.amount,
+                _ => return Err(ContractError::InvalidSwapFunds {}),
+            };
+
+            let to_addr = if let Some(to_addr) = to {
+                Some(deps.api.addr_validate(&to_addr)?)
+            } else {
+                None
+            };
+            swap_exact_out(
+                deps,
+                env,
+                info.sender,
+                Asset {
+                    info: offer_info,
+                    amount: offer_amount,
+                },
+                ask_asset,
+                max_offer_amount,
+                to_addr,
+            )
+        }
         ExecuteMsg::UpdateNativeTokenDecimals {
             denom,
             asset_decimals,
         } => update_native_token_decimals(deps, env, info, denom, asset_decimals),
+        ExecuteMsg::CollectProtocolFees {} => collect_protocol_fees(deps, env, info),
+        ExecuteMsg::StartChangingAmp {
+            next_amp,
+            next_amp_time,
+        } => start_changing_amp(deps, env, info, next_amp, next_amp_time),
+        ExecuteMsg::StopChangingAmp {} => stop_changing_amp(deps, env, info),
+        ExecuteMsg::ProvideSingleSidedLiquidity {
+            asset,
+            min_share,
+            receiver,
+        } => {
+            if !asset.is_native_token() {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            asset.assert_sent_native_token_balance(&info)?;
+            provide_single_sided_liquidity(deps, env, info.sender, asset, min_share, receiver)
+        }
+        ExecuteMsg::SetPause { swaps, deposits } => {
+            set_pause(deps, env, info, PauseStatus { swaps, deposits })
+        }
+        ExecuteMsg::UpdateCommissionRate { commission_rate } => {
+            update_commission_rate(deps, env, info, commission_rate)
+        }
+        ExecuteMsg::Sync {} => sync(deps, env, info),
+        ExecuteMsg::Skim { to } => {
+            let to_addr = deps.api.addr_validate(&to)?;
+            skim(deps, env, info, to_addr)
+        }
+        ExecuteMsg::FlashSwap {
+            ask_asset,
+            recipient,
+            data,
+        } => {
+            let recipient_addr = deps.api.addr_validate(&recipient)?;
+            flash_swap(deps, env, info, ask_asset, recipient_addr, data)
+        }
+        ExecuteMsg::AssertFlashSwap {} => assert_flash_swap(deps, env, info),
+        ExecuteMsg::WithdrawPendingDeposit {} => withdraw_pending_deposit(deps, env, info),
     }
 }
 
@@ -147,7 +276,10 @@ pub fn receive_cw20(
             belief_price,
             max_spread,
             to,
+            deadline,
         }) => {
+            assert_deadline(&env, deadline)?;
+
             // verify amount of asset info is same as the amount in cw20_msg
             if offer_asset.amount != cw20_msg.amount {
                 return Err(ContractError::AssetMismatch {});
@@ -156,8 +288,7 @@ pub fn receive_cw20(
             // only asset contract can execute this message
             let mut authorized: bool = false;
             let config: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
-            let pools: [Asset; 2] =
-                config.query_pools(&deps.querier, deps.api, env.contract.address.clone())?;
+            let pools: [Asset; 2] = load_pools(deps.as_ref(), &config)?;
             for pool in pools.iter() {
                 if let AssetInfo::Token { contract_addr, .. } = &pool.info {
                     if contract_addr == &info.sender {
@@ -170,6 +301,14 @@ pub fn receive_cw20(
                 return Err(ContractError::Unauthorized {});
             }
 
+            // verify the offer asset is the received token, the other asset of the pair
+            // would be swapped out of the reserves otherwise
+            if !offer_asset.info.equal(&AssetInfo::Token {
+                contract_addr: info.sender.to_string(),
+            }) {
+                return Err(ContractError::AssetMismatch {});
+            }
+
             let to_addr = if let Some(to_addr) = to {
                 Some(deps.api.addr_validate(to_addr.as_str())?)
             } else {
@@ -187,14 +326,147 @@ pub fn receive_cw20(
                 to_addr,
             )
         }
-        Ok(Cw20HookMsg::WithdrawLiquidity {}) => {
+        Ok(Cw20HookMsg::SwapExactOut {
+            ask_asset,
+            max_offer_amount,
+            to,
+        }) => {
+            // the received token is checked to be the offer asset of the pair
+            let to_addr = if let Some(to_addr) = to {
+                Some(deps.api.addr_validate(to_addr.as_str())?)
+            } else {
+                None
+            };
+
+            swap_exact_out(
+                deps,
+                env,
+                Addr::unchecked(cw20_msg.sender),
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: info.sender.to_string(),
+                    },
+                    amount: cw20_msg.amount,
+                },
+                ask_asset,
+                max_offer_amount,
+                to_addr,
+            )
+        }
+        Ok(Cw20HookMsg::ProvideLiquidity {
+            other_asset,
+            slippage_tolerance,
+            receiver,
+            deadline,
+        }) => {
+            assert_deadline(&env, deadline)?;
+
+            let received_asset = Asset {
+                info: AssetInfo::Token {
+                    contract_addr: info.sender.to_string(),
+                },
+                amount: cw20_msg.amount,
+            };
+
+            // only asset contract can execute this message, with the other asset of the pair
+            let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+            let pools: [Asset; 2] = load_pools(deps.as_ref(), &pair_info)?;
+            let received_index = pools
+                .iter()
+                .position(|pool| pool.info.equal(&received_asset.info))
+                .ok_or(ContractError::Unauthorized {})?;
+            if !pools[1 - received_index].info.equal(&other_asset.info) {
+                return Err(ContractError::AssetMismatch {});
+            }
+
+            let sender_addr = deps.api.addr_validate(cw20_msg.sender.as_str())?;
+            match PENDING_DEPOSITS.may_load(deps.storage, &sender_addr)? {
+                Some(pending_deposit) if pending_deposit.info.equal(&other_asset.info) => {
+                    provide_liquidity(
+                        deps,
+                        env,
+                        MessageInfo {
+                            sender: sender_addr,
+                            funds: vec![],
+                        },
+                        [received_asset.clone(), other_asset],
+                        slippage_tolerance,
+                        receiver,
+                        Some(received_asset),
+                    )
+                }
+                Some(_) => Err(ContractError::PendingDepositExists {}),
+                None => deposit_pending(deps, sender_addr, received_asset),
+            }
+        }
+        Ok(Cw20HookMsg::WithdrawLiquidity {
+            min_assets,
+            receiver,
+            deadline,
+        }) => {
+            assert_deadline(&env, deadline)?;
+
+            let config: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+            if deps.api.addr_canonicalize(info.sender.as_str())? != config.liquidity_token {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let sender_addr = deps.api.addr_validate(cw20_msg.sender.as_str())?;
+            let receiver_addr = if let Some(receiver) = receiver {
+                Some(deps.api.addr_validate(&receiver)?)
+            } else {
+                None
+            };
+            withdraw_liquidity(
+                deps,
+                env,
+                info,
+                sender_addr,
+                cw20_msg.amount,
+                min_assets,
+                receiver_addr,
+            )
+        }
+        Ok(Cw20HookMsg::WithdrawLiquidityToSingleAsset {
+            ask_asset_info,
+            min_receive,
+        }) => {
             let config: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
             if deps.api.addr_canonicalize(info.sender.as_str())? != config.liquidity_token {
                 return Err(ContractError::Unauthorized {});
             }
 
             let sender_addr = deps.api.addr_validate(cw20_msg.sender.as_str())?;
-            withdraw_liquidity(deps, env, info, sender_addr, cw20_msg.amount)
+            withdraw_liquidity_to_single_asset(
+                deps,
+                env,
+                sender_addr,
+                cw20_msg.amount,
+                ask_asset_info,
+                min_receive,
+            )
+        }
+        Ok(Cw20HookMsg::ProvideSingleSidedLiquidity {
+            min_share,
+            receiver,
+        }) => {
+            // only asset contract can execute this message
+            let config: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+            let asset = Asset {
+                info: AssetInfo::Token {
+                    contract_addr: info.sender.to_string(),
+                },
+                amount: cw20_msg.amount,
+            };
+            if !config.asset_infos.iter().any(|a| {
+                a.to_normal(deps.api)
+                    .map_or(false, |a| a.equal(&asset.info))
+            }) {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let sender_addr = deps.api.addr_validate(cw20_msg.sender.as_str())?;
+            provide_single_sided_liquidity(deps, env, sender_addr, asset, min_share, receiver)
         }
         Err(err) => Err(ContractError::Std(err)),
     }
@@ -202,8 +474,8 @@ pub fn receive_cw20(
 
 /// This just stores the result for future query
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
-    let res = parse_reply_instantiate_data(msg).unwrap();
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let res = parse_reply_instantiate_data(msg)?;
     let liquidity_token = res.contract_address;
 
     let api = deps.api;
@@ -215,7 +487,8 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
     Ok(Response::new().add_attribute("liquidity_token_addr", liquidity_token))
 }
 
-/// CONTRACT - should approve contract to use the amount of token
+/// CONTRACT - should approve contract to use the amount of token, unless the token is
+/// `received_asset` or the pending deposit of the sender
 pub fn provide_liquidity(
     deps: DepsMut,
     env: Env,
@@ -223,7 +496,11 @@ pub fn provide_liquidity(
     assets: [Asset; 2],
     slippage_tolerance: Option<Decimal>,
     receiver: Option<String>,
+    received_asset: Option<Asset>,
 ) -> Result<Response, ContractError> {
+    assert_no_flash_swap(deps.storage)?;
+    assert_deposits_not_paused(deps.storage)?;
+
     for asset in assets.iter() {
         // check the balance of native token is sent with the message
         asset.assert_sent_native_token_balance(&info)?;
@@ -232,23 +509,47 @@ pub fn provide_liquidity(
     // get information of the pair
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
 
-    // query the information of the pair of assets
-    let mut pools: [Asset; 2] =
-        pair_info.query_pools(&deps.querier, deps.api, env.contract.address.clone())?;
+    // query the reserves of the pair, which do not include the deposits yet
+    let pools: [Asset; 2] = load_pools(deps.as_ref(), &pair_info)?;
 
     // get the amount of assets that user deposited after checking the assets is same as the assets in pair
-    let deposits: [Uint128; 2] = [
-        assets
+    for asset in assets.iter() {
+        if !pools.iter().any(|pool| pool.info.equal(&asset.info)) {
+            return Err(ContractError::AssetNotInPair {
+                asset: asset.info.to_string(),
+            });
+        }
+    }
+    let mut deposits: [Uint128; 2] = [Uint128::zero(), Uint128::zero()];
+    for (i, pool) in pools.iter().enumerate() {
+        deposits[i] = assets
             .iter()
-            .find(|a| a.info.equal(&pools[0].info))
+            .find(|a| a.info.equal(&pool.info))
             .map(|a| a.amount)
-            .expect("Wrong asset info is given"),
-        assets
+            .ok_or(ContractError::AssetMismatch {})?;
+    }
+
+    // the tokens received with the message or kept as the pending deposit of the sender
+    // are already in the balance of the contract
+    let pending_deposit = PENDING_DEPOSITS.may_load(deps.storage, &info.sender)?;
+    let mut deposited = [false, false];
+    let mut pending_deposit_used = false;
+    for (i, pool) in pools.iter().enumerate() {
+        let deposit = received_asset
             .iter()
-            .find(|a| a.info.equal(&pools[1].info))
-            .map(|a| a.amount)
-            .expect("Wrong asset info is given"),
-    ];
+            .chain(pending_deposit.iter())
+            .find(|deposit| deposit.info.equal(&pool.info));
+        if let Some(deposit) = deposit {
+            if deposit.amount != deposits[i] {
+                return Err(ContractError::AssetMismatch {});
+            }
+
+            deposited[i] = true;
+            pending_deposit_used |= received_asset.as_ref().map_or(true, |received_asset| {
+                !received_asset.info.equal(&pool.info)
+            });
+        }
+    }
 
     // For accurately calculate return values of compute_swap function,
     // It is required to use Decimal256 type for all division calculations
@@ -260,45 +561,66 @@ pub fn provide_liquidity(
     let amount_0 = Uint256::from(deposits[0]);
     let amount_1 = Uint256::from(deposits[1]);
 
-    Decimal256::from_uint256((pool_0 + amount_0) * (pool_1 + amount_1));
+    let (product, overflow) = (pool_0 + amount_0).0.overflowing_mul((pool_1 + amount_1).0);
+    if overflow || product.overflowing_mul(Decimal256::DECIMAL_FRACTIONAL).1 {
+        return Err(ContractError::PoolOverflow {});
+    }
+
+    // accumulate the prices of the reserves before the deposit
+    update_price_accumulators(deps.storage, &env, [pools[0].amount, pools[1].amount])?;
+
+    // if the user provides the slippage tolerance, we should check it
+    assert_slippage_tolerance(&slippage_tolerance, &deposits, &pools)?;
+
+    // get the address of the LP token
+    let liquidity_token = deps.api.addr_humanize(&pair_info.liquidity_token)?;
+
+    // get total supply of the LP token
+    let total_share = query_token_info(&deps.querier, liquidity_token)?.total_supply;
+
+    // only the deposits at the ratio of the pools are used, the excess is refunded
+    let used_deposits = compute_used_deposits(total_share, deposits, &pools);
 
-    // If the asset is a token, the value of pools[i] is correct. But we must take the token from the user.
-    // If the asset is a native token, the amount of native token is already sent with the message to the pool.
-    // So we must subtract that amount of native token from the pools[i].
-    // pools[] will be used to calculate the amount of LP token to mint after.
     let mut messages: Vec<CosmosMsg> = vec![];
-    for (i, pool) in pools.iter_mut().enumerate() {
-        // If the asset 'pool' is a token, then we need to execute TransferFrom msg to receive funds
-        // User must approve the pool contract to transfer the token before calling this function
-        if let AssetInfo::Token { contract_addr, .. } = &pool.info {
+    let mut refund_assets: Vec<Asset> = vec![];
+    for (i, pool) in pools.iter().enumerate() {
+        let refund_amount = deposits[i].checked_sub(used_deposits[i])?;
+        refund_assets.push(Asset {
+            info: pool.info.clone(),
+            amount: refund_amount,
+        });
+
+        if deposited[i] {
+            // the excess of the token in the balance of the contract is sent back to the sender
+            if !refund_amount.is_zero() {
+                messages.push(refund_assets[i].clone().into_msg(info.sender.clone())?);
+            }
+        } else if let AssetInfo::Token { contract_addr, .. } = &pool.info {
+            // If the asset 'pool' is a token, then we need to execute TransferFrom msg to receive funds
+            // User must approve the pool contract to transfer the token before calling this function
             messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: contract_addr.to_string(),
                 msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
                     owner: info.sender.to_string(),
                     recipient: env.contract.address.to_string(),
-                    amount: deposits[i],
+                    amount: used_deposits[i],
                 })?,
                 funds: vec![],
             }));
-        } else {
-            // If the asset 'pool' is native token, balance is already increased
-            // To calculated properly we should subtract user deposit from the pool
-            pool.amount = pool.amount.checked_sub(deposits[i])?;
+        } else if !refund_amount.is_zero() {
+            // the excess of native token is sent back to the sender
+            messages.push(refund_assets[i].clone().into_msg(info.sender.clone())?);
         }
     }
 
-    // if the user provides the slippage tolerance, we should check it
-    assert_slippage_tolerance(&slippage_tolerance, &deposits, &pools)?;
-
-    // get the address of the LP token
-    let liquidity_token = deps.api.addr_humanize(&pair_info.liquidity_token)?;
-
-    // get total supply of the LP token
-    let total_share = query_token_info(&deps.querier, liquidity_token)?.total_supply;
-
     // calculate the amount of LP token is minted to the user
-    let mut share =
-        calculate_lp_token_amount_to_user(&info, &pair_info, total_share, deposits, pools).unwrap();
+    let mut share = calculate_lp_token_amount_to_user(
+        &info,
+        &pair_info,
+        total_share,
+        used_deposits,
+        pools.clone(),
+    )?;
 
     // prevent providing free token (one of the deposits is zero)
     if share.is_zero() {
@@ -310,7 +632,12 @@ pub fn provide_liquidity(
     let receiver = receiver.unwrap_or_else(|| info.sender.to_string());
 
     if total_share == Uint128::zero() {
-        // mint amount of 'share' LP token to the receiver
+        // the minimum liquidity is locked forever in the LP token contract
+        let min_liquidity = MIN_LIQUIDITY.load(deps.storage)?;
+        if share <= min_liquidity {
+            return Err(ContractError::MinimumLiquidityNotReached {});
+        }
+
         messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: deps
                 .api
@@ -321,13 +648,26 @@ pub fn provide_liquidity(
                     .api
                     .addr_humanize(&pair_info.liquidity_token)?
                     .to_string(),
-                amount: Uint128::from(LP_TOKEN_RESERVED_AMOUNT),
+                amount: min_liquidity,
             })?,
             funds: vec![],
         }));
-        share = share.checked_sub(Uint128::from(LP_TOKEN_RESERVED_AMOUNT))?;
+        share = share.checked_sub(min_liquidity)?;
+    }
+
+    if pending_deposit_used {
+        remove_pending_deposit(deps.storage, &pools, &info.sender)?;
     }
 
+    // the used deposits are added to the reserves
+    RESERVES.save(
+        deps.storage,
+        &[
+            pools[0].amount.checked_add(used_deposits[0])?,
+            pools[1].amount.checked_add(used_deposits[1])?,
+        ],
+    )?;
+
     // mint amount of 'share' LP token to the receiver
     messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
         contract_addr: deps
@@ -347,118 +687,515 @@ pub fn provide_liquidity(
         ("receiver", receiver.as_str()),
         ("assets", &format!("{}, {}", assets[0], assets[1])),
         ("share", &share.to_string()),
+        (
+            "refund_assets",
+            &format!("{}, {}", refund_assets[0], refund_assets[1]),
+        ),
     ]))
 }
 
-pub fn withdraw_liquidity(
+/// The received token is kept by the pair until the sender provides the other asset
+pub fn deposit_pending(
     deps: DepsMut,
-    env: Env,
-    _info: MessageInfo,
     sender: Addr,
-    amount: Uint128,
+    deposit: Asset,
 ) -> Result<Response, ContractError> {
-    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
-    let liquidity_addr: Addr = deps.api.addr_humanize(&pair_info.liquidity_token)?;
+    assert_deposits_not_paused(deps.storage)?;
 
-    let pools: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, env.contract.address)?;
-    let total_share: Uint128 = query_token_info(&deps.querier, liquidity_addr)?.total_supply;
+    if deposit.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
 
-    let share_ratio: Decimal = Decimal::from_ratio(amount, total_share);
-    let refund_assets: Vec<Asset> = pools
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let pools: [Asset; 2] = load_pools(deps.as_ref(), &pair_info)?;
+    let index = pools
         .iter()
-        .map(|a| Asset {
-            info: a.info.clone(),
-            amount: a.amount * share_ratio,
-        })
-        .collect();
+        .position(|pool| pool.info.equal(&deposit.info))
+        .ok_or(ContractError::AssetMismatch {})?;
+
+    PENDING_DEPOSITS.save(deps.storage, &sender, &deposit)?;
+    PENDING_DEPOSIT_AMOUNTS.update(deps.storage, |mut amounts| -> StdResult<_> {
+        amounts[index] = amounts[index].checked_add(deposit.amount)?;
+        Ok(amounts)
+    })?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "deposit_pending"),
+        ("sender", sender.as_str()),
+        ("deposit", &deposit.to_string()),
+    ]))
+}
+
+/// The pending deposit is sent back to the sender, it is never paused
+pub fn withdraw_pending_deposit(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let pools: [Asset; 2] = load_pools(deps.as_ref(), &pair_info)?;
+    let deposit = remove_pending_deposit(deps.storage, &pools, &info.sender)?;
 
-    // update pool info
     Ok(Response::new()
-        .add_messages(vec![
-            refund_assets[0].clone().into_msg(sender.clone())?,
-            refund_assets[1].clone().into_msg(sender.clone())?,
-            // burn liquidity token
-            CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: deps
-                    .api
-                    .addr_humanize(&pair_info.liquidity_token)?
-                    .to_string(),
-                msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
-                funds: vec![],
-            }),
-        ])
+        .add_message(deposit.clone().into_msg(info.sender.clone())?)
         .add_attributes(vec![
-            ("action", "withdraw_liquidity"),
-            ("sender", sender.as_str()),
-            ("withdrawn_share", &amount.to_string()),
-            (
-                "refund_assets",
-                &format!("{}, {}", refund_assets[0], refund_assets[1]),
-            ),
+            ("action", "withdraw_pending_deposit"),
+            ("sender", info.sender.as_str()),
+            ("deposit", &deposit.to_string()),
         ]))
 }
 
-// CONTRACT - a user must do token approval
-#[allow(clippy::too_many_arguments)]
-pub fn swap(
+fn remove_pending_deposit(
+    storage: &mut dyn Storage,
+    pools: &[Asset; 2],
+    sender: &Addr,
+) -> Result<Asset, ContractError> {
+    let deposit = PENDING_DEPOSITS
+        .may_load(storage, sender)?
+        .ok_or(ContractError::PendingDepositNotFound {})?;
+    let index = pools
+        .iter()
+        .position(|pool| pool.info.equal(&deposit.info))
+        .ok_or(ContractError::AssetMismatch {})?;
+
+    PENDING_DEPOSITS.remove(storage, sender);
+    PENDING_DEPOSIT_AMOUNTS.update(storage, |mut amounts| -> StdResult<_> {
+        amounts[index] = amounts[index].checked_sub(deposit.amount)?;
+        Ok(amounts)
+    })?;
+
+    Ok(deposit)
+}
+
+/// The deposit must already be in the balance of the contract
+pub fn provide_single_sided_liquidity(
     deps: DepsMut,
     env: Env,
-    info: MessageInfo,
     sender: Addr,
-    offer_asset: Asset,
-    belief_price: Option<Decimal>,
-    max_spread: Option<Decimal>,
-    to: Option<Addr>,
+    asset: Asset,
+    min_share: Option<Uint128>,
+    receiver: Option<String>,
 ) -> Result<Response, ContractError> {
-    offer_asset.assert_sent_native_token_balance(&info)?;
+    assert_no_flash_swap(deps.storage)?;
+    assert_deposits_not_paused(deps.storage)?;
+
+    if asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
 
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
-    // get pool info of the pair contract
-    let pools: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, env.contract.address)?;
-    // Commission rate OR Fee amount for framework
-    let commission_rate = COMMISSION_RATE_INFO.load(deps.storage)?;
+    let pools: [Asset; 2] = load_pools(deps.as_ref(), &pair_info)?;
 
-    let offer_pool: Asset;
-    let ask_pool: Asset;
+    let offer_index = if asset.info.equal(&pools[0].info) {
+        0
+    } else if asset.info.equal(&pools[1].info) {
+        1
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    };
 
-    let offer_decimal: u8;
-    let ask_decimal: u8;
+    // accumulate the prices of the reserves before the deposit
+    update_price_accumulators(deps.storage, &env, [pools[0].amount, pools[1].amount])?;
 
-    // If the asset balance is already increased
-    // To calculated properly we should subtract user deposit from the pool
-    if offer_asset.info.equal(&pools[0].info) {
-        offer_pool = Asset::new(
-            pools[0].info.clone(),
-            pools[0].amount.checked_sub(offer_asset.amount)?,
-        );
-        ask_pool = pools[1].clone();
+    let liquidity_token = deps.api.addr_humanize(&pair_info.liquidity_token)?;
+    let total_share = query_token_info(&deps.querier, liquidity_token.clone())?.total_supply;
 
-        offer_decimal = pair_info.asset_decimals[0];
-        ask_decimal = pair_info.asset_decimals[1];
-    } else if offer_asset.info.equal(&pools[1].info) {
-        offer_pool = Asset::new(
-            pools[1].info.clone(),
-            pools[1].amount.checked_sub(offer_asset.amount)?,
-        );
-        ask_pool = pools[0].clone();
+    let single_sided = compute_single_sided_provision(
+        deps.storage,
+        &env,
+        &pair_info,
+        total_share,
+        pools.clone(),
+        offer_index,
+        asset.amount,
+    )?;
 
-        offer_decimal = pair_info.asset_decimals[1];
-        ask_decimal = pair_info.asset_decimals[0];
-    } else {
-        return Err(ContractError::AssetMismatch {});
+    if single_sided.share.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    if let Some(min_share) = min_share {
+        if single_sided.share < min_share {
+            return Err(ContractError::MaxSlippageAssertion {});
+        }
     }
 
-    let offer_amount = offer_asset.amount;
-    let (return_amount, spread_amount, commission_amount) = compute_swap(
-        offer_pool.amount,
-        ask_pool.amount,
-        offer_amount,
-        commission_rate,
-    );
+    // the protocol fee of the inner swap stays in the contract balance but is excluded from the pool
+    let ask_index = 1 - offer_index;
+    if !single_sided.protocol_fee_amount.is_zero() {
+        PROTOCOL_FEES.update(deps.storage, |mut fees| -> StdResult<_> {
+            fees[ask_index] = fees[ask_index].checked_add(single_sided.protocol_fee_amount)?;
+            Ok(fees)
+        })?;
+    }
 
-    let return_asset = Asset {
-        info: ask_pool.info.clone(),
-        amount: return_amount,
+    // the whole deposit ends in the pool, the return of the inner swap is provided back
+    let mut reserves = [pools[0].amount, pools[1].amount];
+    reserves[offer_index] = reserves[offer_index].checked_add(asset.amount)?;
+    reserves[ask_index] = reserves[ask_index].checked_sub(single_sided.protocol_fee_amount)?;
+    RESERVES.save(deps.storage, &reserves)?;
+
+    // mint LP token to the receiver if provided, otherwise to the sender
+    let receiver = receiver.unwrap_or_else(|| sender.to_string());
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: liquidity_token.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: receiver.clone(),
+                amount: single_sided.share,
+            })?,
+            funds: vec![],
+        }))
+        .add_attributes(vec![
+            ("action", "provide_single_sided_liquidity"),
+            ("sender", sender.as_str()),
+            ("receiver", receiver.as_str()),
+            ("asset", &asset.to_string()),
+            ("swap_amount", &single_sided.swap_amount.to_string()),
+            ("return_amount", &single_sided.return_amount.to_string()),
+            ("share", &single_sided.share.to_string()),
+        ]))
+}
+
+/// Result of a single-sided provision, the deposit being partly swapped to the other asset
+struct SingleSidedProvision {
+    swap_amount: Uint128,
+    return_amount: Uint128,
+    protocol_fee_amount: Uint128,
+    share: Uint128,
+}
+
+/// `pools` are the reserves before the deposit of `amount` of the asset at `offer_index`
+fn compute_single_sided_provision(
+    storage: &dyn Storage,
+    env: &Env,
+    pair_info: &PairInfoRaw,
+    total_share: Uint128,
+    pools: [Asset; 2],
+    offer_index: usize,
+    amount: Uint128,
+) -> Result<SingleSidedProvision, ContractError> {
+    // the swapped part needs a price, so the pool must be initialized with both assets
+    if total_share.is_zero() || pools[0].amount.is_zero() || pools[1].amount.is_zero() {
+        return Err(ContractError::NoLiquidity {});
+    }
+
+    let ask_index = 1 - offer_index;
+    let commission_rate = COMMISSION_RATE_INFO.load(storage)?;
+    let protocol_fee_rate = PROTOCOL_FEE_RATE.load(storage)?;
+    let offer_decimal = pair_info.asset_decimals[offer_index];
+    let ask_decimal = pair_info.asset_decimals[ask_index];
+
+    let swap_amount = match pair_info.pair_type {
+        PairType::Xyk => {
+            compute_single_sided_swap_amount(pools[offer_index].amount, amount, commission_rate)
+        }
+        PairType::Stable { .. } => compute_stable_single_sided_swap_amount(
+            pools[offer_index].amount,
+            pools[ask_index].amount,
+            amount,
+            offer_decimal,
+            ask_decimal,
+            query_current_amp(storage, env)?,
+            commission_rate,
+        )?,
+    };
+
+    let (return_amount, _, commission_amount) = compute_pair_swap(
+        storage,
+        env,
+        &pair_info.pair_type,
+        pools[offer_index].amount,
+        pools[ask_index].amount,
+        swap_amount,
+        offer_decimal,
+        ask_decimal,
+        commission_rate,
+    )?;
+    let (_, protocol_fee_amount) = compute_protocol_fee(commission_amount, protocol_fee_rate);
+
+    // the remaining deposit and the return of the swap are provided to the pool after the swap
+    let mut pools = pools;
+    let mut deposits = [Uint128::zero(), Uint128::zero()];
+    pools[offer_index].amount = pools[offer_index].amount.checked_add(swap_amount)?;
+    pools[ask_index].amount = pools[ask_index]
+        .amount
+        .checked_sub(return_amount.checked_add(protocol_fee_amount)?)?;
+    deposits[offer_index] = amount.checked_sub(swap_amount)?;
+    deposits[ask_index] = return_amount;
+
+    Ok(SingleSidedProvision {
+        swap_amount,
+        return_amount,
+        protocol_fee_amount,
+        share: compute_share(total_share, deposits, &pools)?,
+    })
+}
+
+pub fn withdraw_liquidity(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    sender: Addr,
+    amount: Uint128,
+    min_assets: Option<[Asset; 2]>,
+    receiver: Option<Addr>,
+) -> Result<Response, ContractError> {
+    assert_no_flash_swap(deps.storage)?;
+
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let liquidity_addr: Addr = deps.api.addr_humanize(&pair_info.liquidity_token)?;
+
+    let pools: [Asset; 2] = load_pools(deps.as_ref(), &pair_info)?;
+    let total_share: Uint128 = query_token_info(&deps.querier, liquidity_addr)?.total_supply;
+
+    // accumulate the prices of the reserves before the withdrawal
+    update_price_accumulators(deps.storage, &env, [pools[0].amount, pools[1].amount])?;
+
+    let refund_assets = compute_withdrawn_assets(&pools, amount, total_share);
+
+    // check the minimum amounts if the user provides them
+    if let Some(min_assets) = min_assets {
+        for min_asset in min_assets.iter() {
+            let refund_asset = refund_assets
+                .iter()
+                .find(|a| a.info.equal(&min_asset.info))
+                .ok_or(ContractError::AssetMismatch {})?;
+            if refund_asset.amount < min_asset.amount {
+                return Err(ContractError::MaxSlippageAssertion {});
+            }
+        }
+    }
+
+    // update pool info
+    RESERVES.save(
+        deps.storage,
+        &[
+            pools[0].amount.checked_sub(refund_assets[0].amount)?,
+            pools[1].amount.checked_sub(refund_assets[1].amount)?,
+        ],
+    )?;
+
+    let receiver = receiver.unwrap_or_else(|| sender.clone());
+
+    Ok(Response::new()
+        .add_messages(vec![
+            refund_assets[0].clone().into_msg(receiver.clone())?,
+            refund_assets[1].clone().into_msg(receiver.clone())?,
+            // burn liquidity token
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: deps
+                    .api
+                    .addr_humanize(&pair_info.liquidity_token)?
+                    .to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+                funds: vec![],
+            }),
+        ])
+        .add_attributes(vec![
+            ("action", "withdraw_liquidity"),
+            ("sender", sender.as_str()),
+            ("withdrawn_share", &amount.to_string()),
+            (
+                "refund_assets",
+                &format!("{}, {}", refund_assets[0], refund_assets[1]),
+            ),
+            ("receiver", receiver.as_str()),
+        ]))
+}
+
+/// The other withdrawn asset is swapped against the pool after the withdrawal
+pub fn withdraw_liquidity_to_single_asset(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    amount: Uint128,
+    ask_asset_info: AssetInfo,
+    min_receive: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    assert_no_flash_swap(deps.storage)?;
+    // the withdrawn asset is swapped, so it is rejected while the swaps are paused
+    assert_swaps_not_paused(deps.storage)?;
+
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let liquidity_addr: Addr = deps.api.addr_humanize(&pair_info.liquidity_token)?;
+
+    let pools: [Asset; 2] = load_pools(deps.as_ref(), &pair_info)?;
+    let total_share: Uint128 =
+        query_token_info(&deps.querier, liquidity_addr.clone())?.total_supply;
+
+    let ask_index = if ask_asset_info.equal(&pools[0].info) {
+        0
+    } else if ask_asset_info.equal(&pools[1].info) {
+        1
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    };
+    let offer_index = 1 - ask_index;
+
+    // accumulate the prices of the reserves before the withdrawal
+    update_price_accumulators(deps.storage, &env, [pools[0].amount, pools[1].amount])?;
+
+    let refund_assets = compute_withdrawn_assets(&pools, amount, total_share);
+
+    // swap the withdrawn offer asset with the pools left after the withdrawal
+    let commission_rate = COMMISSION_RATE_INFO.load(deps.storage)?;
+    let protocol_fee_rate = PROTOCOL_FEE_RATE.load(deps.storage)?;
+    let (return_amount, _, commission_amount) = compute_pair_swap(
+        deps.storage,
+        &env,
+        &pair_info.pair_type,
+        pools[offer_index]
+            .amount
+            .checked_sub(refund_assets[offer_index].amount)?,
+        pools[ask_index]
+            .amount
+            .checked_sub(refund_assets[ask_index].amount)?,
+        refund_assets[offer_index].amount,
+        pair_info.asset_decimals[offer_index],
+        pair_info.asset_decimals[ask_index],
+        commission_rate,
+    )?;
+    let (_, protocol_fee_amount) = compute_protocol_fee(commission_amount, protocol_fee_rate);
+
+    let return_asset = Asset {
+        info: ask_asset_info,
+        amount: refund_assets[ask_index].amount.checked_add(return_amount)?,
+    };
+    if let Some(min_receive) = min_receive {
+        if return_asset.amount < min_receive {
+            return Err(ContractError::MaxSlippageAssertion {});
+        }
+    }
+
+    // the protocol fee of the inner swap stays in the contract balance but is excluded from the pool
+    if !protocol_fee_amount.is_zero() {
+        PROTOCOL_FEES.update(deps.storage, |mut fees| -> StdResult<_> {
+            fees[ask_index] = fees[ask_index].checked_add(protocol_fee_amount)?;
+            Ok(fees)
+        })?;
+    }
+
+    // the withdrawn offer asset is swapped back into the pool
+    let mut reserves = [pools[0].amount, pools[1].amount];
+    reserves[ask_index] = reserves[ask_index]
+        .checked_sub(return_asset.amount)?
+        .checked_sub(protocol_fee_amount)?;
+    RESERVES.save(deps.storage, &reserves)?;
+
+    Ok(Response::new()
+        .add_messages(vec![
+            return_asset.clone().into_msg(sender.clone())?,
+            // burn liquidity token
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: liquidity_addr.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+                funds: vec![],
+            }),
+        ])
+        .add_attributes(vec![
+            ("action", "withdraw_liquidity_to_single_asset"),
+            ("sender", sender.as_str()),
+            ("withdrawn_share", &amount.to_string()),
+            (
+                "swap_amount",
+                &refund_assets[offer_index].amount.to_string(),
+            ),
+            ("return_asset", &return_asset.to_string()),
+        ]))
+}
+
+/// Returns the share of the pools of `amount` LP tokens
+fn compute_withdrawn_assets(
+    pools: &[Asset; 2],
+    amount: Uint128,
+    total_share: Uint128,
+) -> [Asset; 2] {
+    let share_ratio: Decimal = Decimal::from_ratio(amount, total_share);
+    [
+        Asset {
+            info: pools[0].info.clone(),
+            amount: pools[0].amount * share_ratio,
+        },
+        Asset {
+            info: pools[1].info.clone(),
+            amount: pools[1].amount * share_ratio,
+        },
+    ]
+}
+
+// CONTRACT - a user must do token approval
+#[allow(clippy::too_many_arguments)]
+pub fn swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    offer_asset: Asset,
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    to: Option<Addr>,
+) -> Result<Response, ContractError> {
+    assert_no_flash_swap(deps.storage)?;
+    assert_swaps_not_paused(deps.storage)?;
+
+    offer_asset.assert_sent_native_token_balance(&info)?;
+
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    // get pool info of the pair contract
+    let pools: [Asset; 2] = load_pools(deps.as_ref(), &pair_info)?;
+    // Commission rate OR Fee amount for framework
+    let commission_rate = COMMISSION_RATE_INFO.load(deps.storage)?;
+    let protocol_fee_rate = PROTOCOL_FEE_RATE.load(deps.storage)?;
+
+    let offer_pool: Asset;
+    let ask_pool: Asset;
+
+    let offer_decimal: u8;
+    let ask_decimal: u8;
+    let ask_index: usize;
+
+    // the reserves do not include the offer asset yet
+    if offer_asset.info.equal(&pools[0].info) {
+        offer_pool = pools[0].clone();
+        ask_pool = pools[1].clone();
+
+        offer_decimal = pair_info.asset_decimals[0];
+        ask_decimal = pair_info.asset_decimals[1];
+        ask_index = 1;
+    } else if offer_asset.info.equal(&pools[1].info) {
+        offer_pool = pools[1].clone();
+        ask_pool = pools[0].clone();
+
+        offer_decimal = pair_info.asset_decimals[1];
+        ask_decimal = pair_info.asset_decimals[0];
+        ask_index = 0;
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    }
+
+    // accumulate the prices of the reserves before the swap
+    let mut reserves = [offer_pool.amount, offer_pool.amount];
+    reserves[ask_index] = ask_pool.amount;
+    update_price_accumulators(deps.storage, &env, reserves)?;
+
+    let offer_amount = offer_asset.amount;
+    let (return_amount, spread_amount, commission_amount) = compute_pair_swap(
+        deps.storage,
+        &env,
+        &pair_info.pair_type,
+        offer_pool.amount,
+        ask_pool.amount,
+        offer_amount,
+        offer_decimal,
+        ask_decimal,
+        commission_rate,
+    )?;
+    let (_, protocol_fee_amount) = compute_protocol_fee(commission_amount, protocol_fee_rate);
+
+    let return_asset = Asset {
+        info: ask_pool.info.clone(),
+        amount: return_amount,
     };
 
     // check max spread limit if exist
@@ -472,6 +1209,21 @@ pub fn swap(
         ask_decimal,
     )?;
 
+    // the protocol fee stays in the contract balance but is excluded from the pool
+    if !protocol_fee_amount.is_zero() {
+        PROTOCOL_FEES.update(deps.storage, |mut fees| -> StdResult<_> {
+            fees[ask_index] = fees[ask_index].checked_add(protocol_fee_amount)?;
+            Ok(fees)
+        })?;
+    }
+
+    let mut reserves = [offer_pool.amount.checked_add(offer_amount)?; 2];
+    reserves[ask_index] = ask_pool
+        .amount
+        .checked_sub(return_amount)?
+        .checked_sub(protocol_fee_amount)?;
+    RESERVES.save(deps.storage, &reserves)?;
+
     let receiver = to.unwrap_or_else(|| sender.clone());
 
     let mut messages: Vec<CosmosMsg> = vec![];
@@ -491,6 +1243,104 @@ pub fn swap(
         ("return_amount", &return_amount.to_string()),
         ("spread_amount", &spread_amount.to_string()),
         ("commission_amount", &commission_amount.to_string()),
+        ("protocol_fee_amount", &protocol_fee_amount.to_string()),
+    ]))
+}
+
+/// The offer asset is already in the balance of the contract, only the amount required
+/// to receive the ask asset is swapped and the rest is refunded to the sender
+pub fn swap_exact_out(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    offer_asset: Asset,
+    ask_asset: Asset,
+    max_offer_amount: Uint128,
+    to: Option<Addr>,
+) -> Result<Response, ContractError> {
+    assert_no_flash_swap(deps.storage)?;
+    assert_swaps_not_paused(deps.storage)?;
+
+    if ask_asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    // get pool info of the pair contract
+    let pools: [Asset; 2] = load_pools(deps.as_ref(), &pair_info)?;
+    // Commission rate OR Fee amount for framework
+    let commission_rate = COMMISSION_RATE_INFO.load(deps.storage)?;
+    let protocol_fee_rate = PROTOCOL_FEE_RATE.load(deps.storage)?;
+
+    let ask_index = pools
+        .iter()
+        .position(|pool| pool.info.equal(&ask_asset.info))
+        .ok_or(ContractError::AssetMismatch {})?;
+    let offer_index = 1 - ask_index;
+    if !offer_asset.info.equal(&pools[offer_index].info) {
+        return Err(ContractError::AssetMismatch {});
+    }
+
+    // accumulate the prices of the reserves before the swap
+    update_price_accumulators(deps.storage, &env, [pools[0].amount, pools[1].amount])?;
+
+    let (offer_amount, spread_amount, commission_amount) = compute_pair_offer_amount(
+        deps.storage,
+        &env,
+        &pair_info.pair_type,
+        pools[offer_index].amount,
+        pools[ask_index].amount,
+        ask_asset.amount,
+        pair_info.asset_decimals[offer_index],
+        pair_info.asset_decimals[ask_index],
+        commission_rate,
+    )?;
+    if offer_amount > max_offer_amount {
+        return Err(ContractError::MaxOfferAmountAssertion {});
+    }
+    if offer_amount > offer_asset.amount {
+        return Err(ContractError::InsufficientOfferAmount { offer_amount });
+    }
+    let (_, protocol_fee_amount) = compute_protocol_fee(commission_amount, protocol_fee_rate);
+
+    // the protocol fee stays in the contract balance but is excluded from the pool
+    if !protocol_fee_amount.is_zero() {
+        PROTOCOL_FEES.update(deps.storage, |mut fees| -> StdResult<_> {
+            fees[ask_index] = fees[ask_index].checked_add(protocol_fee_amount)?;
+            Ok(fees)
+        })?;
+    }
+
+    let mut reserves = [pools[offer_index].amount.checked_add(offer_amount)?; 2];
+    reserves[ask_index] = pools[ask_index]
+        .amount
+        .checked_sub(ask_asset.amount)?
+        .checked_sub(protocol_fee_amount)?;
+    RESERVES.save(deps.storage, &reserves)?;
+
+    let receiver = to.unwrap_or_else(|| sender.clone());
+    let refund_asset = Asset {
+        info: offer_asset.info.clone(),
+        amount: offer_asset.amount.checked_sub(offer_amount)?,
+    };
+
+    let mut messages: Vec<CosmosMsg> = vec![ask_asset.clone().into_msg(receiver.clone())?];
+    if !refund_asset.amount.is_zero() {
+        messages.push(refund_asset.clone().into_msg(sender.clone())?);
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "swap_exact_out"),
+        ("sender", sender.as_str()),
+        ("receiver", receiver.as_str()),
+        ("offer_asset", &offer_asset.info.to_string()),
+        ("ask_asset", &ask_asset.info.to_string()),
+        ("offer_amount", &offer_amount.to_string()),
+        ("return_amount", &ask_asset.amount.to_string()),
+        ("refund_amount", &refund_asset.amount.to_string()),
+        ("spread_amount", &spread_amount.to_string()),
+        ("commission_amount", &commission_amount.to_string()),
+        ("protocol_fee_amount", &protocol_fee_amount.to_string()),
     ]))
 }
 
@@ -536,31 +1386,395 @@ pub fn update_native_token_decimals(
     ]))
 }
 
+/// Only the factory can execute it
+pub fn set_pause(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    pause_status: PauseStatus,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if info.sender != config.halo_factory {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    PAUSE_STATUS.save(deps.storage, &pause_status)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "set_pause"),
+        ("swaps", &pause_status.swaps.to_string()),
+        ("deposits", &pause_status.deposits.to_string()),
+    ]))
+}
+
+pub fn update_commission_rate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    commission_rate: Decimal256,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if info.sender != config.halo_factory {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // commission rate must be less than 1 equivalents to 0% up to 100%, which would take the whole return
+    if commission_rate >= Decimal256::one() {
+        return Err(ContractError::InvalidCommissionRate {});
+    }
+
+    COMMISSION_RATE_INFO.save(deps.storage, &commission_rate)?;
+    PAIR_INFO.update(deps.storage, |mut pair_info| -> StdResult<_> {
+        pair_info.commission_rate = commission_rate;
+        Ok(pair_info)
+    })?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_commission_rate"),
+        ("commission_rate", &commission_rate.to_string()),
+    ]))
+}
+
+/// Anyone can execute it, the fees are always sent to the fee collector of the factory
+pub fn collect_protocol_fees(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let fee_collector =
+        match query_factory_config(&deps.querier, config.halo_factory)?.fee_collector {
+            Some(fee_collector) => deps.api.addr_validate(&fee_collector)?,
+            None => return Err(ContractError::FeeCollectorNotSet {}),
+        };
+
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let fees = PROTOCOL_FEES.load(deps.storage)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut collected_assets: Vec<String> = vec![];
+    for (i, fee) in fees.iter().enumerate() {
+        if fee.is_zero() {
+            continue;
+        }
+
+        let asset = Asset {
+            info: pair_info.asset_infos[i].to_normal(deps.api)?,
+            amount: *fee,
+        };
+        collected_assets.push(asset.to_string());
+        messages.push(asset.into_msg(fee_collector.clone())?);
+    }
+
+    PROTOCOL_FEES.save(deps.storage, &[Uint128::zero(), Uint128::zero()])?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "collect_protocol_fees"),
+        ("pair", env.contract.address.as_str()),
+        ("fee_collector", fee_collector.as_str()),
+        ("collected_assets", &collected_assets.join(", ")),
+    ]))
+}
+
+/// Anyone can execute it, the reserves are set to the balances of the contract
+/// excluding the protocol fees which are not collected yet
+pub fn sync(deps: DepsMut, env: Env, _info: MessageInfo) -> Result<Response, ContractError> {
+    assert_no_flash_swap(deps.storage)?;
+
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let pools: [Asset; 2] = load_pools(deps.as_ref(), &pair_info)?;
+    let balances: [Asset; 2] =
+        query_balances(deps.as_ref(), &pair_info, env.contract.address.clone())?;
+
+    // accumulate the prices of the reserves before the sync
+    update_price_accumulators(deps.storage, &env, [pools[0].amount, pools[1].amount])?;
+
+    RESERVES.save(deps.storage, &[balances[0].amount, balances[1].amount])?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "sync"),
+        ("reserves", &format!("{}, {}", balances[0], balances[1])),
+    ]))
+}
+
+/// Anyone can execute it, the balances of the contract above the reserves
+/// and the protocol fees are sent to `to`
+pub fn skim(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    to: Addr,
+) -> Result<Response, ContractError> {
+    assert_no_flash_swap(deps.storage)?;
+
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let pools: [Asset; 2] = load_pools(deps.as_ref(), &pair_info)?;
+    let balances: [Asset; 2] = query_balances(deps.as_ref(), &pair_info, env.contract.address)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut skimmed_assets: Vec<String> = vec![];
+    for (pool, balance) in pools.iter().zip(balances.iter()) {
+        let asset = Asset {
+            info: pool.info.clone(),
+            amount: balance.amount.saturating_sub(pool.amount),
+        };
+        if asset.amount.is_zero() {
+            continue;
+        }
+
+        skimmed_assets.push(asset.to_string());
+        messages.push(asset.into_msg(to.clone())?);
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "skim"),
+        ("to", to.as_str()),
+        ("skimmed_assets", &skimmed_assets.join(", ")),
+    ]))
+}
+
+/// The ask asset is sent to the recipient before its callback is executed, then the pair
+/// checks the invariant with a message to itself, which reverts everything if it is violated.
+/// The recipient can not be the pair, its LP token or its assets, which the pair is allowed
+/// to execute
+pub fn flash_swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    ask_asset: Asset,
+    recipient: Addr,
+    data: Binary,
+) -> Result<Response, ContractError> {
+    assert_no_flash_swap(deps.storage)?;
+    assert_swaps_not_paused(deps.storage)?;
+
+    if ask_asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let pools: [Asset; 2] = load_pools(deps.as_ref(), &pair_info)?;
+
+    if recipient == env.contract.address
+        || recipient == deps.api.addr_humanize(&pair_info.liquidity_token)?
+        || pools.iter().any(|pool| match &pool.info {
+            AssetInfo::Token { contract_addr } => recipient == *contract_addr,
+            AssetInfo::NativeToken { .. } => false,
+        })
+    {
+        return Err(ContractError::InvalidFlashSwapRecipient {});
+    }
+
+    let ask_index = pools
+        .iter()
+        .position(|pool| pool.info.equal(&ask_asset.info))
+        .ok_or(ContractError::AssetMismatch {})?;
+    if ask_asset.amount >= pools[ask_index].amount {
+        return Err(ContractError::AskAmountExceedsPool {});
+    }
+
+    let mut ask_amounts = [Uint128::zero(), Uint128::zero()];
+    ask_amounts[ask_index] = ask_asset.amount;
+    FLASH_SWAP.save(
+        deps.storage,
+        &FlashSwap {
+            reserves: [pools[0].amount, pools[1].amount],
+            ask_amounts,
+        },
+    )?;
+
+    let messages: Vec<CosmosMsg> = vec![
+        ask_asset.clone().into_msg(recipient.clone())?,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: recipient.to_string(),
+            msg: to_binary(&FlashSwapCallbackMsg::FlashSwapCallback {
+                sender: info.sender.to_string(),
+                ask_asset: ask_asset.clone(),
+                data,
+            })?,
+            funds: vec![],
+        }),
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            msg: to_binary(&ExecuteMsg::AssertFlashSwap {})?,
+            funds: vec![],
+        }),
+    ];
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "flash_swap"),
+        ("sender", info.sender.as_str()),
+        ("recipient", recipient.as_str()),
+        ("ask_asset", &ask_asset.to_string()),
+    ]))
+}
+
+/// Only the pair itself can execute it, the paid back amounts are charged the flash fee
+/// and the invariant of the adjusted balances must not be lower than before, the constant
+/// product for a constant product pair and the StableSwap invariant for a stable pair
+pub fn assert_flash_swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    // permission check
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let flash_swap = FLASH_SWAP
+        .may_load(deps.storage)?
+        .ok_or(ContractError::FlashSwapNotFound {})?;
+
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let flash_fee_rate = FLASH_FEE_RATE.load(deps.storage)?;
+    let balances: [Asset; 2] =
+        query_balances(deps.as_ref(), &pair_info, env.contract.address.clone())?;
+
+    let mut paid_amounts = [Uint128::zero(), Uint128::zero()];
+    let mut adjusted_balances = [Uint128::zero(), Uint128::zero()];
+    for i in 0..2 {
+        // the amounts paid back above what is left in the pool are charged the flash fee
+        paid_amounts[i] = balances[i]
+            .amount
+            .saturating_sub(flash_swap.reserves[i].saturating_sub(flash_swap.ask_amounts[i]));
+        let flash_fee_amount: Uint128 = (Uint256::from(paid_amounts[i]) * flash_fee_rate).into();
+        adjusted_balances[i] = balances[i].amount - flash_fee_amount;
+    }
+
+    let invariant_violated = match pair_info.pair_type {
+        PairType::Xyk => {
+            Uint256::from(adjusted_balances[0]) * Uint256::from(adjusted_balances[1])
+                < Uint256::from(flash_swap.reserves[0]) * Uint256::from(flash_swap.reserves[1])
+        }
+        PairType::Stable { .. } => {
+            // an emptied pool has no invariant
+            let amp = query_current_amp(deps.storage, &env)?;
+            adjusted_balances.iter().any(|balance| balance.is_zero())
+                || compute_stable_invariant(adjusted_balances, pair_info.asset_decimals, amp)?
+                    < compute_stable_invariant(flash_swap.reserves, pair_info.asset_decimals, amp)?
+        }
+    };
+    if invariant_violated {
+        return Err(ContractError::FlashInvariantViolated {});
+    }
+
+    // accumulate the prices of the reserves before the flash swap
+    update_price_accumulators(deps.storage, &env, flash_swap.reserves)?;
+
+    // the flash fee stays in the pool for the liquidity providers
+    RESERVES.save(deps.storage, &[balances[0].amount, balances[1].amount])?;
+    FLASH_SWAP.remove(deps.storage);
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "assert_flash_swap"),
+        (
+            "paid_amounts",
+            &format!("{}, {}", paid_amounts[0], paid_amounts[1]),
+        ),
+        ("reserves", &format!("{}, {}", balances[0], balances[1])),
+    ]))
+}
+
+/// Returns the pool reserves tracked by the contract
+pub fn load_pools(deps: Deps, pair_info: &PairInfoRaw) -> StdResult<[Asset; 2]> {
+    let reserves = RESERVES.load(deps.storage)?;
+
+    Ok([
+        Asset {
+            info: pair_info.asset_infos[0].to_normal(deps.api)?,
+            amount: reserves[0],
+        },
+        Asset {
+            info: pair_info.asset_infos[1].to_normal(deps.api)?,
+            amount: reserves[1],
+        },
+    ])
+}
+
+/// Returns the balances of the contract excluding the protocol fees which are not collected yet
+/// and the pending deposits
+pub fn query_balances(
+    deps: Deps,
+    pair_info: &PairInfoRaw,
+    contract_addr: Addr,
+) -> StdResult<[Asset; 2]> {
+    let mut balances: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, contract_addr)?;
+    let fees = PROTOCOL_FEES.may_load(deps.storage)?.unwrap_or_default();
+    let pending_deposit_amounts = PENDING_DEPOSIT_AMOUNTS
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    for (i, balance) in balances.iter_mut().enumerate() {
+        balance.amount = balance
+            .amount
+            .checked_sub(fees[i])?
+            .checked_sub(pending_deposit_amounts[i])?;
+    }
+
+    Ok(balances)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
     match msg {
-        QueryMsg::Pair {} => Ok(to_binary(&query_pair_info(deps)?)?),
+        QueryMsg::Pair {} => Ok(to_binary(&query_pair_info(deps, env)?)?),
         QueryMsg::Pool {} => Ok(to_binary(&query_pool(deps)?)?),
         QueryMsg::Simulation { offer_asset } => {
-            Ok(to_binary(&query_simulation(deps, offer_asset)?)?)
+            Ok(to_binary(&query_simulation(deps, env, offer_asset)?)?)
         }
         QueryMsg::ReverseSimulation { ask_asset } => {
-            Ok(to_binary(&query_reverse_simulation(deps, ask_asset)?)?)
+            Ok(to_binary(&query_reverse_simulation(deps, env, ask_asset)?)?)
+        }
+        QueryMsg::ProtocolFees {} => Ok(to_binary(&query_protocol_fees(deps)?)?),
+        QueryMsg::CumulativePrices {} => Ok(to_binary(&query_cumulative_prices(deps, env)?)?),
+        QueryMsg::Twap {
+            asset_info,
+            window_seconds,
+        } => Ok(to_binary(&query_twap(
+            deps,
+            env,
+            asset_info,
+            window_seconds,
+        )?)?),
+        QueryMsg::SimulateProvideLiquidity { assets } => Ok(to_binary(
+            &query_simulate_provide_liquidity(deps, env, assets)?,
+        )?),
+        QueryMsg::SimulateWithdrawLiquidity { share } => {
+            Ok(to_binary(&query_simulate_withdraw_liquidity(deps, share)?)?)
         }
+        QueryMsg::PauseStatus {} => Ok(to_binary(
+            &PAUSE_STATUS.may_load(deps.storage)?.unwrap_or_default(),
+        )?),
+        QueryMsg::PendingDeposit { address } => Ok(to_binary(&PendingDepositResponse {
+            deposit: PENDING_DEPOSITS.may_load(deps.storage, &deps.api.addr_validate(&address)?)?,
+        })?),
+        QueryMsg::FlashFeeRate {} => Ok(to_binary(&FlashFeeRateResponse {
+            flash_fee_rate: FLASH_FEE_RATE.load(deps.storage)?,
+        })?),
     }
 }
 
-pub fn query_pair_info(deps: Deps) -> Result<PairInfo, ContractError> {
+pub fn query_pair_info(deps: Deps, env: Env) -> Result<PairInfo, ContractError> {
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
-    let pair_info = pair_info.to_normal(deps.api)?;
+    let mut pair_info = pair_info.to_normal(deps.api)?;
+
+    // the amplification coefficient of a stable pair may be ramping
+    if let PairType::Stable { .. } = pair_info.pair_type {
+        pair_info.pair_type = PairType::Stable {
+            amp: query_current_amp(deps.storage, &env)?,
+        };
+    }
 
     Ok(pair_info)
 }
 
 pub fn query_pool(deps: Deps) -> Result<PoolResponse, ContractError> {
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
-    let contract_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
-    let assets: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, contract_addr)?;
+    let assets: [Asset; 2] = load_pools(deps, &pair_info)?;
     let total_share: Uint128 = query_token_info(
         &deps.querier,
         deps.api.addr_humanize(&pair_info.liquidity_token)?,
@@ -577,81 +1791,260 @@ pub fn query_pool(deps: Deps) -> Result<PoolResponse, ContractError> {
 
 pub fn query_simulation(
     deps: Deps,
+    env: Env,
     offer_asset: Asset,
 ) -> Result<SimulationResponse, ContractError> {
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
 
-    let contract_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
     // get pool info of the pair contract
-    let pools: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, contract_addr)?;
+    let pools: [Asset; 2] = load_pools(deps, &pair_info)?;
     // Commission rate OR Fee amount for framework
     let commission_rate = COMMISSION_RATE_INFO.load(deps.storage)?;
+    let protocol_fee_rate = PROTOCOL_FEE_RATE.load(deps.storage)?;
 
-    let offer_pool: Asset;
-    let ask_pool: Asset;
-    if offer_asset.info.equal(&pools[0].info) {
-        offer_pool = pools[0].clone();
-        ask_pool = pools[1].clone();
+    let offer_index = if offer_asset.info.equal(&pools[0].info) {
+        0
     } else if offer_asset.info.equal(&pools[1].info) {
-        offer_pool = pools[1].clone();
-        ask_pool = pools[0].clone();
+        1
     } else {
         return Err(ContractError::AssetMismatch {});
-    }
+    };
+    let ask_index = 1 - offer_index;
 
-    let (return_amount, spread_amount, commission_amount) = compute_swap(
-        offer_pool.amount,
-        ask_pool.amount,
+    let (return_amount, spread_amount, commission_amount) = compute_pair_swap(
+        deps.storage,
+        &env,
+        &pair_info.pair_type,
+        pools[offer_index].amount,
+        pools[ask_index].amount,
         offer_asset.amount,
+        pair_info.asset_decimals[offer_index],
+        pair_info.asset_decimals[ask_index],
         commission_rate,
-    );
+    )?;
+    let (lp_commission_amount, protocol_fee_amount) =
+        compute_protocol_fee(commission_amount, protocol_fee_rate);
 
     Ok(SimulationResponse {
         return_amount,
         spread_amount,
         commission_amount,
+        lp_commission_amount,
+        protocol_fee_amount,
     })
 }
 
 pub fn query_reverse_simulation(
     deps: Deps,
+    env: Env,
     ask_asset: Asset,
 ) -> Result<ReverseSimulationResponse, ContractError> {
     // get pair info
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
 
-    // get address of the pair contract
-    let contract_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
-
     // get pool info of the pair contract
-    let pools: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, contract_addr)?;
+    let pools: [Asset; 2] = load_pools(deps, &pair_info)?;
     // Commission rate OR Fee amount for framework
     let commission_rate = COMMISSION_RATE_INFO.load(deps.storage)?;
+    let protocol_fee_rate = PROTOCOL_FEE_RATE.load(deps.storage)?;
 
-    let offer_pool: Asset;
-    let ask_pool: Asset;
-    if ask_asset.info.equal(&pools[0].info) {
-        ask_pool = pools[0].clone();
-        offer_pool = pools[1].clone();
+    let ask_index = if ask_asset.info.equal(&pools[0].info) {
+        0
     } else if ask_asset.info.equal(&pools[1].info) {
-        ask_pool = pools[1].clone();
-        offer_pool = pools[0].clone();
+        1
     } else {
         return Err(ContractError::AssetMismatch {});
-    }
+    };
+    let offer_index = 1 - ask_index;
 
     // compute offer amount, spread amount, commission amount when user provide ask amount
-    let (offer_amount, spread_amount, commission_amount) = compute_offer_amount(
-        offer_pool.amount,
-        ask_pool.amount,
+    let (offer_amount, spread_amount, commission_amount) = compute_pair_offer_amount(
+        deps.storage,
+        &env,
+        &pair_info.pair_type,
+        pools[offer_index].amount,
+        pools[ask_index].amount,
         ask_asset.amount,
+        pair_info.asset_decimals[offer_index],
+        pair_info.asset_decimals[ask_index],
         commission_rate,
-    );
+    )?;
+    let (lp_commission_amount, protocol_fee_amount) =
+        compute_protocol_fee(commission_amount, protocol_fee_rate);
 
     Ok(ReverseSimulationResponse {
         offer_amount,
         spread_amount,
         commission_amount,
+        lp_commission_amount,
+        protocol_fee_amount,
+    })
+}
+
+/// Computes the return, spread and commission of a swap with the invariant of the pair
+#[allow(clippy::too_many_arguments)]
+pub fn compute_pair_swap(
+    storage: &dyn Storage,
+    env: &Env,
+    pair_type: &PairType,
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    offer_amount: Uint128,
+    offer_decimal: u8,
+    ask_decimal: u8,
+    commission_rate: Decimal256,
+) -> Result<(Uint128, Uint128, Uint128), ContractError> {
+    match pair_type {
+        PairType::Xyk => Ok(compute_swap(
+            offer_pool,
+            ask_pool,
+            offer_amount,
+            commission_rate,
+        )),
+        PairType::Stable { .. } => Ok(compute_stable_swap(
+            offer_pool,
+            ask_pool,
+            offer_amount,
+            offer_decimal,
+            ask_decimal,
+            query_current_amp(storage, env)?,
+            commission_rate,
+        )?),
+    }
+}
+
+/// Computes the offer, spread and commission to receive `ask_amount` with the invariant of the pair
+#[allow(clippy::too_many_arguments)]
+pub fn compute_pair_offer_amount(
+    storage: &dyn Storage,
+    env: &Env,
+    pair_type: &PairType,
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    ask_amount: Uint128,
+    offer_decimal: u8,
+    ask_decimal: u8,
+    commission_rate: Decimal256,
+) -> Result<(Uint128, Uint128, Uint128), ContractError> {
+    match pair_type {
+        PairType::Xyk => Ok(compute_offer_amount(
+            offer_pool,
+            ask_pool,
+            ask_amount,
+            commission_rate,
+        )?),
+        PairType::Stable { .. } => Ok(compute_stable_offer_amount(
+            offer_pool,
+            ask_pool,
+            ask_amount,
+            offer_decimal,
+            ask_decimal,
+            query_current_amp(storage, env)?,
+            commission_rate,
+        )?),
+    }
+}
+
+pub fn query_simulate_provide_liquidity(
+    deps: Deps,
+    env: Env,
+    assets: [Asset; 2],
+) -> Result<SimulateProvideLiquidityResponse, ContractError> {
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let pools: [Asset; 2] = load_pools(deps, &pair_info)?;
+    let total_share: Uint128 = query_token_info(
+        &deps.querier,
+        deps.api.addr_humanize(&pair_info.liquidity_token)?,
+    )?
+    .total_supply;
+
+    let mut deposits = [Uint128::zero(), Uint128::zero()];
+    for (i, pool) in pools.iter().enumerate() {
+        deposits[i] = assets
+            .iter()
+            .find(|a| a.info.equal(&pool.info))
+            .map(|a| a.amount)
+            .ok_or(ContractError::AssetMismatch {})?;
+    }
+
+    // a single-sided provision uses the whole deposit
+    let (share, used_deposits) = if deposits[0].is_zero() != deposits[1].is_zero() {
+        let offer_index = if deposits[0].is_zero() { 1 } else { 0 };
+        let single_sided = compute_single_sided_provision(
+            deps.storage,
+            &env,
+            &pair_info,
+            total_share,
+            pools.clone(),
+            offer_index,
+            deposits[offer_index],
+        )?;
+        (single_sided.share, deposits)
+    } else {
+        let used_deposits = compute_used_deposits(total_share, deposits, &pools);
+        let share = compute_share(total_share, used_deposits, &pools)?;
+        if total_share.is_zero() {
+            // the minimum liquidity of the first provision is not minted to the provider
+            (
+                share.saturating_sub(MIN_LIQUIDITY.load(deps.storage)?),
+                used_deposits,
+            )
+        } else {
+            (share, used_deposits)
+        }
+    };
+
+    let mut used_assets = [pools[0].clone(), pools[1].clone()];
+    let mut refund_assets = [pools[0].clone(), pools[1].clone()];
+    for i in 0..2 {
+        used_assets[i].amount = used_deposits[i];
+        refund_assets[i].amount = deposits[i].checked_sub(used_deposits[i])?;
+    }
+
+    Ok(SimulateProvideLiquidityResponse {
+        share,
+        used_assets,
+        refund_assets,
+    })
+}
+
+pub fn query_simulate_withdraw_liquidity(
+    deps: Deps,
+    share: Uint128,
+) -> Result<SimulateWithdrawLiquidityResponse, ContractError> {
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let pools: [Asset; 2] = load_pools(deps, &pair_info)?;
+    let total_share: Uint128 = query_token_info(
+        &deps.querier,
+        deps.api.addr_humanize(&pair_info.liquidity_token)?,
+    )?
+    .total_supply;
+
+    if share > total_share {
+        return Err(ContractError::ShareExceedsTotalShare {});
+    }
+
+    Ok(SimulateWithdrawLiquidityResponse {
+        refund_assets: compute_withdrawn_assets(&pools, share, total_share),
+    })
+}
+
+pub fn query_protocol_fees(deps: Deps) -> Result<ProtocolFeesResponse, ContractError> {
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let fees = PROTOCOL_FEES.load(deps.storage)?;
+
+    Ok(ProtocolFeesResponse {
+        protocol_fee_rate: PROTOCOL_FEE_RATE.load(deps.storage)?,
+        fees: [
+            Asset {
+                info: pair_info.asset_infos[0].to_normal(deps.api)?,
+                amount: fees[0],
+            },
+            Asset {
+                info: pair_info.asset_infos[1].to_normal(deps.api)?,
+                amount: fees[1],
+            },
+        ],
     })
 }
 
@@ -663,8 +2056,39 @@ pub fn query_reverse_simulation(
 // }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
+    // pairs created before the protocol fee was introduced do not take any protocol fee
+    if PROTOCOL_FEE_RATE.may_load(deps.storage)?.is_none() {
+        PROTOCOL_FEE_RATE.save(deps.storage, &Decimal256::zero())?;
+        PROTOCOL_FEES.save(deps.storage, &[Uint128::zero(), Uint128::zero()])?;
+    }
+
+    // pairs created before the price accumulators were introduced start accumulating from now
+    if PRICE_CUMULATIVE.may_load(deps.storage)?.is_none() {
+        initialize_price_accumulators(deps.storage, &env)?;
+    }
+
+    // pairs created before the reserves were tracked start from their balances and keep
+    // the amount of LP token they already locked
+    if RESERVES.may_load(deps.storage)?.is_none() {
+        let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+        let balances = query_balances(deps.as_ref(), &pair_info, env.contract.address)?;
+        RESERVES.save(deps.storage, &[balances[0].amount, balances[1].amount])?;
+        MIN_LIQUIDITY.save(deps.storage, &Uint128::from(1u128))?;
+    }
+
+    // pairs created before the pending deposits were introduced do not have any
+    if PENDING_DEPOSIT_AMOUNTS.may_load(deps.storage)?.is_none() {
+        PENDING_DEPOSIT_AMOUNTS.save(deps.storage, &[Uint128::zero(), Uint128::zero()])?;
+    }
+
+    // pairs created before the flash fee rate was introduced keep charging their commission rate
+    if FLASH_FEE_RATE.may_load(deps.storage)?.is_none() {
+        let commission_rate = COMMISSION_RATE_INFO.load(deps.storage)?;
+        FLASH_FEE_RATE.save(deps.storage, &commission_rate)?;
+    }
+
     Ok(Response::default())
 }
@@ -1,5 +1,7 @@
+pub mod amp;
 pub mod assert;
 pub mod contract;
+pub mod oracle;
 pub mod state;
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,172 @@
+use crate::contract::load_pools;
+use crate::state::{
+    Observation, PriceCumulative, OBSERVATIONS, OBSERVATIONS_SIZE, PAIR_INFO, PRICE_CUMULATIVE,
+};
+
+use bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{Deps, Env, StdResult, Storage, Uint128};
+use haloswap::asset::{AssetInfo, PairInfoRaw};
+use haloswap::error::ContractError;
+use haloswap::pair::{CumulativePricesResponse, TwapResponse};
+
+/// Starts the price accumulators from the current block with a first observation
+pub fn initialize_price_accumulators(storage: &mut dyn Storage, env: &Env) -> StdResult<()> {
+    let block_time = env.block.time.seconds();
+
+    OBSERVATIONS.save(
+        storage,
+        0,
+        &Observation {
+            block_time,
+            price0_cumulative: Decimal256::zero(),
+            price1_cumulative: Decimal256::zero(),
+        },
+    )?;
+
+    PRICE_CUMULATIVE.save(
+        storage,
+        &PriceCumulative {
+            price0_cumulative_last: Decimal256::zero(),
+            price1_cumulative_last: Decimal256::zero(),
+            block_time_last: block_time,
+            observations_count: 1,
+        },
+    )
+}
+
+/// Accumulates the prices of the reserves before they are changed by the current operation,
+/// then records an observation of the accumulators at most once per block.
+/// The accumulators are expected to overflow, only their differences are meaningful.
+pub fn update_price_accumulators(
+    storage: &mut dyn Storage,
+    env: &Env,
+    reserves: [Uint128; 2],
+) -> StdResult<()> {
+    let block_time = env.block.time.seconds();
+
+    let mut price = PRICE_CUMULATIVE.load(storage)?;
+
+    let elapsed = block_time.saturating_sub(price.block_time_last);
+    let (price0_cumulative, price1_cumulative) = accumulate(&price, reserves, elapsed);
+    price.price0_cumulative_last = price0_cumulative;
+    price.price1_cumulative_last = price1_cumulative;
+    price.block_time_last = block_time;
+
+    // the last observation is always taken at the time of the previous update
+    if elapsed > 0 {
+        OBSERVATIONS.save(
+            storage,
+            price.observations_count % OBSERVATIONS_SIZE,
+            &Observation {
+                block_time,
+                price0_cumulative,
+                price1_cumulative,
+            },
+        )?;
+        price.observations_count += 1;
+    }
+
+    PRICE_CUMULATIVE.save(storage, &price)
+}
+
+// EQUATION: P_{cumulative} = P_{cumulative\_last} + \frac{R_B}{R_A} * \Delta t
+fn accumulate(
+    price: &PriceCumulative,
+    reserves: [Uint128; 2],
+    elapsed: u64,
+) -> (Decimal256, Decimal256) {
+    // there is no price for an empty pool
+    if elapsed == 0 || reserves[0].is_zero() || reserves[1].is_zero() {
+        return (price.price0_cumulative_last, price.price1_cumulative_last);
+    }
+
+    let reserve_0 = Uint256::from(reserves[0]);
+    let reserve_1 = Uint256::from(reserves[1]);
+    let elapsed = Uint256::from(elapsed);
+
+    (
+        price.price0_cumulative_last.wrapping_add(
+            Decimal256::from_ratio(reserve_1, reserve_0).wrapping_mul_uint256(elapsed),
+        ),
+        price.price1_cumulative_last.wrapping_add(
+            Decimal256::from_ratio(reserve_0, reserve_1).wrapping_mul_uint256(elapsed),
+        ),
+    )
+}
+
+pub fn query_cumulative_prices(
+    deps: Deps,
+    _env: Env,
+) -> Result<CumulativePricesResponse, ContractError> {
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let assets = load_pools(deps, &pair_info)?;
+    let price = PRICE_CUMULATIVE.load(deps.storage)?;
+
+    Ok(CumulativePricesResponse {
+        assets,
+        price0_cumulative_last: price.price0_cumulative_last,
+        price1_cumulative_last: price.price1_cumulative_last,
+        block_time_last: price.block_time_last,
+    })
+}
+
+pub fn query_twap(
+    deps: Deps,
+    env: Env,
+    asset_info: AssetInfo,
+    window_seconds: u64,
+) -> Result<TwapResponse, ContractError> {
+    if window_seconds == 0 {
+        return Err(ContractError::InvalidTwapWindow {});
+    }
+
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let pools = load_pools(deps, &pair_info)?;
+    let is_first_asset = if asset_info.equal(&pools[0].info) {
+        true
+    } else if asset_info.equal(&pools[1].info) {
+        false
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    };
+
+    // accumulators as they would be if they were updated in the current block
+    let block_time = env.block.time.seconds();
+    let price = PRICE_CUMULATIVE.load(deps.storage)?;
+    let (price0_cumulative, price1_cumulative) = accumulate(
+        &price,
+        [pools[0].amount, pools[1].amount],
+        block_time.saturating_sub(price.block_time_last),
+    );
+
+    // find the newest observation which is at least `window_seconds` old
+    let window_start = block_time
+        .checked_sub(window_seconds)
+        .ok_or(ContractError::NotEnoughObservations {})?;
+    let kept_observations = price.observations_count.min(OBSERVATIONS_SIZE);
+    let mut observation: Option<Observation> = None;
+    for i in 1..=kept_observations {
+        let candidate = OBSERVATIONS.load(
+            deps.storage,
+            (price.observations_count - i) % OBSERVATIONS_SIZE,
+        )?;
+        if candidate.block_time <= window_start {
+            observation = Some(candidate);
+            break;
+        }
+    }
+    let observation = observation.ok_or(ContractError::NotEnoughObservations {})?;
+
+    let elapsed = block_time - observation.block_time;
+    let cumulative_diff = if is_first_asset {
+        price0_cumulative.wrapping_sub(observation.price0_cumulative)
+    } else {
+        price1_cumulative.wrapping_sub(observation.price1_cumulative)
+    };
+
+    Ok(TwapResponse {
+        asset_info,
+        price: cumulative_diff / Decimal256::from_uint256(elapsed),
+        window_seconds: elapsed,
+    })
+}
@@ -1,8 +1,9 @@
 use bignumber::Decimal256;
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::Addr;
-use cw_storage_plus::Item;
-use haloswap::asset::PairInfoRaw;
+use cosmwasm_std::{Addr, Uint128};
+use cw_storage_plus::{Item, Map};
+use haloswap::asset::{Asset, PairInfoRaw};
+use haloswap::pair::PauseStatus;
 
 #[cw_serde]
 pub struct Config {
@@ -11,7 +12,81 @@ pub struct Config {
 
 pub const CONFIG: Item<Config> = Item::new("config");
 
+// The amplification coefficient of a stable pair is ramped linearly from `init_amp` to `next_amp`
+#[cw_serde]
+pub struct AmpConfig {
+    pub init_amp: u64,
+    pub init_amp_time: u64,
+    pub next_amp: u64,
+    pub next_amp_time: u64,
+}
+
+pub const AMP_CONFIG: Item<AmpConfig> = Item::new("amp_config");
+
 pub const PAIR_INFO: Item<PairInfoRaw> = Item::new("pair_info");
 
 // Store commission rate for the pair
 pub const COMMISSION_RATE_INFO: Item<Decimal256> = Item::new("commission_rate_info");
+
+// Store the share of the commission which is accrued for the protocol
+pub const PROTOCOL_FEE_RATE: Item<Decimal256> = Item::new("protocol_fee_rate");
+
+// Store the fee rate charged on the amounts paid back by the flash swaps
+pub const FLASH_FEE_RATE: Item<Decimal256> = Item::new("flash_fee_rate");
+
+// Store the protocol fees which are not collected yet, in the same order as the pair assets
+pub const PROTOCOL_FEES: Item<[Uint128; 2]> = Item::new("protocol_fees");
+
+// Store the reserves of the pool, in the same order as the pair assets. The balances of the
+// contract above the reserves and the protocol fees are not part of the pool until a sync
+pub const RESERVES: Item<[Uint128; 2]> = Item::new("reserves");
+
+// key : depositor address / value: the CW20 deposit received before the other asset is provided
+pub const PENDING_DEPOSITS: Map<&Addr, Asset> = Map::new("pending_deposits");
+
+// Store the sum of the pending deposits, in the same order as the pair assets
+pub const PENDING_DEPOSIT_AMOUNTS: Item<[Uint128; 2]> = Item::new("pending_deposit_amounts");
+
+// Store the amount of LP token locked in the LP token contract by the first provision
+pub const MIN_LIQUIDITY: Item<Uint128> = Item::new("min_liquidity");
+
+#[cw_serde]
+pub struct FlashSwap {
+    /// Reserves of the pool before the flash swap
+    pub reserves: [Uint128; 2],
+    /// Amounts sent to the recipient, in the same order as the pair assets
+    pub ask_amounts: [Uint128; 2],
+}
+
+// Store the flash swap in progress, the pair is locked until its invariant is checked
+pub const FLASH_SWAP: Item<FlashSwap> = Item::new("flash_swap");
+
+// Store the operations paused by the factory, nothing is paused if it is not set
+pub const PAUSE_STATUS: Item<PauseStatus> = Item::new("pause_status");
+
+#[cw_serde]
+pub struct PriceCumulative {
+    /// Sum of the price of the first asset in the second asset, weighted by seconds
+    pub price0_cumulative_last: Decimal256,
+    /// Sum of the price of the second asset in the first asset, weighted by seconds
+    pub price1_cumulative_last: Decimal256,
+    /// Block time in seconds of the last update
+    pub block_time_last: u64,
+    /// Number of observations recorded since the pair is created
+    pub observations_count: u64,
+}
+
+pub const PRICE_CUMULATIVE: Item<PriceCumulative> = Item::new("price_cumulative");
+
+#[cw_serde]
+pub struct Observation {
+    pub block_time: u64,
+    pub price0_cumulative: Decimal256,
+    pub price1_cumulative: Decimal256,
+}
+
+/// Maximum number of observations kept in the ring buffer
+pub const OBSERVATIONS_SIZE: u64 = 100;
+
+// Ring buffer of the price accumulators, key is the slot of the observation
+pub const OBSERVATIONS: Map<u64, Observation> = Map::new("observations");
@@ -2,17 +2,26 @@ use crate::assert::assert_max_spread;
 use crate::contract::{
     execute, instantiate, query, query_pool, query_reverse_simulation, query_simulation, reply,
 };
+use crate::state::RESERVES;
 use bignumber::Decimal256;
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, Reply, ReplyOn, Response, StdError,
-    SubMsg, SubMsgResponse, SubMsgResult, Uint128, WasmMsg,
+    attr, from_binary, to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, Reply, ReplyOn,
+    Response, StdError, SubMsg, SubMsgResponse, SubMsgResult, Uint128, WasmMsg,
 };
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
-use haloswap::asset::{Asset, AssetInfo, CreatePairRequirements, LPTokenInfo, PairInfo};
+use haloswap::asset::{
+    Asset, AssetInfo, CreatePairRequirements, LPTokenInfo, PairInfo, PairType,
+    DEFAULT_MIN_LIQUIDITY,
+};
 use haloswap::error::ContractError;
+use haloswap::factory::{ConfigResponse as FactoryConfigResponse, PairCreationMode};
 use haloswap::mock_querier::mock_dependencies;
-use haloswap::pair::{Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, QueryMsg};
+use haloswap::pair::{
+    CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, FlashFeeRateResponse, FlashSwapCallbackMsg,
+    InstantiateMsg, PauseStatus, PendingDepositResponse, PoolResponse, ProtocolFeesResponse,
+    QueryMsg, SimulateProvideLiquidityResponse, SimulateWithdrawLiquidityResponse, TwapResponse,
+};
 use haloswap::pair::{ReverseSimulationResponse, SimulationResponse};
 use haloswap::token::InstantiateMsg as TokenInstantiateMsg;
 
@@ -39,11 +48,15 @@ fn proper_initialization() {
             second_asset_minimum: Uint128::zero(),
         },
         commission_rate: Decimal256::from_str("0.003").unwrap(),
+        protocol_fee_rate: Decimal256::zero(),
+        flash_fee_rate: Decimal256::from_str("0.003").unwrap(),
         lp_token_info: LPTokenInfo {
             lp_token_name: "uusd_asset0000_lp".to_string(),
             lp_token_symbol: "uusd_asset0000_lp".to_string(),
             lp_token_decimals: Some(18),
         },
+        pair_type: PairType::Xyk,
+        min_liquidity: Uint128::from(1u128),
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -116,6 +129,7 @@ fn proper_initialization() {
                 second_asset_minimum: Uint128::zero(),
             },
             commission_rate: Decimal256::from_str("0.003").unwrap(),
+            pair_type: PairType::Xyk,
         })
         .unwrap()
     );
@@ -153,6 +167,7 @@ fn proper_initialization() {
 //             first_asset_minimum: Uint128::zero(),
 //             second_asset_minimum: Uint128::zero(),
 //         },
+//         pair_type: PairType::Xyk,
 //     };
 
 //     let env = mock_env();
@@ -199,7 +214,7 @@ fn proper_initialization() {
 //     );
 //     println!("{:?}", msg);
 //     let res = execute(deps.as_mut(), env, info, msg).unwrap();
-//     let msg_transfer = res.messages.get(0).expect("no message");
+//     let msg_transfer = res.messages.first().expect("no message");
 
 //     assert_eq!(
 //         &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
@@ -247,11 +262,15 @@ fn provide_liquidity() {
             second_asset_minimum: Uint128::zero(),
         },
         commission_rate: Decimal256::from_str("0.003").unwrap(),
+        protocol_fee_rate: Decimal256::zero(),
+        flash_fee_rate: Decimal256::from_str("0.003").unwrap(),
         lp_token_info: LPTokenInfo {
             lp_token_name: "uusd_asset0000_LP".to_string(),
             lp_token_symbol: "uusd_asset0000_LP".to_string(),
             lp_token_decimals: None,
         },
+        pair_type: PairType::Xyk,
+        min_liquidity: Uint128::from(1u128),
     };
 
     let env = mock_env();
@@ -295,6 +314,7 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -329,6 +349,7 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -360,6 +381,7 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -371,7 +393,7 @@ fn provide_liquidity() {
         }],
     );
     let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    let transfer_from_msg = res.messages.get(0).expect("no message");
+    let transfer_from_msg = res.messages.first().expect("no message");
     let mint_for_liquidity0000_msg = res.messages.get(1).expect("no message");
     let mint_for_addr0000_msg = res.messages.get(2).expect("no message");
     assert_eq!(
@@ -433,6 +455,7 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: Some(Decimal::percent(101)), // slippage tolerance is 101%
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -445,14 +468,48 @@ fn provide_liquidity() {
     );
     let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
     match res {
-        ContractError::Std(StdError::GenericErr { msg, .. }) => {
-            assert_eq!(msg, "slippage_tolerance cannot bigger than 1".to_string())
-        }
-        _ => panic!("Must return generic error"),
+        ContractError::InvalidSlippageTolerance {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
     }
 
+    // the assets must be the assets of the pair
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                amount: Uint128::from(100u128),
+            },
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uluna".to_string(),
+                },
+                amount: Uint128::from(100u128),
+            },
+        ],
+        slippage_tolerance: None,
+        receiver: None,
+        deadline: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::from(100u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::AssetNotInPair {
+            asset: "uluna".to_string()
+        }
+    );
+
     // provide more liquidity 1:2, which is not proportional to 1:1,
-    // then it must accept 1:1 and treat left amount as donation
+    // then it must accept 1:1 and refund the left amount
     deps.querier.with_balance(&[(
         &MOCK_CONTRACT_ADDR.to_string(),
         vec![Coin {
@@ -473,6 +530,13 @@ fn provide_liquidity() {
             &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(200u128))],
         ),
     ]);
+    // the reserves do not include the deposit
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[Uint128::from(200u128), Uint128::from(200u128)],
+        )
+        .unwrap();
 
     let msg = ExecuteMsg::ProvideLiquidity {
         assets: [
@@ -491,6 +555,7 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: None,
         receiver: Some("staking0000".to_string()), // try changing receiver
+        deadline: None,
     };
 
     let env = mock_env();
@@ -504,8 +569,23 @@ fn provide_liquidity() {
 
     // only accept 100, then 50 share will be generated with 100 * (100 / 200)
     let res: Response = execute(deps.as_mut(), env, info, msg).unwrap();
-    let transfer_from_msg = res.messages.get(0).expect("no message");
-    let mint_msg = res.messages.get(1).expect("no message");
+    let refund_msg = res.messages.first().expect("no message");
+    let transfer_from_msg = res.messages.get(1).expect("no message");
+    let mint_msg = res.messages.get(2).expect("no message");
+    assert_eq!(
+        refund_msg,
+        &SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(100u128),
+            }],
+        }))
+    );
+    assert_eq!(
+        res.attributes.last().expect("no attribute"),
+        &attr("refund_assets", "100uusd, 0asset0000")
+    );
     assert_eq!(
         transfer_from_msg,
         &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
@@ -551,6 +631,7 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -591,6 +672,12 @@ fn provide_liquidity() {
             &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
         ),
     ]);
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[Uint128::from(100u128), Uint128::from(100u128)],
+        )
+        .unwrap();
 
     // failed because the price is under slippage_tolerance
     let msg = ExecuteMsg::ProvideLiquidity {
@@ -610,6 +697,7 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: Some(Decimal::percent(1)),
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -653,6 +741,7 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: Some(Decimal::percent(1)),
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -698,6 +787,7 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: Some(Decimal::percent(1)),
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -710,6 +800,14 @@ fn provide_liquidity() {
     );
     let _res = execute(deps.as_mut(), env, info, msg).unwrap();
 
+    // initialize the reserves to 1:1
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[Uint128::from(100u128), Uint128::from(100u128)],
+        )
+        .unwrap();
+
     // initialize token balance to 1:1
     deps.querier.with_balance(&[(
         &MOCK_CONTRACT_ADDR.to_string(),
@@ -737,6 +835,7 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: Some(Decimal::percent(1)),
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -751,7 +850,6 @@ fn provide_liquidity() {
 }
 
 #[test]
-#[should_panic(expected = "arithmetic operation overflow")]
 fn provide_overflow_liquidity() {
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
@@ -783,11 +881,15 @@ fn provide_overflow_liquidity() {
             second_asset_minimum: Uint128::zero(),
         },
         commission_rate: Decimal256::from_str("0.003").unwrap(),
+        protocol_fee_rate: Decimal256::zero(),
+        flash_fee_rate: Decimal256::from_str("0.003").unwrap(),
         lp_token_info: LPTokenInfo {
             lp_token_name: "uusd_asset0000_LP".to_string(),
             lp_token_symbol: "uusd_asset0000_LP".to_string(),
             lp_token_decimals: None,
         },
+        pair_type: PairType::Xyk,
+        min_liquidity: Uint128::from(1u128),
     };
 
     let env = mock_env();
@@ -831,6 +933,7 @@ fn provide_overflow_liquidity() {
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -847,6 +950,17 @@ fn provide_overflow_liquidity() {
         _ => panic!("DO NOT ENTER HERE"),
     }
 
+    // the pool holds the native tokens of the balance
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[
+                Uint128::from(340_282_366_917_999_999_999_999_999_999u128),
+                Uint128::zero(),
+            ],
+        )
+        .unwrap();
+
     // verify failed provide liquidity with over limit asset amount:
     let msg = ExecuteMsg::ProvideLiquidity {
         assets: [
@@ -865,6 +979,7 @@ fn provide_overflow_liquidity() {
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -875,7 +990,8 @@ fn provide_overflow_liquidity() {
             amount: Uint128::from(1u128),
         }],
     );
-    let _res = execute(deps.as_mut(), env, info, msg);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    assert_eq!(res, ContractError::PoolOverflow {});
 }
 
 #[test]
@@ -913,11 +1029,15 @@ fn withdraw_liquidity() {
             second_asset_minimum: Uint128::zero(),
         },
         commission_rate: Decimal256::from_str("0.003").unwrap(),
+        protocol_fee_rate: Decimal256::zero(),
+        flash_fee_rate: Decimal256::from_str("0.003").unwrap(),
         lp_token_info: LPTokenInfo {
             lp_token_name: "uusd_asset0000_LP".to_string(),
             lp_token_symbol: "uusd_asset0000_LP".to_string(),
             lp_token_decimals: None,
         },
+        pair_type: PairType::Xyk,
+        min_liquidity: Uint128::from(1u128),
     };
 
     let env = mock_env();
@@ -942,10 +1062,23 @@ fn withdraw_liquidity() {
 
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
+    // the pool holds the balances of the contract
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[Uint128::from(100u128), Uint128::from(100u128)],
+        )
+        .unwrap();
+
     // withdraw successfully liquidity
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr0000".to_string(),
-        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets: None,
+            receiver: None,
+            deadline: None,
+        })
+        .unwrap(),
         amount: Uint128::from(100u128),
     });
 
@@ -954,7 +1087,7 @@ fn withdraw_liquidity() {
     let res = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap();
     let log_withdrawn_share = res.attributes.get(2).expect("no log");
     let log_refund_assets = res.attributes.get(3).expect("no log");
-    let msg_refund_0 = res.messages.get(0).expect("no message");
+    let msg_refund_0 = res.messages.first().expect("no message");
     let msg_refund_1 = res.messages.get(1).expect("no message");
     let msg_burn_liquidity = res.messages.get(2).expect("no message");
     assert_eq!(
@@ -1009,6 +1142,201 @@ fn withdraw_liquidity() {
     }
 }
 
+#[test]
+fn withdraw_liquidity_with_min_assets_and_to_single_asset() {
+    let pool_amount = Uint128::from(1_000_000u128);
+    let share = Uint128::from(100_000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: pool_amount,
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &pool_amount)],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &pool_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 6u8],
+        requirements: CreatePairRequirements {
+            whitelist: vec![Addr::unchecked("addr0000")],
+            first_asset_minimum: Uint128::zero(),
+            second_asset_minimum: Uint128::zero(),
+        },
+        commission_rate: Decimal256::from_str("0.003").unwrap(),
+        protocol_fee_rate: Decimal256::zero(),
+        flash_fee_rate: Decimal256::from_str("0.003").unwrap(),
+        lp_token_info: LPTokenInfo {
+            lp_token_name: "uusd_asset0000_LP".to_string(),
+            lp_token_symbol: "uusd_asset0000_LP".to_string(),
+            lp_token_decimals: None,
+        },
+        pair_type: PairType::Xyk,
+        min_liquidity: Uint128::from(1u128),
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    // binary message which is converted from "liquidity0000" string.
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // the pool holds the balances of the contract
+    RESERVES
+        .save(&mut deps.storage, &[pool_amount, pool_amount])
+        .unwrap();
+
+    // 10% of the share withdraws 10% of the pools
+    let res: SimulateWithdrawLiquidityResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SimulateWithdrawLiquidity { share },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let refund_assets = [
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: share,
+        },
+        Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: share,
+        },
+    ];
+    assert_eq!(res.refund_assets, refund_assets);
+
+    // the withdrawn assets are less than the minimum
+    let mut min_assets = refund_assets.clone();
+    min_assets[0].amount = share + Uint128::one();
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets: Some(min_assets),
+            receiver: None,
+            deadline: None,
+        })
+        .unwrap(),
+        amount: share,
+    });
+    let info = mock_info("liquidity0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(err, ContractError::MaxSlippageAssertion {});
+
+    // the withdrawn assets are sent to the receiver
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets: Some(refund_assets),
+            receiver: Some("addr0001".to_string()),
+            deadline: None,
+        })
+        .unwrap(),
+        amount: share,
+    });
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0001".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: share,
+            }],
+        }))
+    );
+    assert_eq!(res.attributes.last(), Some(&attr("receiver", "addr0001")));
+
+    // restore the pools before the withdrawal
+    RESERVES
+        .save(&mut deps.storage, &[pool_amount, pool_amount])
+        .unwrap();
+
+    // the withdrawn asset0000 is swapped to uusd against the pools left after the withdrawal
+    // 89.730 = (900 - 900 * 900 / (900 + 100)) * (1 - 0.003)
+    let expected_return = share + Uint128::from(89_730u128);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidityToSingleAsset {
+            ask_asset_info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            min_receive: Some(expected_return + Uint128::one()),
+        })
+        .unwrap(),
+        amount: share,
+    });
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(err, ContractError::MaxSlippageAssertion {});
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidityToSingleAsset {
+            ask_asset_info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            min_receive: Some(expected_return),
+        })
+        .unwrap(),
+        amount: share,
+    });
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: expected_return,
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "liquidity0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Burn { amount: share }).unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+}
+
 // #[test]
 // fn try_native_to_token() {
 //     let total_share = Uint128::from(30_000_000_000u128);
@@ -1049,6 +1377,7 @@ fn withdraw_liquidity() {
 //             first_asset_minimum: Uint128::zero(),
 //             second_asset_minimum: Uint128::zero(),
 //         },
+//         pair_type: PairType::Xyk,
 //     };
 
 //     let env = mock_env();
@@ -1105,7 +1434,7 @@ fn withdraw_liquidity() {
 //         }],
 //     );
 //     let res = execute(deps.as_mut(), env, info, msg).unwrap();
-//     let msg_transfer = res.messages.get(0).expect("no message");
+//     let msg_transfer = res.messages.first().expect("no message");
 
 //     // current price is 1.5, so expected return without spread is 1000
 //     // 952.380952 = 20000 - 20000 * 30000 / (30000 + 1500)
@@ -1240,11 +1569,15 @@ fn try_token_to_native() {
             second_asset_minimum: Uint128::zero(),
         },
         commission_rate: Decimal256::from_str("0.003").unwrap(),
+        protocol_fee_rate: Decimal256::zero(),
+        flash_fee_rate: Decimal256::from_str("0.003").unwrap(),
         lp_token_info: LPTokenInfo {
             lp_token_name: "uusd_asset0000_LP".to_string(),
             lp_token_symbol: "uusd_asset0000_LP".to_string(),
             lp_token_decimals: None,
         },
+        pair_type: PairType::Xyk,
+        min_liquidity: Uint128::from(1u128),
     };
 
     let env = mock_env();
@@ -1269,6 +1602,14 @@ fn try_token_to_native() {
 
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
+    // the reserves do not include the offer amount
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
+
     // unauthorized access; can not execute swap directly for token swap
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr0000".to_string(),
@@ -1283,6 +1624,7 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: None,
             to: None,
+            deadline: None,
         })
         .unwrap(),
     });
@@ -1309,6 +1651,7 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: None,
             to: None,
+            deadline: None,
         })
         .unwrap(),
     });
@@ -1321,6 +1664,33 @@ fn try_token_to_native() {
         _ => panic!("DO NOT ENTER HERE"),
     }
 
+    // asset mismatch; the declared offer asset is not the received token
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: offer_amount,
+        msg: to_binary(&Cw20HookMsg::Swap {
+            offer_asset: Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: offer_amount,
+            },
+            belief_price: None,
+            max_spread: None,
+            to: None,
+            deadline: None,
+        })
+        .unwrap(),
+    });
+    let env = mock_env();
+    let info = mock_info("asset0000", &[]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+
+    match res {
+        ContractError::AssetMismatch {} => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
     // swap to another address
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr0000".to_string(),
@@ -1335,6 +1705,7 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: None,
             to: Some("addr0001".to_string()),
+            deadline: None,
         })
         .unwrap(),
     });
@@ -1342,7 +1713,7 @@ fn try_token_to_native() {
     let env = mock_env();
     let info = mock_info("asset0000", &[]);
     let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    let msg_transfer = res.messages.get(0).expect("no message");
+    let msg_transfer = res.messages.first().expect("no message");
 
     assert_eq!(
         &SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
@@ -1358,6 +1729,14 @@ fn try_token_to_native() {
         msg_transfer,
     );
 
+    // restore the reserves before the swap
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
+
     // normal sell
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr0000".to_string(),
@@ -1372,6 +1751,7 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: None,
             to: None,
+            deadline: None,
         })
         .unwrap(),
     });
@@ -1379,7 +1759,7 @@ fn try_token_to_native() {
     let info = mock_info("asset0000", &[]);
 
     let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    let msg_transfer = res.messages.get(0).expect("no message");
+    let msg_transfer = res.messages.first().expect("no message");
 
     // current price is 1.5, so expected return without spread is 1000
     // 952.380952 = 20000 - 20000 * 30000 / (30000 + 1500)
@@ -1404,8 +1784,16 @@ fn try_token_to_native() {
         ),
     ]);
 
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
+
     let simulation_res: SimulationResponse = query_simulation(
         deps.as_ref(),
+        mock_env(),
         Asset {
             amount: offer_amount,
             info: AssetInfo::Token {
@@ -1421,6 +1809,7 @@ fn try_token_to_native() {
     // check reverse simulation res
     let reverse_simulation_res: ReverseSimulationResponse = query_reverse_simulation(
         deps.as_ref(),
+        mock_env(),
         Asset {
             amount: expected_return_amount,
             info: AssetInfo::NativeToken {
@@ -1458,6 +1847,7 @@ fn try_token_to_native() {
             attr("return_amount", expected_return_amount.to_string()),
             attr("spread_amount", expected_spread_amount.to_string()),
             attr("commission_amount", expected_commission_amount.to_string()),
+            attr("protocol_fee_amount", "0"),
         ]
     );
 
@@ -1486,6 +1876,7 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: None,
             to: None,
+            deadline: None,
         })
         .unwrap(),
     });
@@ -1709,11 +2100,15 @@ fn test_query_pool() {
             second_asset_minimum: Uint128::zero(),
         },
         commission_rate: Decimal256::from_str("0.003").unwrap(),
+        protocol_fee_rate: Decimal256::zero(),
+        flash_fee_rate: Decimal256::from_str("0.003").unwrap(),
         lp_token_info: LPTokenInfo {
             lp_token_name: "uusd_asset0000_LP".to_string(),
             lp_token_symbol: "uusd_asset0000_LP".to_string(),
             lp_token_decimals: None,
         },
+        pair_type: PairType::Xyk,
+        min_liquidity: Uint128::from(1u128),
     };
 
     let env = mock_env();
@@ -1738,6 +2133,15 @@ fn test_query_pool() {
 
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
+    // the reserves are synced to the balances of the contract
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Sync {},
+    )
+    .unwrap();
+
     let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
 
     assert_eq!(
@@ -1759,3 +2163,2506 @@ fn test_query_pool() {
     );
     assert_eq!(res.total_share, total_share_amount);
 }
+
+#[test]
+fn collect_protocol_fees() {
+    let total_share = Uint128::from(20_000_000_000u128);
+    let asset_pool_amount = Uint128::from(30_000_000_000u128);
+    let collateral_pool_amount = Uint128::from(20_000_000_000u128);
+    let offer_amount = Uint128::from(1_500_000_000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount,
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &(asset_pool_amount + offer_amount),
+            )],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [8u8, 8u8],
+        requirements: CreatePairRequirements {
+            whitelist: vec![Addr::unchecked("addr0000")],
+            first_asset_minimum: Uint128::zero(),
+            second_asset_minimum: Uint128::zero(),
+        },
+        commission_rate: Decimal256::from_str("0.003").unwrap(),
+        // 20% of the commission is taken by the protocol
+        protocol_fee_rate: Decimal256::from_str("0.2").unwrap(),
+        flash_fee_rate: Decimal256::from_str("0.003").unwrap(),
+        lp_token_info: LPTokenInfo {
+            lp_token_name: "uusd_asset0000_LP".to_string(),
+            lp_token_symbol: "uusd_asset0000_LP".to_string(),
+            lp_token_decimals: None,
+        },
+        pair_type: PairType::Xyk,
+        min_liquidity: Uint128::from(1u128),
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    // binary message which is converted from "liquidity0000" string.
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // the reserves do not include the offer amount
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
+
+    // the simulation breaks out the commission of the liquidity providers and the protocol
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &asset_pool_amount)],
+        ),
+    ]);
+    let simulation_res: SimulationResponse = query_simulation(
+        deps.as_ref(),
+        mock_env(),
+        Asset {
+            amount: offer_amount,
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        },
+    )
+    .unwrap();
+
+    // 952.380952 = 20000 - 20000 * 30000 / (30000 + 1500)
+    let expected_commission_amount = Uint128::from(952_380_952u128).multiply_ratio(3u128, 1000u128);
+    let expected_protocol_fee_amount = expected_commission_amount.multiply_ratio(2u128, 10u128);
+    assert_eq!(simulation_res.commission_amount, expected_commission_amount);
+    assert_eq!(
+        simulation_res.protocol_fee_amount,
+        expected_protocol_fee_amount
+    );
+    assert_eq!(
+        simulation_res.lp_commission_amount,
+        expected_commission_amount - expected_protocol_fee_amount
+    );
+
+    // swap asset0000 to uusd
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &(asset_pool_amount + offer_amount),
+            )],
+        ),
+    ]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: offer_amount,
+        msg: to_binary(&Cw20HookMsg::Swap {
+            offer_asset: Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                amount: offer_amount,
+            },
+            belief_price: None,
+            max_spread: None,
+            to: None,
+            deadline: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info("asset0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes.last().unwrap(),
+        &attr(
+            "protocol_fee_amount",
+            expected_protocol_fee_amount.to_string()
+        )
+    );
+
+    // the accrued protocol fee is in the ask asset
+    let protocol_fees: ProtocolFeesResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::ProtocolFees {}).unwrap()).unwrap();
+    assert_eq!(
+        protocol_fees,
+        ProtocolFeesResponse {
+            protocol_fee_rate: Decimal256::from_str("0.2").unwrap(),
+            fees: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                    amount: expected_protocol_fee_amount,
+                },
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: "asset0000".to_string(),
+                    },
+                    amount: Uint128::zero(),
+                },
+            ],
+        }
+    );
+
+    // the accrued protocol fee is excluded from the pool with the returned amount
+    // 949.523810 = 952.380952 * (1 - 0.003)
+    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(
+        res.assets[0].amount,
+        collateral_pool_amount - Uint128::from(949_523_810u128) - expected_protocol_fee_amount
+    );
+    assert_eq!(res.assets[1].amount, asset_pool_amount + offer_amount);
+
+    // failed to collect the protocol fees when the fee collector is not set in the factory
+    deps.querier
+        .with_halo_factory_config(FactoryConfigResponse {
+            owner: "owner0000".to_string(),
+            pair_code_id: 321u64,
+            token_code_id: 123u64,
+            protocol_fee_rate: Decimal256::from_str("0.2").unwrap(),
+            flash_fee_rate: Decimal256::from_str("0.003").unwrap(),
+            fee_collector: None,
+            pair_creation_mode: PairCreationMode::OwnerOnly,
+            min_liquidity: Uint128::from(DEFAULT_MIN_LIQUIDITY),
+        });
+    let msg = ExecuteMsg::CollectProtocolFees {};
+    let info = mock_info("anyone0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(res, ContractError::FeeCollectorNotSet {});
+
+    // anyone can collect the protocol fees to the fee collector
+    deps.querier
+        .with_halo_factory_config(FactoryConfigResponse {
+            owner: "owner0000".to_string(),
+            pair_code_id: 321u64,
+            token_code_id: 123u64,
+            protocol_fee_rate: Decimal256::from_str("0.2").unwrap(),
+            flash_fee_rate: Decimal256::from_str("0.003").unwrap(),
+            fee_collector: Some("collector0000".to_string()),
+            pair_creation_mode: PairCreationMode::OwnerOnly,
+            min_liquidity: Uint128::from(DEFAULT_MIN_LIQUIDITY),
+        });
+    let msg = ExecuteMsg::CollectProtocolFees {};
+    let info = mock_info("anyone0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "collector0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: expected_protocol_fee_amount,
+            }],
+        }))]
+    );
+
+    // the protocol fees are reset after the collection
+    let protocol_fees: ProtocolFeesResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::ProtocolFees {}).unwrap()).unwrap();
+    assert_eq!(protocol_fees.fees[0].amount, Uint128::zero());
+    assert_eq!(protocol_fees.fees[1].amount, Uint128::zero());
+}
+
+#[test]
+fn price_accumulators_and_twap() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2_000_000_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(1_000_000_000u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_000_000_000u128),
+            )],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 6u8],
+        requirements: CreatePairRequirements {
+            whitelist: vec![Addr::unchecked("addr0000")],
+            first_asset_minimum: Uint128::zero(),
+            second_asset_minimum: Uint128::zero(),
+        },
+        commission_rate: Decimal256::from_str("0.003").unwrap(),
+        protocol_fee_rate: Decimal256::zero(),
+        flash_fee_rate: Decimal256::from_str("0.003").unwrap(),
+        lp_token_info: LPTokenInfo {
+            lp_token_name: "uusd_asset0000_LP".to_string(),
+            lp_token_symbol: "uusd_asset0000_LP".to_string(),
+            lp_token_decimals: None,
+        },
+        pair_type: PairType::Xyk,
+        min_liquidity: Uint128::from(1u128),
+    };
+
+    let env = mock_env();
+    let start_time = env.block.time;
+    let info = mock_info("addr0000", &[]);
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    // binary message which is converted from "liquidity0000" string.
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // the pool holds the balances of the contract
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[
+                Uint128::from(2_000_000_000u128),
+                Uint128::from(1_000_000_000u128),
+            ],
+        )
+        .unwrap();
+
+    // the window can not start before the creation of the pair
+    let mut env = mock_env();
+    env.block.time = start_time.plus_seconds(50);
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::Twap {
+            asset_info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            window_seconds: 60,
+        },
+    );
+    assert_eq!(res.unwrap_err(), ContractError::NotEnoughObservations {});
+
+    // withdraw a bit of liquidity after 100 seconds, the prices are accumulated
+    // with the reserves before the withdrawal (2000 uusd - 1000 asset0000)
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets: None,
+            receiver: None,
+            deadline: None,
+        })
+        .unwrap(),
+        amount: Uint128::from(1u128),
+    });
+    let mut env = mock_env();
+    env.block.time = start_time.plus_seconds(100);
+    let info = mock_info("liquidity0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let res: CumulativePricesResponse =
+        from_binary(&query(deps.as_ref(), env, QueryMsg::CumulativePrices {}).unwrap()).unwrap();
+    assert_eq!(
+        res.price0_cumulative_last,
+        Decimal256::from_str("50").unwrap()
+    );
+    assert_eq!(
+        res.price1_cumulative_last,
+        Decimal256::from_str("200").unwrap()
+    );
+    assert_eq!(res.block_time_last, start_time.plus_seconds(100).seconds());
+
+    // the price of the pool changes to 1000 uusd - 1000 asset0000 right after the withdrawal
+    // and liquidity is withdrawn again after 50 seconds
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[
+                Uint128::from(1_000_000_000u128),
+                Uint128::from(1_000_000_000u128),
+            ],
+        )
+        .unwrap();
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets: None,
+            receiver: None,
+            deadline: None,
+        })
+        .unwrap(),
+        amount: Uint128::from(1u128),
+    });
+    let mut env = mock_env();
+    env.block.time = start_time.plus_seconds(150);
+    let info = mock_info("liquidity0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let res: CumulativePricesResponse =
+        from_binary(&query(deps.as_ref(), env, QueryMsg::CumulativePrices {}).unwrap()).unwrap();
+    assert_eq!(
+        res.price0_cumulative_last,
+        Decimal256::from_str("100").unwrap()
+    );
+    assert_eq!(
+        res.price1_cumulative_last,
+        Decimal256::from_str("250").unwrap()
+    );
+
+    // TWAP over the last 100 seconds only uses the current price
+    let mut env = mock_env();
+    env.block.time = start_time.plus_seconds(250);
+    let res: TwapResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Twap {
+                asset_info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                window_seconds: 100,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.price, Decimal256::one());
+    assert_eq!(res.window_seconds, 100);
+
+    // TWAP since the creation of the pair: (0.5 * 100 + 1 * 150) / 250 uusd and (2 * 100 + 1 * 150) / 250 asset0000
+    let res: TwapResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Twap {
+                asset_info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                window_seconds: 250,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.price, Decimal256::from_str("0.8").unwrap());
+    assert_eq!(res.window_seconds, 250);
+
+    let res: TwapResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Twap {
+                asset_info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                window_seconds: 250,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.price, Decimal256::from_str("1.4").unwrap());
+
+    // the window is extended to the newest observation which is old enough
+    let res: TwapResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Twap {
+                asset_info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                window_seconds: 120,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.window_seconds, 150);
+
+    // the asset must be in the pair
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::Twap {
+            asset_info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            window_seconds: 100,
+        },
+    );
+    assert_eq!(res.unwrap_err(), ContractError::AssetMismatch {});
+}
+
+#[test]
+fn stable_swap_and_amp_ramping() {
+    let pool_amount = Uint128::from(1_000_000_000_000u128);
+    let offer_amount = Uint128::from(1_000_000_000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: pool_amount,
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &pool_amount)],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &pool_amount)],
+        ),
+    ]);
+    deps.querier
+        .with_halo_factory_config(FactoryConfigResponse {
+            owner: "owner0000".to_string(),
+            pair_code_id: 321u64,
+            token_code_id: 123u64,
+            protocol_fee_rate: Decimal256::zero(),
+            flash_fee_rate: Decimal256::from_str("0.003").unwrap(),
+            fee_collector: None,
+            pair_creation_mode: PairCreationMode::OwnerOnly,
+            min_liquidity: Uint128::from(DEFAULT_MIN_LIQUIDITY),
+        });
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 6u8],
+        requirements: CreatePairRequirements {
+            whitelist: vec![Addr::unchecked("addr0000")],
+            first_asset_minimum: Uint128::zero(),
+            second_asset_minimum: Uint128::zero(),
+        },
+        commission_rate: Decimal256::from_str("0.0005").unwrap(),
+        protocol_fee_rate: Decimal256::zero(),
+        flash_fee_rate: Decimal256::from_str("0.003").unwrap(),
+        lp_token_info: LPTokenInfo {
+            lp_token_name: "uusd_asset0000_LP".to_string(),
+            lp_token_symbol: "uusd_asset0000_LP".to_string(),
+            lp_token_decimals: None,
+        },
+        pair_type: PairType::Stable { amp: 100 },
+        min_liquidity: Uint128::from(1u128),
+    };
+
+    let env = mock_env();
+    let start_time = env.block.time;
+    let info = mock_info("addr0000", &[]);
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    // binary message which is converted from "liquidity0000" string.
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // the pool holds the balances of the contract
+    RESERVES
+        .save(&mut deps.storage, &[pool_amount, pool_amount])
+        .unwrap();
+
+    // the stable pair gives an almost 1:1 execution
+    let simulation_res: SimulationResponse = query_simulation(
+        deps.as_ref(),
+        mock_env(),
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+    )
+    .unwrap();
+    assert_eq!(simulation_res.return_amount, Uint128::from(999_490_104u128));
+    assert_eq!(simulation_res.spread_amount, Uint128::from(9_901u128));
+    assert_eq!(simulation_res.commission_amount, Uint128::from(499_995u128));
+
+    // the swap returns the simulated amount
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: pool_amount + offer_amount,
+        }],
+    )]);
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes
+            .iter()
+            .find(|attr| attr.key == "return_amount")
+            .unwrap()
+            .value,
+        "999490104"
+    );
+
+    // only the owner of the factory can ramp the amp
+    let msg = ExecuteMsg::StartChangingAmp {
+        next_amp: 200,
+        next_amp_time: start_time.plus_seconds(2 * 86_400).seconds(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+
+    // the amp can not be changed too fast
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::StartChangingAmp {
+            next_amp: 200,
+            next_amp_time: start_time.plus_seconds(3_600).seconds(),
+        },
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::AmpChangeTooFast { min_time: 86_400 }
+    );
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::StartChangingAmp {
+            next_amp: 1_001,
+            next_amp_time: start_time.plus_seconds(2 * 86_400).seconds(),
+        },
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::AmpChangeTooLarge { max_change: 10 }
+    );
+
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+    // the amp is ramped linearly
+    let mut env = mock_env();
+    env.block.time = start_time.plus_seconds(86_400);
+    let pair_info: PairInfo =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Pair {}).unwrap()).unwrap();
+    assert_eq!(pair_info.pair_type, PairType::Stable { amp: 150 });
+
+    // and stopped at its current value
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("owner0000", &[]),
+        ExecuteMsg::StopChangingAmp {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "stop_changing_amp"),
+            attr("current_amp", "150"),
+        ]
+    );
+
+    let mut env = mock_env();
+    env.block.time = start_time.plus_seconds(3 * 86_400);
+    let pair_info: PairInfo =
+        from_binary(&query(deps.as_ref(), env, QueryMsg::Pair {}).unwrap()).unwrap();
+    assert_eq!(pair_info.pair_type, PairType::Stable { amp: 150 });
+}
+
+#[test]
+fn provide_single_sided_liquidity() {
+    let total_share = Uint128::from(1_000_000_000u128);
+    let pool_amount = Uint128::from(1_000_000_000u128);
+    let deposit_amount = Uint128::from(100_000_000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: pool_amount,
+    }]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 6u8],
+        requirements: CreatePairRequirements {
+            whitelist: vec![Addr::unchecked("addr0000")],
+            first_asset_minimum: Uint128::zero(),
+            second_asset_minimum: Uint128::zero(),
+        },
+        commission_rate: Decimal256::from_str("0.003").unwrap(),
+        protocol_fee_rate: Decimal256::zero(),
+        flash_fee_rate: Decimal256::from_str("0.003").unwrap(),
+        lp_token_info: LPTokenInfo {
+            lp_token_name: "uusd_asset0000_LP".to_string(),
+            lp_token_symbol: "uusd_asset0000_LP".to_string(),
+            lp_token_decimals: None,
+        },
+        pair_type: PairType::Xyk,
+        min_liquidity: Uint128::from(1u128),
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    // binary message which is converted from "liquidity0000" string.
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // the pool must have liquidity to swap a part of the deposit
+    deps.querier.with_token_balances(&[
+        (&"liquidity0000".to_string(), &[]),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &pool_amount)],
+        ),
+    ]);
+    let err = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::SimulateProvideLiquidity {
+            assets: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                    amount: deposit_amount,
+                },
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: "asset0000".to_string(),
+                    },
+                    amount: Uint128::zero(),
+                },
+            ],
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoLiquidity {});
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &pool_amount)],
+        ),
+    ]);
+
+    RESERVES
+        .save(&mut deps.storage, &[pool_amount, pool_amount])
+        .unwrap();
+
+    // a provision of both assets uses them at the ratio of the pools and refunds the excess
+    let res: SimulateProvideLiquidityResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SimulateProvideLiquidity {
+                assets: [
+                    Asset {
+                        info: AssetInfo::NativeToken {
+                            denom: "uusd".to_string(),
+                        },
+                        amount: deposit_amount,
+                    },
+                    Asset {
+                        info: AssetInfo::Token {
+                            contract_addr: "asset0000".to_string(),
+                        },
+                        amount: deposit_amount + deposit_amount,
+                    },
+                ],
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.share, deposit_amount);
+    assert_eq!(
+        res.used_assets,
+        [
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: deposit_amount,
+            },
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                amount: deposit_amount,
+            },
+        ]
+    );
+    assert_eq!(res.refund_assets[0].amount, Uint128::zero());
+    assert_eq!(res.refund_assets[1].amount, deposit_amount);
+
+    // 48.885753 uusd is swapped to 46.467497 asset, which are provided to the pool
+    // 48.731948 = 51.114247 * 1000 / 1048.885753
+    let res: SimulateProvideLiquidityResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SimulateProvideLiquidity {
+                assets: [
+                    Asset {
+                        info: AssetInfo::NativeToken {
+                            denom: "uusd".to_string(),
+                        },
+                        amount: deposit_amount,
+                    },
+                    Asset {
+                        info: AssetInfo::Token {
+                            contract_addr: "asset0000".to_string(),
+                        },
+                        amount: Uint128::zero(),
+                    },
+                ],
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let expected_share = Uint128::from(48_731_948u128);
+    assert_eq!(res.share, expected_share);
+
+    // provide uusd only
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: pool_amount + deposit_amount,
+        }],
+    )]);
+    let msg = ExecuteMsg::ProvideSingleSidedLiquidity {
+        asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: deposit_amount,
+        },
+        min_share: Some(expected_share + Uint128::one()),
+        receiver: None,
+    };
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: deposit_amount,
+        }],
+    );
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(err, ContractError::MaxSlippageAssertion {});
+
+    let msg = ExecuteMsg::ProvideSingleSidedLiquidity {
+        asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: deposit_amount,
+        },
+        min_share: Some(expected_share),
+        receiver: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "liquidity0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "addr0001".to_string(),
+                amount: expected_share,
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+    assert_eq!(
+        res.attributes[0],
+        attr("action", "provide_single_sided_liquidity")
+    );
+    assert_eq!(res.attributes[6], attr("share", expected_share.to_string()));
+
+    // provide asset0000 only with the cw20 hook, which is symmetric to the uusd provision
+    RESERVES
+        .save(&mut deps.storage, &[pool_amount, pool_amount])
+        .unwrap();
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &(pool_amount + deposit_amount),
+            )],
+        ),
+    ]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0001".to_string(),
+        amount: deposit_amount,
+        msg: to_binary(&Cw20HookMsg::ProvideSingleSidedLiquidity {
+            min_share: None,
+            receiver: Some("addr0002".to_string()),
+        })
+        .unwrap(),
+    });
+
+    // only the tokens of the pair can be provided
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("asset0001", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("asset0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "liquidity0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "addr0002".to_string(),
+                amount: expected_share,
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+}
+
+#[test]
+fn pause_swaps_and_deposits() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(200u128),
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(100u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 6u8],
+        requirements: CreatePairRequirements {
+            whitelist: vec![Addr::unchecked("addr0000")],
+            first_asset_minimum: Uint128::zero(),
+            second_asset_minimum: Uint128::zero(),
+        },
+        commission_rate: Decimal256::from_str("0.003").unwrap(),
+        protocol_fee_rate: Decimal256::zero(),
+        flash_fee_rate: Decimal256::from_str("0.003").unwrap(),
+        lp_token_info: LPTokenInfo {
+            lp_token_name: "uusd_asset0000_LP".to_string(),
+            lp_token_symbol: "uusd_asset0000_LP".to_string(),
+            lp_token_decimals: None,
+        },
+        pair_type: PairType::Xyk,
+        min_liquidity: Uint128::from(1u128),
+    };
+
+    // the factory instantiates the pair
+    let _res = instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("factory0000", &[]),
+        msg,
+    )
+    .unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    // binary message which is converted from "liquidity0000" string.
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // only the factory can pause the pair
+    let msg = ExecuteMsg::SetPause {
+        swaps: true,
+        deposits: true,
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("factory0000", &[]),
+        msg,
+    )
+    .unwrap();
+    let pause_status: PauseStatus =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PauseStatus {}).unwrap()).unwrap();
+    assert_eq!(
+        pause_status,
+        PauseStatus {
+            swaps: true,
+            deposits: true,
+        }
+    );
+
+    // the swaps and the deposits are rejected
+    let offer_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: Uint128::from(100u128),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(100u128),
+        }],
+    );
+    let msg = ExecuteMsg::Swap {
+        offer_asset: offer_asset.clone(),
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(err, ContractError::Paused {});
+
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: [
+            offer_asset.clone(),
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                amount: Uint128::from(100u128),
+            },
+        ],
+        slippage_tolerance: None,
+        receiver: None,
+        deadline: None,
+    };
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(err, ContractError::Paused {});
+
+    let msg = ExecuteMsg::ProvideSingleSidedLiquidity {
+        asset: offer_asset,
+        min_share: None,
+        receiver: None,
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Paused {});
+
+    // but the liquidity providers can still exit
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets: None,
+            receiver: None,
+            deadline: None,
+        })
+        .unwrap(),
+        amount: Uint128::from(100u128),
+    });
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(res.attributes[0], attr("action", "withdraw_liquidity"));
+
+    // the pair is resumed
+    let msg = ExecuteMsg::SetPause {
+        swaps: false,
+        deposits: false,
+    };
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("factory0000", &[]),
+        msg,
+    )
+    .unwrap();
+    let pause_status: PauseStatus =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PauseStatus {}).unwrap()).unwrap();
+    assert_eq!(pause_status, PauseStatus::default());
+}
+
+#[test]
+fn update_commission_rate() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(200u128),
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(100u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 6u8],
+        requirements: CreatePairRequirements {
+            whitelist: vec![Addr::unchecked("addr0000")],
+            first_asset_minimum: Uint128::zero(),
+            second_asset_minimum: Uint128::zero(),
+        },
+        commission_rate: Decimal256::from_str("0.003").unwrap(),
+        protocol_fee_rate: Decimal256::zero(),
+        flash_fee_rate: Decimal256::from_str("0.003").unwrap(),
+        lp_token_info: LPTokenInfo {
+            lp_token_name: "uusd_asset0000_LP".to_string(),
+            lp_token_symbol: "uusd_asset0000_LP".to_string(),
+            lp_token_decimals: None,
+        },
+        pair_type: PairType::Xyk,
+        min_liquidity: Uint128::from(1u128),
+    };
+
+    // the factory instantiates the pair
+    let _res = instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("factory0000", &[]),
+        msg,
+    )
+    .unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    // binary message which is converted from "liquidity0000" string.
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // the pool holds the balances of the contract
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[Uint128::from(200u128), Uint128::from(100u128)],
+        )
+        .unwrap();
+
+    // only the factory can update the commission rate
+    let msg = ExecuteMsg::UpdateCommissionRate {
+        commission_rate: Decimal256::zero(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("factory0000", &[]),
+        ExecuteMsg::UpdateCommissionRate {
+            commission_rate: Decimal256::from_str("1.1").unwrap(),
+        },
+    );
+    match res {
+        Err(ContractError::InvalidCommissionRate {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("factory0000", &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_commission_rate"),
+            attr("commission_rate", "0"),
+        ]
+    );
+
+    let pair_info: PairInfo =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Pair {}).unwrap()).unwrap();
+    assert_eq!(pair_info.commission_rate, Decimal256::zero());
+
+    // the swaps take no commission anymore
+    let simulation_res: SimulationResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Simulation {
+                offer_asset: Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                    amount: Uint128::from(100u128),
+                },
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(simulation_res.commission_amount, Uint128::zero());
+    assert_eq!(simulation_res.return_amount, Uint128::from(33u128));
+}
+
+#[test]
+fn reserves_sync_and_skim() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2_000u128),
+    }]);
+    deps.querier.with_token_balances(&[
+        (&"liquidity0000".to_string(), &[]),
+        (&"asset0000".to_string(), &[]),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 6u8],
+        requirements: CreatePairRequirements {
+            whitelist: vec![Addr::unchecked("addr0000")],
+            first_asset_minimum: Uint128::zero(),
+            second_asset_minimum: Uint128::zero(),
+        },
+        commission_rate: Decimal256::from_str("0.003").unwrap(),
+        protocol_fee_rate: Decimal256::zero(),
+        flash_fee_rate: Decimal256::from_str("0.003").unwrap(),
+        lp_token_info: LPTokenInfo {
+            lp_token_name: "uusd_asset0000_LP".to_string(),
+            lp_token_symbol: "uusd_asset0000_LP".to_string(),
+            lp_token_decimals: None,
+        },
+        pair_type: PairType::Xyk,
+        min_liquidity: Uint128::from(1_000u128),
+    };
+
+    let _res = instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("factory0000", &[]),
+        msg,
+    )
+    .unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    // binary message which is converted from "liquidity0000" string.
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // the first provision must mint more than the minimum liquidity
+    let provide_msg = |amount: u128| {
+        (
+            ExecuteMsg::ProvideLiquidity {
+                assets: [
+                    Asset {
+                        info: AssetInfo::NativeToken {
+                            denom: "uusd".to_string(),
+                        },
+                        amount: Uint128::from(amount),
+                    },
+                    Asset {
+                        info: AssetInfo::Token {
+                            contract_addr: "asset0000".to_string(),
+                        },
+                        amount: Uint128::from(amount),
+                    },
+                ],
+                slippage_tolerance: None,
+                receiver: None,
+                deadline: None,
+            },
+            mock_info(
+                "addr0000",
+                &[Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(amount),
+                }],
+            ),
+        )
+    };
+    let (msg, info) = provide_msg(1_000u128);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(res, ContractError::MinimumLiquidityNotReached {});
+
+    // 1000uLP of the 2000uLP are locked in the LP token contract
+    let (msg, info) = provide_msg(2_000u128);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "liquidity0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "liquidity0000".to_string(),
+                amount: Uint128::from(1_000u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    );
+    assert_eq!(
+        res.messages[2],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "liquidity0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(1_000u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    );
+
+    // the donations to the contract are not part of the pool
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(2_500u128),
+        }],
+    )]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(2_000u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(2_100u128))],
+        ),
+    ]);
+    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(res.assets[0].amount, Uint128::from(2_000u128));
+    assert_eq!(res.assets[1].amount, Uint128::from(2_000u128));
+
+    // anyone can skim the balances above the reserves
+    let msg = ExecuteMsg::Skim {
+        to: "addr0001".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0001".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(500u128),
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0001".to_string(),
+                    amount: Uint128::from(100u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "skim"),
+            attr("to", "addr0001"),
+            attr("skimmed_assets", "500uusd, 100asset0000"),
+        ]
+    );
+
+    // or add them to the pool
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::Sync {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "sync"),
+            attr("reserves", "2500uusd, 2100asset0000"),
+        ]
+    );
+    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(res.assets[0].amount, Uint128::from(2_500u128));
+    assert_eq!(res.assets[1].amount, Uint128::from(2_100u128));
+}
+
+#[test]
+fn flash_swap() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1_000_000u128),
+    }]);
+    deps.querier.with_token_balances(&[
+        (&"liquidity0000".to_string(), &[]),
+        (
+            &"asset0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_000_000u128),
+            )],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 6u8],
+        requirements: CreatePairRequirements {
+            whitelist: vec![Addr::unchecked("addr0000")],
+            first_asset_minimum: Uint128::zero(),
+            second_asset_minimum: Uint128::zero(),
+        },
+        commission_rate: Decimal256::from_str("0.003").unwrap(),
+        protocol_fee_rate: Decimal256::zero(),
+        flash_fee_rate: Decimal256::from_str("0.005").unwrap(),
+        lp_token_info: LPTokenInfo {
+            lp_token_name: "uusd_asset0000_LP".to_string(),
+            lp_token_symbol: "uusd_asset0000_LP".to_string(),
+            lp_token_decimals: None,
+        },
+        pair_type: PairType::Xyk,
+        min_liquidity: Uint128::from(1u128),
+    };
+
+    let _res = instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("factory0000", &[]),
+        msg,
+    )
+    .unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    // binary message which is converted from "liquidity0000" string.
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(1_000_000u128), Uint128::from(1_000_000u128)],
+        )
+        .unwrap();
+
+    let ask_asset = |amount: u128| Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: Uint128::from(amount),
+    };
+    let data = to_binary(&"flash_data").unwrap();
+
+    let res: FlashFeeRateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::FlashFeeRate {}).unwrap()).unwrap();
+    assert_eq!(res.flash_fee_rate, Decimal256::from_str("0.005").unwrap());
+
+    // the ask amount must be lower than the reserve
+    let msg = ExecuteMsg::FlashSwap {
+        ask_asset: ask_asset(1_000_000u128),
+        recipient: "borrower0000".to_string(),
+        data: data.clone(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+    assert_eq!(res, Err(ContractError::AskAmountExceedsPool {}));
+
+    // the pair, its LP token and its assets can not be the recipient
+    for recipient in [MOCK_CONTRACT_ADDR, "liquidity0000", "asset0000"] {
+        let msg = ExecuteMsg::FlashSwap {
+            ask_asset: ask_asset(100_000u128),
+            recipient: recipient.to_string(),
+            data: data.clone(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+        assert_eq!(res, Err(ContractError::InvalidFlashSwapRecipient {}));
+    }
+
+    let msg = ExecuteMsg::FlashSwap {
+        ask_asset: ask_asset(100_000u128),
+        recipient: "borrower0000".to_string(),
+        data: data.clone(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "borrower0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(100_000u128),
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "borrower0000".to_string(),
+                msg: to_binary(&FlashSwapCallbackMsg::FlashSwapCallback {
+                    sender: "addr0000".to_string(),
+                    ask_asset: ask_asset(100_000u128),
+                    data,
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                msg: to_binary(&ExecuteMsg::AssertFlashSwap {}).unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+
+    // the pool is locked during the callback
+    let msg = ExecuteMsg::Swap {
+        offer_asset: ask_asset(1_000u128),
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(
+            "borrower0000",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(1_000u128),
+            }],
+        ),
+        msg,
+    );
+    match res {
+        Err(ContractError::FlashSwapInProgress {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // only the pair can check the invariant
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("borrower0000", &[]),
+        ExecuteMsg::AssertFlashSwap {},
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // the borrowed amount is paid back without the flash fee
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_000_000u128),
+        }],
+    )]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::AssertFlashSwap {},
+    );
+    assert_eq!(res, Err(ContractError::FlashInvariantViolated {}));
+
+    // the flash fee rate of the pair is charged instead of its commission rate
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_000_301u128),
+        }],
+    )]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::AssertFlashSwap {},
+    );
+    assert_eq!(res, Err(ContractError::FlashInvariantViolated {}));
+
+    // the flash fee of 0.5% of the paid back amount stays in the pool
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_000_502u128),
+        }],
+    )]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::AssertFlashSwap {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "assert_flash_swap"),
+            attr("paid_amounts", "100502, 0"),
+            attr("reserves", "1000502uusd, 1000000asset0000"),
+        ]
+    );
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [Uint128::from(1_000_502u128), Uint128::from(1_000_000u128)]
+    );
+
+    // the pool is unlocked
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::AssertFlashSwap {},
+    );
+    assert_eq!(res, Err(ContractError::FlashSwapNotFound {}));
+}
+
+#[test]
+fn flash_swap_stable_pair() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1_000_000u128),
+    }]);
+    deps.querier.with_token_balances(&[
+        (&"liquidity0000".to_string(), &[]),
+        (
+            &"asset0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_000_000u128),
+            )],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 6u8],
+        requirements: CreatePairRequirements {
+            whitelist: vec![Addr::unchecked("addr0000")],
+            first_asset_minimum: Uint128::zero(),
+            second_asset_minimum: Uint128::zero(),
+        },
+        commission_rate: Decimal256::from_str("0.0005").unwrap(),
+        protocol_fee_rate: Decimal256::zero(),
+        flash_fee_rate: Decimal256::from_str("0.003").unwrap(),
+        lp_token_info: LPTokenInfo {
+            lp_token_name: "uusd_asset0000_LP".to_string(),
+            lp_token_symbol: "uusd_asset0000_LP".to_string(),
+            lp_token_decimals: None,
+        },
+        pair_type: PairType::Stable { amp: 100 },
+        min_liquidity: Uint128::from(1u128),
+    };
+
+    let _res = instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("factory0000", &[]),
+        msg,
+    )
+    .unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    // binary message which is converted from "liquidity0000" string.
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(1_000_000u128), Uint128::from(1_000_000u128)],
+        )
+        .unwrap();
+
+    let msg = ExecuteMsg::FlashSwap {
+        ask_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(100_000u128),
+        },
+        recipient: "borrower0000".to_string(),
+        data: to_binary(&"flash_data").unwrap(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    // the borrowed amount is paid back in the other asset, the StableSwap invariant
+    // of the balances after the flash fee must not be lower than before
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(900_000u128),
+        }],
+    )]);
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(1_100_401u128),
+        )],
+    )]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::AssertFlashSwap {},
+    );
+    assert_eq!(res, Err(ContractError::FlashInvariantViolated {}));
+
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(1_100_402u128),
+        )],
+    )]);
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::AssertFlashSwap {},
+    )
+    .unwrap();
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [Uint128::from(900_000u128), Uint128::from(1_100_402u128)]
+    );
+}
+
+#[test]
+fn swap_exact_out() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1_001_100_000u128),
+    }]);
+    deps.querier.with_token_balances(&[
+        (&"liquidity0000".to_string(), &[]),
+        (
+            &"asset0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_000_000_000u128),
+            )],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 6u8],
+        requirements: CreatePairRequirements {
+            whitelist: vec![Addr::unchecked("addr0000")],
+            first_asset_minimum: Uint128::zero(),
+            second_asset_minimum: Uint128::zero(),
+        },
+        commission_rate: Decimal256::from_str("0.003").unwrap(),
+        protocol_fee_rate: Decimal256::zero(),
+        flash_fee_rate: Decimal256::from_str("0.003").unwrap(),
+        lp_token_info: LPTokenInfo {
+            lp_token_name: "uusd_asset0000_LP".to_string(),
+            lp_token_symbol: "uusd_asset0000_LP".to_string(),
+            lp_token_decimals: None,
+        },
+        pair_type: PairType::Xyk,
+        min_liquidity: Uint128::from(1u128),
+    };
+
+    let _res = instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("factory0000", &[]),
+        msg,
+    )
+    .unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    // binary message which is converted from "liquidity0000" string.
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[
+                Uint128::from(1_000_000_000u128),
+                Uint128::from(1_000_000_000u128),
+            ],
+        )
+        .unwrap();
+
+    let swap_msg = |max_offer_amount: u128| ExecuteMsg::SwapExactOut {
+        ask_asset: Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: Uint128::from(1_000_000u128),
+        },
+        max_offer_amount: Uint128::from(max_offer_amount),
+        to: None,
+    };
+    let sent_funds = [Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1_100_000u128),
+    }];
+
+    // only the offer asset can be sent with the message
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(
+            "addr0000",
+            &[
+                sent_funds[0].clone(),
+                Coin {
+                    denom: "uluna".to_string(),
+                    amount: Uint128::from(1_000u128),
+                },
+            ],
+        ),
+        swap_msg(2_000_000u128),
+    );
+    assert_eq!(res, Err(ContractError::InvalidSwapFunds {}));
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::from(1_100_000u128),
+            }],
+        ),
+        swap_msg(2_000_000u128),
+    );
+    assert_eq!(res, Err(ContractError::InvalidSwapFunds {}));
+
+    // the funds sent must cover the required offer amount, which is rounded up to 1004018uusd
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(1_004_017u128),
+            }],
+        ),
+        swap_msg(1_100_000u128),
+    );
+    assert_eq!(
+        res,
+        Err(ContractError::InsufficientOfferAmount {
+            offer_amount: Uint128::from(1_004_018u128)
+        })
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &sent_funds),
+        swap_msg(1_004_017u128),
+    );
+    assert_eq!(res, Err(ContractError::MaxOfferAmountAssertion {}));
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &sent_funds),
+        swap_msg(2_000_000u128),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0000".to_string(),
+                    amount: Uint128::from(1_000_000u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(95_982u128),
+                }],
+            })),
+        ]
+    );
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [
+            Uint128::from(1_001_004_018u128),
+            Uint128::from(999_000_000u128)
+        ]
+    );
+
+    // swap the received token to an exact amount of uusd
+    let ask_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: Uint128::from(500_000u128),
+    };
+    let simulation_res: ReverseSimulationResponse =
+        query_reverse_simulation(deps.as_ref(), mock_env(), ask_asset.clone()).unwrap();
+    let receive_msg = |amount: Uint128| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "addr0000".to_string(),
+            amount,
+            msg: to_binary(&Cw20HookMsg::SwapExactOut {
+                ask_asset: ask_asset.clone(),
+                max_offer_amount: amount,
+                to: Some("addr0001".to_string()),
+            })
+            .unwrap(),
+        })
+    };
+
+    // the received token must be the offer asset of the pair
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        receive_msg(Uint128::from(600_000u128)),
+    );
+    assert_eq!(res, Err(ContractError::AssetMismatch {}));
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("asset0000", &[]),
+        receive_msg(Uint128::from(600_000u128)),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0001".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(500_000u128),
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0000".to_string(),
+                    amount: Uint128::from(600_000u128) - simulation_res.offer_amount,
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+}
+
+#[test]
+fn reject_after_deadline() {
+    let mut deps = mock_dependencies(&[]);
+    let env = mock_env();
+    let deadline = Some(env.block.time.minus_seconds(1));
+
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::from(100u128),
+            },
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                amount: Uint128::from(100u128),
+            },
+        ],
+        slippage_tolerance: None,
+        receiver: None,
+        deadline,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg);
+    assert_eq!(res, Err(ContractError::DeadlineExceeded {}));
+
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(100u128),
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg);
+    assert_eq!(res, Err(ContractError::DeadlineExceeded {}));
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::Swap {
+            offer_asset: Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                amount: Uint128::from(100u128),
+            },
+            belief_price: None,
+            max_spread: None,
+            to: None,
+            deadline,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), env.clone(), mock_info("asset0000", &[]), msg);
+    assert_eq!(res, Err(ContractError::DeadlineExceeded {}));
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets: None,
+            receiver: None,
+            deadline,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), env, mock_info("liquidity0000", &[]), msg);
+    assert_eq!(res, Err(ContractError::DeadlineExceeded {}));
+}
+
+#[test]
+fn provide_liquidity_with_send() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(200u128),
+    }]);
+    deps.querier.with_token_balances(&[
+        (&"liquidity0000".to_string(), &[]),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(150u128))],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 6u8],
+        requirements: CreatePairRequirements {
+            whitelist: vec![Addr::unchecked("addr0000")],
+            first_asset_minimum: Uint128::zero(),
+            second_asset_minimum: Uint128::zero(),
+        },
+        commission_rate: Decimal256::from_str("0.003").unwrap(),
+        protocol_fee_rate: Decimal256::zero(),
+        flash_fee_rate: Decimal256::from_str("0.003").unwrap(),
+        lp_token_info: LPTokenInfo {
+            lp_token_name: "uusd_asset0000_LP".to_string(),
+            lp_token_symbol: "uusd_asset0000_LP".to_string(),
+            lp_token_decimals: None,
+        },
+        pair_type: PairType::Xyk,
+        min_liquidity: Uint128::from(1u128),
+    };
+
+    let _res = instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("factory0000", &[]),
+        msg,
+    )
+    .unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    // binary message which is converted from "liquidity0000" string.
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    let uusd_asset = |amount: u128| Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: Uint128::from(amount),
+    };
+    let token_asset = |amount: u128| Asset {
+        info: AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+        amount: Uint128::from(amount),
+    };
+    let send_msg = |sender: &str, amount: u128, other_asset: Asset| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: sender.to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::ProvideLiquidity {
+                other_asset,
+                slippage_tolerance: None,
+                receiver: None,
+                deadline: None,
+            })
+            .unwrap(),
+        })
+    };
+
+    // only the pair assets can be provided
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("asset0001", &[]),
+        send_msg("addr0000", 100u128, uusd_asset(100u128)),
+    );
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("asset0000", &[]),
+        send_msg("addr0000", 100u128, token_asset(100u128)),
+    );
+    assert_eq!(res, Err(ContractError::AssetMismatch {}));
+
+    // the received token is kept as the pending deposit of the sender
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("asset0000", &[]),
+        send_msg("addr0000", 100u128, uusd_asset(100u128)),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "deposit_pending"),
+            attr("sender", "addr0000"),
+            attr("deposit", "100asset0000"),
+        ]
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("asset0000", &[]),
+        send_msg("addr0000", 100u128, uusd_asset(100u128)),
+    );
+    assert_eq!(res, Err(ContractError::PendingDepositExists {}));
+
+    let pending_deposit_res: PendingDepositResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PendingDeposit {
+                address: "addr0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(pending_deposit_res.deposit, Some(token_asset(100u128)));
+
+    // the pending deposit is not skimmed
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::Skim {
+            to: "addr0001".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes[2],
+        attr("skimmed_assets", "200uusd, 50asset0000")
+    );
+
+    // the native funds are provided with the pending deposit, without any allowance
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(100u128),
+            }],
+        ),
+        ExecuteMsg::ProvideLiquidity {
+            assets: [uusd_asset(100u128), token_asset(100u128)],
+            slippage_tolerance: None,
+            receiver: None,
+            deadline: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "liquidity0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: "liquidity0000".to_string(),
+                    amount: Uint128::from(1u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "liquidity0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: "addr0000".to_string(),
+                    amount: Uint128::from(99u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [Uint128::from(100u128), Uint128::from(100u128)]
+    );
+    let pending_deposit_res: PendingDepositResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PendingDeposit {
+                address: "addr0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(pending_deposit_res.deposit, None);
+
+    // the pending deposit can be withdrawn
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("asset0000", &[]),
+        send_msg("addr0001", 50u128, uusd_asset(50u128)),
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::WithdrawPendingDeposit {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "asset0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0001".to_string(),
+                amount: Uint128::from(50u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::WithdrawPendingDeposit {},
+    );
+    assert_eq!(res, Err(ContractError::PendingDepositNotFound {}));
+}
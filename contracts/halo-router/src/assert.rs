@@ -1,42 +1,38 @@
-use cosmwasm_std::{Addr, Deps, Env, MessageInfo, Response, StdError, StdResult, Uint128};
+use cosmwasm_std::{Env, Timestamp};
 use haloswap::asset::AssetInfo;
-use haloswap::router::SwapOperation;
+use haloswap::error::RouterError;
+use haloswap::router::{SplitRoute, SwapOperation};
 use std::collections::HashMap;
 
-pub fn assert_minium_receive(
-    deps: Deps,
-    env: Env,
-    info: MessageInfo,
-    asset_info: AssetInfo,
-    prev_balance: Uint128,
-    minium_receive: Uint128,
-    receiver: Addr,
-) -> StdResult<Response> {
-    if env.contract.address != info.sender {
-        return Err(StdError::generic_err(
-            "unauthorized: assert_minium_receive only can be called by contract itself",
-        ));
-    }
-    let receiver_balance = asset_info.query_pool(&deps.querier, deps.api, receiver)?;
-    let swap_amount = receiver_balance.checked_sub(prev_balance)?;
-
-    if swap_amount < minium_receive {
-        return Err(StdError::generic_err(format!(
-            "assertion failed; minimum receive amount: {}, swap amount: {}",
-            minium_receive, swap_amount
-        )));
+pub fn assert_deadline(env: &Env, deadline: Option<Timestamp>) -> Result<(), RouterError> {
+    if let Some(deadline) = deadline {
+        if env.block.time > deadline {
+            return Err(RouterError::DeadlineExceeded {});
+        }
     }
 
-    Ok(Response::default())
+    Ok(())
 }
 
-pub fn assert_operations(operations: &[SwapOperation]) -> StdResult<()> {
+/// Every operation offers the token asked by the previous one, so the router only swaps
+/// the return amounts of the operations and never the tokens it holds
+pub fn assert_operations(operations: &[SwapOperation]) -> Result<(), RouterError> {
+    for window in operations.windows(2) {
+        let SwapOperation::HaloSwap {
+            offer_asset_info, ..
+        } = &window[1];
+        if window[0].get_target_asset_info() != *offer_asset_info {
+            return Err(RouterError::DisconnectedOperations {});
+        }
+    }
+
     let mut ask_asset_map: HashMap<String, bool> = HashMap::new();
     for operation in operations.iter() {
         let (offer_asset, ask_asset) = match operation {
             SwapOperation::HaloSwap {
                 offer_asset_info,
                 ask_asset_info,
+                ..
             } => (offer_asset_info.clone(), ask_asset_info.clone()),
         };
 
@@ -45,10 +41,48 @@ pub fn assert_operations(operations: &[SwapOperation]) -> StdResult<()> {
     }
 
     if ask_asset_map.keys().len() != 1 {
-        return Err(StdError::generic_err(
-            "invalid operations; multiple output token",
-        ));
+        return Err(RouterError::MultipleOutputTokens {});
     }
 
     Ok(())
 }
+
+/// Every route swaps the offered token to the same token, without swapping back to the
+/// offered token which would spend the offer amounts of the next routes
+pub fn assert_split_routes(
+    offer_asset_info: &AssetInfo,
+    routes: &[SplitRoute],
+) -> Result<AssetInfo, RouterError> {
+    let mut target_asset_info: Option<AssetInfo> = None;
+    for route in routes.iter() {
+        let first_operation = route
+            .operations
+            .first()
+            .ok_or(RouterError::NoOperations {})?;
+        assert_operations(&route.operations)?;
+
+        let SwapOperation::HaloSwap {
+            offer_asset_info: route_offer_asset_info,
+            ..
+        } = first_operation;
+        if route_offer_asset_info != offer_asset_info
+            || route
+                .operations
+                .iter()
+                .any(|operation| operation.get_target_asset_info() == *offer_asset_info)
+        {
+            return Err(RouterError::InvalidRouteOffer {});
+        }
+
+        let route_target_asset_info =
+            route.operations[route.operations.len() - 1].get_target_asset_info();
+        match &target_asset_info {
+            Some(target_asset_info) if *target_asset_info != route_target_asset_info => {
+                return Err(RouterError::MultipleOutputTokens {});
+            }
+            _ => target_asset_info = Some(route_target_asset_info),
+        }
+    }
+
+    target_asset_info.ok_or(RouterError::NoRoutes {})
+}
@@ -2,33 +2,37 @@
 use cosmwasm_std::entry_point;
 
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, Api, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo,
-    Response, StdError, StdResult, Uint128, WasmMsg,
+    from_binary, to_binary, Addr, Api, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Reply,
+    Response, StdError, StdResult, Uint128,
 };
 use cw2::set_contract_version;
 
-use crate::assert::{assert_minium_receive, assert_operations};
-use crate::operations::execute_swap_operation;
-use crate::state::{Config, CONFIG};
+use crate::assert::{assert_deadline, assert_operations, assert_split_routes};
+use crate::operations::{execute_swap_exact_out, execute_swap_routes, swap_reply, SWAP_REPLY_ID};
+use crate::route::find_best_route;
+use crate::state::{Config, CONFIG, LEGACY_CONFIG};
 
 use cw20::Cw20ReceiveMsg;
-use haloswap::asset::{Asset, AssetInfo, PairInfo};
+use haloswap::asset::{Asset, AssetInfo, PairInfo, PairType};
+use haloswap::error::RouterError;
 use haloswap::pair::SimulationResponse;
 use haloswap::querier::{query_pair_info, reverse_simulate, simulate};
 use haloswap::router::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
-    SimulateSwapOperationsResponse, SwapOperation,
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, FindBestRouteResponse, InstantiateMsg, MigrateMsg,
+    QueryMsg, SimulateSplitSwapResponse, SimulateSwapOperationsResponse, SplitRoute, SwapOperation,
 };
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:halo-router";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+// the offer amount of a split swap is split in parts to suggest the split across the routes
+const SPLIT_PARTS: u128 = 20;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: InstantiateMsg,
 ) -> StdResult<Response> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
@@ -36,7 +40,9 @@ pub fn instantiate(
     CONFIG.save(
         deps.storage,
         &Config {
+            owner: deps.api.addr_canonicalize(info.sender.as_str())?,
             halo_factory: deps.api.addr_canonicalize(&msg.halo_factory)?,
+            max_spread: msg.max_spread,
         },
     )?;
 
@@ -44,48 +50,115 @@ pub fn instantiate(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, RouterError> {
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::ExecuteSwapOperations {
             operations,
             minimum_receive,
             to,
+            deadline,
         } => {
+            assert_deadline(&env, deadline)?;
+
+            let offer_asset = native_offer_asset(&info)?;
             let api = deps.api;
             execute_swap_operations(
                 deps,
-                env,
                 info.sender,
+                offer_asset,
                 operations,
                 minimum_receive,
                 optional_addr_validate(api, to)?,
             )
         }
-        ExecuteMsg::ExecuteSwapOperation { operation, to } => {
+        ExecuteMsg::ExecuteSwapOperationsExactOut {
+            operations,
+            ask_amount,
+            max_offer_amount,
+            to,
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+
+            let offer_asset = native_offer_asset(&info)?;
             let api = deps.api;
-            execute_swap_operation(
+            execute_swap_operations_exact_out(
                 deps,
-                env,
-                info,
-                operation,
-                optional_addr_validate(api, to)?.map(|v| v.to_string()),
+                info.sender,
+                offer_asset,
+                operations,
+                ask_amount,
+                max_offer_amount,
+                optional_addr_validate(api, to)?,
             )
         }
-        ExecuteMsg::AssertMinimumReceive {
-            asset_info,
-            prev_balance,
+        ExecuteMsg::ExecuteSplitSwap {
+            routes,
             minimum_receive,
-            receiver,
-        } => assert_minium_receive(
-            deps.as_ref(),
-            env,
-            info,
-            asset_info,
-            prev_balance,
+            to,
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+
+            let offer_asset = native_offer_asset(&info)?;
+            let api = deps.api;
+            execute_split_swap(
+                deps,
+                info.sender,
+                offer_asset,
+                routes,
+                minimum_receive,
+                optional_addr_validate(api, to)?,
+            )
+        }
+        ExecuteMsg::SwapAuto {
+            ask_asset_info,
             minimum_receive,
-            deps.api.addr_validate(&receiver)?,
-        ),
+            to,
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+
+            let offer_asset = native_offer_asset(&info)?;
+            let api = deps.api;
+            execute_swap_auto(
+                deps,
+                info.sender,
+                offer_asset,
+                ask_asset_info,
+                minimum_receive,
+                optional_addr_validate(api, to)?,
+            )
+        }
+        ExecuteMsg::Sweep { asset_info, to } => {
+            let api = deps.api;
+            execute_sweep(
+                deps,
+                env,
+                info,
+                asset_info,
+                optional_addr_validate(api, to)?,
+            )
+        }
+    }
+}
+
+// the native token sent with the message is swapped
+fn native_offer_asset(info: &MessageInfo) -> Result<Asset, RouterError> {
+    match info.funds.as_slice() {
+        [coin] => Ok(Asset {
+            info: AssetInfo::NativeToken {
+                denom: coin.denom.clone(),
+            },
+            amount: coin.amount,
+        }),
+        _ => Err(RouterError::InvalidSwapFunds {}),
     }
 }
 
@@ -102,22 +175,113 @@ fn optional_addr_validate(api: &dyn Api, addr: Option<String>) -> StdResult<Opti
 pub fn receive_cw20(
     deps: DepsMut,
     env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     cw20_msg: Cw20ReceiveMsg,
-) -> StdResult<Response> {
+) -> Result<Response, RouterError> {
     let sender = deps.api.addr_validate(&cw20_msg.sender)?;
     match from_binary(&cw20_msg.msg)? {
         Cw20HookMsg::ExecuteSwapOperations {
             operations,
             minimum_receive,
             to,
+            deadline,
         } => {
+            assert_deadline(&env, deadline)?;
+
+            // the received token is swapped
+            let offer_asset = Asset {
+                info: AssetInfo::Token {
+                    contract_addr: info.sender.to_string(),
+                },
+                amount: cw20_msg.amount,
+            };
+
             let api = deps.api;
             execute_swap_operations(
                 deps,
-                env,
                 sender,
+                offer_asset,
+                operations,
+                minimum_receive,
+                optional_addr_validate(api, to)?,
+            )
+        }
+        Cw20HookMsg::ExecuteSwapOperationsExactOut {
+            operations,
+            ask_amount,
+            max_offer_amount,
+            to,
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+
+            // the received token is swapped
+            let offer_asset = Asset {
+                info: AssetInfo::Token {
+                    contract_addr: info.sender.to_string(),
+                },
+                amount: cw20_msg.amount,
+            };
+
+            let api = deps.api;
+            execute_swap_operations_exact_out(
+                deps,
+                sender,
+                offer_asset,
                 operations,
+                ask_amount,
+                max_offer_amount,
+                optional_addr_validate(api, to)?,
+            )
+        }
+        Cw20HookMsg::ExecuteSplitSwap {
+            routes,
+            minimum_receive,
+            to,
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+
+            // the received token is swapped
+            let offer_asset = Asset {
+                info: AssetInfo::Token {
+                    contract_addr: info.sender.to_string(),
+                },
+                amount: cw20_msg.amount,
+            };
+
+            let api = deps.api;
+            execute_split_swap(
+                deps,
+                sender,
+                offer_asset,
+                routes,
+                minimum_receive,
+                optional_addr_validate(api, to)?,
+            )
+        }
+        Cw20HookMsg::SwapAuto {
+            ask_asset_info,
+            minimum_receive,
+            to,
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+
+            // the received token is swapped
+            let offer_asset = Asset {
+                info: AssetInfo::Token {
+                    contract_addr: info.sender.to_string(),
+                },
+                amount: cw20_msg.amount,
+            };
+
+            let api = deps.api;
+            execute_swap_auto(
+                deps,
+                sender,
+                offer_asset,
+                ask_asset_info,
                 minimum_receive,
                 optional_addr_validate(api, to)?,
             )
@@ -125,101 +289,271 @@ pub fn receive_cw20(
     }
 }
 
-pub fn execute_swap_operations(
+/// Swaps the exact offer amount of each hop, computed backwards from `ask_amount` with the
+/// reverse simulation of the pairs, and sends `ask_amount` to `to`.
+/// The offer amounts are rounded up by the pairs, so a hop may return slightly more than the
+/// next one needs, the remainder is refunded to the sender with the unspent offer amount
+#[allow(clippy::too_many_arguments)]
+pub fn execute_swap_operations_exact_out(
     deps: DepsMut,
-    env: Env,
     sender: Addr,
+    offer_asset: Asset,
     operations: Vec<SwapOperation>,
+    ask_amount: Uint128,
+    max_offer_amount: Uint128,
+    to: Option<Addr>,
+) -> Result<Response, RouterError> {
+    let first_operation = operations.first().ok_or(RouterError::NoOperations {})?;
+    assert_operations(&operations)?;
+
+    let SwapOperation::HaloSwap {
+        offer_asset_info, ..
+    } = first_operation;
+    if *offer_asset_info != offer_asset.info {
+        return Err(RouterError::InvalidRouteOffer {});
+    }
+    let target_asset_info = operations[operations.len() - 1].get_target_asset_info();
+
+    let config: Config = CONFIG.load(deps.storage)?;
+    let halo_factory = deps.api.addr_humanize(&config.halo_factory)?;
+
+    let mut amount = ask_amount;
+    let mut offer_amounts: Vec<Uint128> = vec![];
+    for operation in operations.iter().rev() {
+        let SwapOperation::HaloSwap {
+            offer_asset_info,
+            ask_asset_info,
+            pair_type,
+            ..
+        } = operation;
+
+        amount = reverse_simulate_return_amount(
+            deps.as_ref(),
+            halo_factory.clone(),
+            amount,
+            offer_asset_info.clone(),
+            ask_asset_info.clone(),
+            pair_type.clone(),
+        )?;
+        offer_amounts.push(amount);
+    }
+    offer_amounts.reverse();
+
+    // the sent amount can not be exceeded either
+    let max_offer_amount = max_offer_amount.min(offer_asset.amount);
+    if amount > max_offer_amount {
+        return Err(RouterError::MaxOfferAmountAssertion {
+            max_offer_amount,
+            offer_amount: amount,
+        });
+    }
+
+    let to = if let Some(to) = to {
+        to
+    } else {
+        sender.clone()
+    };
+    let res = execute_swap_exact_out(
+        deps,
+        operations,
+        offer_amounts,
+        Asset {
+            info: target_asset_info,
+            amount: ask_amount,
+        },
+        to,
+        sender.clone(),
+    )?;
+
+    // refund the unspent offer amount
+    let refund_amount = offer_asset.amount - amount;
+    if refund_amount.is_zero() {
+        return Ok(res);
+    }
+
+    Ok(res.add_message(
+        Asset {
+            info: offer_asset.info,
+            amount: refund_amount,
+        }
+        .into_msg(sender)?,
+    ))
+}
+
+pub fn execute_split_swap(
+    deps: DepsMut,
+    sender: Addr,
+    offer_asset: Asset,
+    routes: Vec<SplitRoute>,
     minimum_receive: Option<Uint128>,
     to: Option<Addr>,
-) -> StdResult<Response> {
-    let operations_len = operations.len();
-    if operations_len == 0 {
-        return Err(StdError::generic_err("must provide operations"));
+) -> Result<Response, RouterError> {
+    assert_split_routes(&offer_asset.info, &routes)?;
+
+    let routes_amount = routes.iter().try_fold(Uint128::zero(), |amount, route| {
+        amount.checked_add(route.offer_amount)
+    })?;
+    if routes_amount != offer_asset.amount {
+        return Err(RouterError::InvalidSplitAmount {
+            offer_amount: offer_asset.amount,
+        });
     }
 
-    // Assert the operations are properly set
-    assert_operations(&operations)?;
+    let to = if let Some(to) = to { to } else { sender };
+
+    // the amount received from all routes is asserted
+    execute_swap_routes(deps, routes, minimum_receive, to)
+}
+
+pub fn execute_swap_auto(
+    deps: DepsMut,
+    sender: Addr,
+    offer_asset: Asset,
+    ask_asset_info: AssetInfo,
+    minimum_receive: Option<Uint128>,
+    to: Option<Addr>,
+) -> Result<Response, RouterError> {
+    let (operations, _) = find_best_route(deps.as_ref(), &offer_asset, &ask_asset_info, None)?
+        .ok_or(RouterError::NoRouteFound {})?;
+
+    execute_swap_operations(deps, sender, offer_asset, operations, minimum_receive, to)
+}
+
+pub fn execute_swap_operations(
+    deps: DepsMut,
+    sender: Addr,
+    offer_asset: Asset,
+    operations: Vec<SwapOperation>,
+    minimum_receive: Option<Uint128>,
+    to: Option<Addr>,
+) -> Result<Response, RouterError> {
+    // the operations are a single route swapping the whole offer amount
+    let routes = vec![SplitRoute {
+        operations,
+        offer_amount: offer_asset.amount,
+    }];
+    assert_split_routes(&offer_asset.info, &routes)?;
 
     let to = if let Some(to) = to { to } else { sender };
-    let target_asset_info = operations.last().unwrap().get_target_asset_info();
-
-    let mut operation_index = 0;
-    let mut messages: Vec<CosmosMsg> = operations
-        .into_iter()
-        .map(|op| {
-            operation_index += 1;
-            Ok(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: env.contract.address.to_string(),
-                funds: vec![],
-                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
-                    operation: op,
-                    to: if operation_index == operations_len {
-                        Some(to.to_string())
-                    } else {
-                        None
-                    },
-                })?,
-            }))
-        })
-        .collect::<StdResult<Vec<CosmosMsg>>>()?;
-
-    // Execute minimum amount assertion
-    if let Some(minimum_receive) = minimum_receive {
-        let receiver_balance = target_asset_info.query_pool(&deps.querier, deps.api, to.clone())?;
-
-        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: env.contract.address.to_string(),
-            funds: vec![],
-            msg: to_binary(&ExecuteMsg::AssertMinimumReceive {
-                asset_info: target_asset_info,
-                prev_balance: receiver_balance,
-                minimum_receive,
-                receiver: to.to_string(),
-            })?,
-        }))
+
+    execute_swap_routes(deps, routes, minimum_receive, to)
+}
+
+// Only owner can execute it
+pub fn execute_sweep(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset_info: AssetInfo,
+    to: Option<Addr>,
+) -> Result<Response, RouterError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(RouterError::Unauthorized {});
+    }
+
+    // no swap is in progress, the whole balance is stuck in the router
+    let amount = asset_info.query_pool(&deps.querier, deps.api, env.contract.address)?;
+    let to = if let Some(to) = to { to } else { info.sender };
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !amount.is_zero() {
+        messages.push(
+            Asset {
+                info: asset_info.clone(),
+                amount,
+            }
+            .into_msg(to.clone())?,
+        );
     }
 
-    Ok(Response::new().add_messages(messages))
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "sweep"),
+        ("asset", &asset_info.to_string()),
+        ("to", to.as_str()),
+        ("amount", &amount.to_string()),
+    ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, RouterError> {
+    match msg.id {
+        SWAP_REPLY_ID => swap_reply(
+            deps,
+            msg.result.into_result().map_err(StdError::generic_err)?,
+        ),
+        id => Err(RouterError::UnknownReplyId { id }),
+    }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, RouterError> {
     match msg {
-        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::Config {} => Ok(to_binary(&query_config(deps)?)?),
         QueryMsg::SimulateSwapOperations {
             offer_amount,
             operations,
-        } => to_binary(&simulate_swap_operations(deps, offer_amount, operations)?),
+        } => Ok(to_binary(&simulate_swap_operations(
+            deps,
+            offer_amount,
+            operations,
+        )?)?),
         QueryMsg::ReverseSimulateSwapOperations {
             ask_amount,
             operations,
-        } => to_binary(&reverse_simulate_swap_operations(
+        } => Ok(to_binary(&reverse_simulate_swap_operations(
             deps, ask_amount, operations,
-        )?),
+        )?)?),
+        QueryMsg::SimulateSplitSwap { routes } => {
+            Ok(to_binary(&simulate_split_swap(deps, routes)?)?)
+        }
+        QueryMsg::FindBestRoute {
+            offer_asset,
+            ask_asset_info,
+            max_hops,
+        } => Ok(to_binary(&query_find_best_route(
+            deps,
+            offer_asset,
+            ask_asset_info,
+            max_hops,
+        )?)?),
     }
 }
 
 pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let state = CONFIG.load(deps.storage)?;
     let resp = ConfigResponse {
+        owner: deps.api.addr_humanize(&state.owner)?.to_string(),
         halo_factory: deps.api.addr_humanize(&state.halo_factory)?.to_string(),
+        max_spread: state.max_spread,
     };
 
     Ok(resp)
 }
 
+pub fn query_find_best_route(
+    deps: Deps,
+    offer_asset: Asset,
+    ask_asset_info: AssetInfo,
+    max_hops: Option<u32>,
+) -> Result<FindBestRouteResponse, RouterError> {
+    let (operations, amount) = find_best_route(deps, &offer_asset, &ask_asset_info, max_hops)?
+        .ok_or(RouterError::NoRouteFound {})?;
+
+    Ok(FindBestRouteResponse { operations, amount })
+}
+
 fn simulate_swap_operations(
     deps: Deps,
     offer_amount: Uint128,
     operations: Vec<SwapOperation>,
-) -> StdResult<SimulateSwapOperationsResponse> {
+) -> Result<SimulateSwapOperationsResponse, RouterError> {
     let config: Config = CONFIG.load(deps.storage)?;
     let halo_factory = deps.api.addr_humanize(&config.halo_factory)?;
 
     let operations_len = operations.len();
     if operations_len == 0 {
-        return Err(StdError::generic_err("must provide operations"));
+        return Err(RouterError::NoOperations {});
     }
 
     let mut offer_amount = offer_amount;
@@ -228,11 +562,14 @@ fn simulate_swap_operations(
             SwapOperation::HaloSwap {
                 offer_asset_info,
                 ask_asset_info,
+                pair_type,
+                ..
             } => {
                 let pair_info: PairInfo = query_pair_info(
                     &deps.querier,
                     halo_factory.clone(),
                     &[offer_asset_info.clone(), ask_asset_info.clone()],
+                    pair_type,
                 )?;
 
                 let res: SimulationResponse = simulate(
@@ -254,16 +591,112 @@ fn simulate_swap_operations(
     })
 }
 
+fn simulate_split_swap(
+    deps: Deps,
+    routes: Vec<SplitRoute>,
+) -> Result<SimulateSplitSwapResponse, RouterError> {
+    // the routes are validated as the split swap validates them, all of them must swap the
+    // token offered by the first one
+    let first_route = routes.first().ok_or(RouterError::NoRoutes {})?;
+    let SwapOperation::HaloSwap {
+        offer_asset_info, ..
+    } = first_route
+        .operations
+        .first()
+        .ok_or(RouterError::NoOperations {})?;
+    assert_split_routes(offer_asset_info, &routes)?;
+
+    let mut offer_amount = Uint128::zero();
+    let mut amount = Uint128::zero();
+    for route in routes.iter() {
+        offer_amount = offer_amount.checked_add(route.offer_amount)?;
+        if !route.offer_amount.is_zero() {
+            amount = amount.checked_add(
+                simulate_swap_operations(deps, route.offer_amount, route.operations.clone())?
+                    .amount,
+            )?;
+        }
+    }
+
+    let (offer_amounts, suggested_amount) = suggest_split(deps, offer_amount, &routes)?;
+
+    Ok(SimulateSplitSwapResponse {
+        amount,
+        suggested_routes: routes
+            .into_iter()
+            .zip(offer_amounts)
+            .map(|(route, offer_amount)| SplitRoute {
+                operations: route.operations,
+                offer_amount,
+            })
+            .collect(),
+        suggested_amount,
+    })
+}
+
+// each part of the offer amount is given to the route whose return amount increases the most,
+// returns the offer amounts of the routes and their total return amount
+fn suggest_split(
+    deps: Deps,
+    offer_amount: Uint128,
+    routes: &[SplitRoute],
+) -> Result<(Vec<Uint128>, Uint128), RouterError> {
+    let mut offer_amounts = vec![Uint128::zero(); routes.len()];
+    let mut return_amounts = vec![Uint128::zero(); routes.len()];
+
+    let part_amount = offer_amount / Uint128::from(SPLIT_PARTS);
+    for part in 0..SPLIT_PARTS {
+        // the last part takes the remainder
+        let amount = if part + 1 == SPLIT_PARTS {
+            offer_amount - part_amount * Uint128::from(SPLIT_PARTS - 1)
+        } else {
+            part_amount
+        };
+        if amount.is_zero() {
+            continue;
+        }
+
+        let mut best: Option<(usize, Uint128)> = None;
+        for (index, route) in routes.iter().enumerate() {
+            let return_amount = simulate_swap_operations(
+                deps,
+                offer_amounts[index] + amount,
+                route.operations.clone(),
+            )?
+            .amount;
+            let is_better = match best {
+                // a pool may return less for a larger amount because of the rounding
+                Some((best_index, best_return_amount)) => {
+                    return_amount.saturating_sub(return_amounts[index])
+                        > best_return_amount.saturating_sub(return_amounts[best_index])
+                }
+                None => true,
+            };
+            if is_better {
+                best = Some((index, return_amount));
+            }
+        }
+
+        if let Some((index, return_amount)) = best {
+            offer_amounts[index] += amount;
+            return_amounts[index] = return_amount;
+        }
+    }
+
+    let return_amount = return_amounts.into_iter().sum();
+    Ok((offer_amounts, return_amount))
+}
+
 fn reverse_simulate_swap_operations(
     deps: Deps,
     ask_amount: Uint128,
     operations: Vec<SwapOperation>,
-) -> StdResult<SimulateSwapOperationsResponse> {
+) -> Result<SimulateSwapOperationsResponse, RouterError> {
     let config: Config = CONFIG.load(deps.storage)?;
 
     let operations_len = operations.len();
     if operations_len == 0 {
-        return Err(StdError::generic_err("must provide operations"));
+        return Err(RouterError::NoOperations {});
     }
 
     let mut ask_amount = ask_amount;
@@ -272,6 +705,8 @@ fn reverse_simulate_swap_operations(
             SwapOperation::HaloSwap {
                 offer_asset_info,
                 ask_asset_info,
+                pair_type,
+                ..
             } => {
                 let halo_factory = deps.api.addr_humanize(&config.halo_factory)?;
 
@@ -281,8 +716,8 @@ fn reverse_simulate_swap_operations(
                     ask_amount,
                     offer_asset_info,
                     ask_asset_info,
-                )
-                .unwrap()
+                    pair_type,
+                )?
             }
         }
     }
@@ -296,11 +731,13 @@ fn reverse_simulate_return_amount(
     ask_amount: Uint128,
     offer_asset_info: AssetInfo,
     ask_asset_info: AssetInfo,
+    pair_type: Option<PairType>,
 ) -> StdResult<Uint128> {
     let pair_info: PairInfo = query_pair_info(
         &deps.querier,
         factory,
         &[offer_asset_info, ask_asset_info.clone()],
+        pair_type,
     )?;
 
     let res = reverse_simulate(
@@ -316,7 +753,30 @@ fn reverse_simulate_return_amount(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, RouterError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    // the config is read without the owner, which the routers instantiated before it was added
+    // do not have
+    match msg.owner {
+        Some(owner) => {
+            let config = LEGACY_CONFIG.load(deps.storage)?;
+            CONFIG.save(
+                deps.storage,
+                &Config {
+                    owner: deps.api.addr_canonicalize(&owner)?,
+                    halo_factory: config.halo_factory,
+                    max_spread: config.max_spread,
+                },
+            )?;
+        }
+        // the config could not be loaded anymore without the owner
+        None => {
+            if CONFIG.load(deps.storage).is_err() {
+                return Err(RouterError::OwnerRequired {});
+            }
+        }
+    }
+
     Ok(Response::default())
 }
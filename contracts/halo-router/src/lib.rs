@@ -1,6 +1,7 @@
 pub mod assert;
 pub mod contract;
 mod operations;
+mod route;
 pub mod state;
 
 #[cfg(test)]
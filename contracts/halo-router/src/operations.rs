@@ -1,74 +1,284 @@
+use std::str::FromStr;
+
 use cosmwasm_std::{
-    to_binary, Addr, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo, Response, StdError,
-    StdResult, WasmMsg,
+    to_binary, Addr, Coin, CosmosMsg, Decimal, Deps, DepsMut, Response, StdResult, SubMsg,
+    SubMsgResponse, Uint128, WasmMsg,
 };
 
-use crate::state::{Config, CONFIG};
+use crate::state::{Config, ExactOutState, SwapState, CONFIG, SWAP_STATE};
 
 use cw20::Cw20ExecuteMsg;
 use haloswap::asset::{Asset, AssetInfo, PairInfo};
+use haloswap::error::RouterError;
 use haloswap::pair::Cw20HookMsg as PairHookMsg;
-use haloswap::querier::{query_balance, query_pair_info, query_token_balance};
-use haloswap::router::SwapOperation;
+use haloswap::querier::query_pair_info;
+use haloswap::router::{SplitRoute, SwapOperation};
+
+pub const SWAP_REPLY_ID: u64 = 1;
 
-/// Execute swap operation
-/// swap all offer asset to ask asset
-pub fn execute_swap_operation(
+/// Swaps the offer amount of each route, the return amount of each hop is read from its reply
+/// and swapped by the next one, so the balance held by the router is never swapped
+pub fn execute_swap_routes(
     deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    operation: SwapOperation,
-    to: Option<String>,
-) -> StdResult<Response> {
-    if env.contract.address != info.sender {
-        return Err(StdError::generic_err("unauthorized"));
+    routes: Vec<SplitRoute>,
+    minimum_receive: Option<Uint128>,
+    to: Addr,
+) -> Result<Response, RouterError> {
+    // a token called by a hop must not start another swap
+    if SWAP_STATE.may_load(deps.storage)?.is_some() {
+        return Err(RouterError::SwapInProgress {});
     }
 
-    let messages: Vec<CosmosMsg> = match operation {
-        SwapOperation::HaloSwap {
-            offer_asset_info,
-            ask_asset_info,
-        } => {
-            let config: Config = CONFIG.load(deps.as_ref().storage)?;
-            let halo_factory = deps.api.addr_humanize(&config.halo_factory)?;
-            let pair_info: PairInfo = query_pair_info(
-                &deps.querier,
-                halo_factory,
-                &[offer_asset_info.clone(), ask_asset_info],
-            )?;
-
-            let amount = match offer_asset_info.clone() {
-                AssetInfo::NativeToken { denom } => {
-                    query_balance(&deps.querier, env.contract.address, denom)?
-                }
-                AssetInfo::Token { contract_addr } => query_token_balance(
-                    &deps.querier,
-                    deps.api.addr_validate(contract_addr.as_str())?,
-                    env.contract.address,
-                )?,
-            };
-            let offer_asset: Asset = Asset {
-                info: offer_asset_info,
-                amount,
-            };
-
-            vec![asset_into_swap_msg(
-                deps.as_ref(),
-                Addr::unchecked(pair_info.contract_addr),
-                offer_asset,
-                None,
-                to,
-            )?]
-        }
+    let mut routes: Vec<SplitRoute> = routes
+        .into_iter()
+        .filter(|route| !route.offer_amount.is_zero())
+        .collect();
+    if routes.is_empty() {
+        return Err(RouterError::NoRoutes {});
+    }
+
+    let route = routes.remove(0);
+    let mut state = SwapState {
+        pair_contract: Addr::unchecked(""),
+        operations: route.operations,
+        routes,
+        minimum_receive,
+        to,
+        return_amount: Uint128::zero(),
+        exact_out: None,
     };
+    let message = swap_hop_msg(deps.as_ref(), &mut state, route.offer_amount)?;
+    SWAP_STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new().add_submessage(message))
+}
+
+/// Swaps the offer amounts of the operations, computed backwards from the ask amount, each hop
+/// swaps its offer amount out of the return amount of the previous one, read from its reply,
+/// and the router sends the ask amount out of the return amount of the last one.
+/// The surplus of each return amount is refunded to the sender
+pub fn execute_swap_exact_out(
+    deps: DepsMut,
+    operations: Vec<SwapOperation>,
+    mut offer_amounts: Vec<Uint128>,
+    ask_asset: Asset,
+    to: Addr,
+    sender: Addr,
+) -> Result<Response, RouterError> {
+    // a token called by a hop must not start another swap
+    if SWAP_STATE.may_load(deps.storage)?.is_some() {
+        return Err(RouterError::SwapInProgress {});
+    }
+    if offer_amounts.is_empty() {
+        return Err(RouterError::NoOperations {});
+    }
+
+    let offer_amount = offer_amounts.remove(0);
+    let mut state = SwapState {
+        pair_contract: Addr::unchecked(""),
+        operations,
+        routes: vec![],
+        minimum_receive: None,
+        to,
+        return_amount: Uint128::zero(),
+        exact_out: Some(ExactOutState {
+            offer_amounts,
+            ask_asset,
+            sender,
+        }),
+    };
+    let message = swap_hop_msg(deps.as_ref(), &mut state, offer_amount)?;
+    SWAP_STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new().add_submessage(message))
+}
+
+/// Swaps the return amount of the hop with the next operation or starts the next route,
+/// the minimum receive is asserted on the amount received from all routes
+pub fn swap_reply(deps: DepsMut, result: SubMsgResponse) -> Result<Response, RouterError> {
+    let mut state = SWAP_STATE.load(deps.storage)?;
+    let return_amount = parse_return_amount(&result, &state.pair_contract)?;
+
+    if let Some(exact_out) = state.exact_out.take() {
+        return exact_out_reply(deps, state, exact_out, return_amount);
+    }
+
+    if !state.operations.is_empty() {
+        let message = swap_hop_msg(deps.as_ref(), &mut state, return_amount)?;
+        SWAP_STATE.save(deps.storage, &state)?;
+        return Ok(Response::new().add_submessage(message));
+    }
+
+    state.return_amount = state.return_amount.checked_add(return_amount)?;
+    if !state.routes.is_empty() {
+        let route = state.routes.remove(0);
+        state.operations = route.operations;
+        let message = swap_hop_msg(deps.as_ref(), &mut state, route.offer_amount)?;
+        SWAP_STATE.save(deps.storage, &state)?;
+        return Ok(Response::new().add_submessage(message));
+    }
+
+    SWAP_STATE.remove(deps.storage);
+    if let Some(minimum_receive) = state.minimum_receive {
+        if state.return_amount < minimum_receive {
+            return Err(RouterError::MinimumReceiveAssertion {
+                minimum_receive,
+                swap_amount: state.return_amount,
+            });
+        }
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "swap_operations"),
+        ("receiver", state.to.as_str()),
+        ("return_amount", &state.return_amount.to_string()),
+    ]))
+}
+
+// swaps the offer amount of the next operation out of the return amount of the hop, the router
+// sends the ask amount out of the return amount of the last hop
+fn exact_out_reply(
+    deps: DepsMut,
+    mut state: SwapState,
+    mut exact_out: ExactOutState,
+    return_amount: Uint128,
+) -> Result<Response, RouterError> {
+    if !state.operations.is_empty() {
+        // the pools round the offer amounts up, so the hop returns at least the offer amount
+        // of the next one, unless the route goes through a pool twice
+        let offer_amount = exact_out.offer_amounts.remove(0).min(return_amount);
+        let SwapOperation::HaloSwap {
+            offer_asset_info, ..
+        } = state.operations[0].clone();
+        let refund_to = exact_out.sender.clone();
+        state.exact_out = Some(exact_out);
+        let message = swap_hop_msg(deps.as_ref(), &mut state, offer_amount)?;
+        SWAP_STATE.save(deps.storage, &state)?;
+
+        return Ok(Response::new()
+            .add_submessage(message)
+            .add_messages(refund_msg(
+                Asset {
+                    info: offer_asset_info,
+                    amount: return_amount - offer_amount,
+                },
+                refund_to,
+            )?));
+    }
+
+    SWAP_STATE.remove(deps.storage);
+    let ask_asset = exact_out.ask_asset;
+    let refund_amount = return_amount.checked_sub(ask_asset.amount).map_err(|_| {
+        RouterError::MinimumReceiveAssertion {
+            minimum_receive: ask_asset.amount,
+            swap_amount: return_amount,
+        }
+    })?;
+
+    Ok(Response::new()
+        .add_message(ask_asset.clone().into_msg(state.to.clone())?)
+        .add_messages(refund_msg(
+            Asset {
+                info: ask_asset.info.clone(),
+                amount: refund_amount,
+            },
+            exact_out.sender,
+        )?)
+        .add_attributes(vec![
+            ("action", "swap_operations_exact_out"),
+            ("receiver", state.to.as_str()),
+            ("ask_amount", &ask_asset.amount.to_string()),
+        ]))
+}
+
+// the surplus returned by a hop of an exact output swap goes back to the sender
+fn refund_msg(asset: Asset, sender: Addr) -> StdResult<Vec<CosmosMsg>> {
+    if asset.amount.is_zero() {
+        return Ok(vec![]);
+    }
+
+    Ok(vec![asset.into_msg(sender)?])
+}
+
+// the next operation of the current route swaps `offer_amount`, the last one of the route
+// sends its return amount to the receiver, unless the router sends the exact output amount
+fn swap_hop_msg(
+    deps: Deps,
+    state: &mut SwapState,
+    offer_amount: Uint128,
+) -> Result<SubMsg, RouterError> {
+    if state.operations.is_empty() {
+        return Err(RouterError::NoOperations {});
+    }
+    let SwapOperation::HaloSwap {
+        offer_asset_info,
+        ask_asset_info,
+        max_spread,
+        belief_price,
+        pair_type,
+    } = state.operations.remove(0);
+
+    let config: Config = CONFIG.load(deps.storage)?;
+    let halo_factory = deps.api.addr_humanize(&config.halo_factory)?;
+    let pair_info: PairInfo = query_pair_info(
+        &deps.querier,
+        halo_factory,
+        &[offer_asset_info.clone(), ask_asset_info],
+        pair_type,
+    )?;
+    state.pair_contract = Addr::unchecked(pair_info.contract_addr);
+
+    let message = asset_into_swap_msg(
+        deps,
+        state.pair_contract.clone(),
+        Asset {
+            info: offer_asset_info,
+            amount: offer_amount,
+        },
+        belief_price,
+        // the hop falls back to the max spread of the router
+        max_spread.or(config.max_spread),
+        if state.operations.is_empty() && state.exact_out.is_none() {
+            Some(state.to.to_string())
+        } else {
+            None
+        },
+    )?;
+
+    Ok(SubMsg::reply_on_success(message, SWAP_REPLY_ID))
+}
+
+// the return amount is an attribute of the swap event of the pair, which is nested
+// in the events of the token when the offer asset is a token
+fn parse_return_amount(
+    result: &SubMsgResponse,
+    pair_contract: &Addr,
+) -> Result<Uint128, RouterError> {
+    let return_amount = result
+        .events
+        .iter()
+        .filter(|event| {
+            event.ty == "wasm"
+                && event.attributes.iter().any(|attr| {
+                    attr.key == "_contract_addr" && attr.value == pair_contract.as_str()
+                })
+        })
+        .find_map(|event| {
+            event
+                .attributes
+                .iter()
+                .find(|attr| attr.key == "return_amount")
+        })
+        .ok_or(RouterError::ReturnAmountNotFound {})?;
 
-    Ok(Response::new().add_messages(messages))
+    Ok(Uint128::from_str(&return_amount.value)?)
 }
 
 pub fn asset_into_swap_msg(
     _deps: Deps,
     pair_contract: Addr,
     offer_asset: Asset,
+    belief_price: Option<Decimal>,
     max_spread: Option<Decimal>,
     to: Option<String>,
 ) -> StdResult<CosmosMsg> {
@@ -81,9 +291,11 @@ pub fn asset_into_swap_msg(
             }],
             msg: to_binary(&PairHookMsg::Swap {
                 offer_asset,
-                belief_price: None,
+                belief_price,
                 max_spread,
                 to,
+                // the deadline is checked by the router
+                deadline: None,
             })?,
         })),
         AssetInfo::Token { contract_addr } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
@@ -94,9 +306,10 @@ pub fn asset_into_swap_msg(
                 amount: offer_asset.amount,
                 msg: to_binary(&PairHookMsg::Swap {
                     offer_asset,
-                    belief_price: None,
+                    belief_price,
                     max_spread,
                     to,
+                    deadline: None,
                 })?,
             })?,
         })),
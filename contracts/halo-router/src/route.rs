@@ -0,0 +1,160 @@
+use cosmwasm_std::{Addr, Deps, StdResult, Uint128};
+
+use crate::state::{Config, CONFIG};
+
+use haloswap::asset::{Asset, AssetInfo};
+use haloswap::querier::{query_pairs_by_asset, simulate};
+use haloswap::router::SwapOperation;
+
+// settings for the route discovery
+const MAX_HOPS: u32 = 4;
+const DEFAULT_MAX_HOPS: u32 = 3;
+// pairs listed by each query of the factory, the pairs of an asset are paged through
+const PAIRS_LIMIT: u32 = 30;
+// the search stops once this many swaps are simulated, which bounds its gas
+const MAX_SIMULATIONS: u32 = 60;
+
+/// Finds the route from the offer asset to the ask asset which returns the most,
+/// the shortest one is kept among the routes returning the same amount
+pub fn find_best_route(
+    deps: Deps,
+    offer_asset: &Asset,
+    ask_asset_info: &AssetInfo,
+    max_hops: Option<u32>,
+) -> StdResult<Option<(Vec<SwapOperation>, Uint128)>> {
+    let max_hops = max_hops.unwrap_or(DEFAULT_MAX_HOPS).min(MAX_HOPS);
+    let config: Config = CONFIG.load(deps.storage)?;
+    let halo_factory = deps.api.addr_humanize(&config.halo_factory)?;
+
+    let mut simulations = MAX_SIMULATIONS;
+    let mut best_route = None;
+    search_routes(
+        deps,
+        &halo_factory,
+        offer_asset.clone(),
+        ask_asset_info,
+        max_hops,
+        &mut simulations,
+        &mut vec![offer_asset.info.clone()],
+        &mut vec![],
+        &mut best_route,
+    )?;
+
+    Ok(best_route)
+}
+
+// depth first search of the routes which do not go through an asset twice, the pairs of
+// each asset are listed page by page when it is reached, the deprecated pairs are not listed
+#[allow(clippy::too_many_arguments)]
+fn search_routes(
+    deps: Deps,
+    halo_factory: &Addr,
+    offer_asset: Asset,
+    ask_asset_info: &AssetInfo,
+    hops: u32,
+    simulations: &mut u32,
+    visited: &mut Vec<AssetInfo>,
+    route: &mut Vec<SwapOperation>,
+    best_route: &mut Option<(Vec<SwapOperation>, Uint128)>,
+) -> StdResult<()> {
+    if hops == 0 || *simulations == 0 {
+        return Ok(());
+    }
+
+    // all the pairs of the asset are simulated before the routes through them are searched,
+    // so the shorter routes are considered before the simulations run out
+    let mut next_assets: Vec<(SwapOperation, Asset)> = vec![];
+    let mut start_after: Option<String> = None;
+    loop {
+        let pairs = query_pairs_by_asset(
+            &deps.querier,
+            halo_factory.clone(),
+            offer_asset.info.clone(),
+            start_after,
+            Some(PAIRS_LIMIT),
+        )?
+        .pairs;
+        let is_last_page = pairs.len() < PAIRS_LIMIT as usize;
+        start_after = pairs.last().map(|pair| pair.contract_addr.clone());
+
+        for pair in pairs.into_iter() {
+            let next_asset_info = if pair.asset_infos[0] == offer_asset.info {
+                &pair.asset_infos[1]
+            } else {
+                &pair.asset_infos[0]
+            };
+            if visited.contains(next_asset_info) {
+                continue;
+            }
+            if *simulations == 0 {
+                break;
+            }
+            *simulations -= 1;
+
+            // the pools which can not be simulated, e.g. the empty ones, are skipped
+            let return_amount = match simulate(
+                &deps.querier,
+                Addr::unchecked(&pair.contract_addr),
+                &offer_asset,
+            ) {
+                Ok(res) if !res.return_amount.is_zero() => res.return_amount,
+                _ => continue,
+            };
+
+            let operation = SwapOperation::HaloSwap {
+                offer_asset_info: offer_asset.info.clone(),
+                ask_asset_info: next_asset_info.clone(),
+                max_spread: None,
+                belief_price: None,
+                pair_type: Some(pair.pair_type.clone()),
+            };
+            if next_asset_info == ask_asset_info {
+                let is_better = match best_route {
+                    Some((best_operations, best_amount)) => {
+                        return_amount > *best_amount
+                            || (return_amount == *best_amount
+                                && route.len() + 1 < best_operations.len())
+                    }
+                    None => true,
+                };
+                if is_better {
+                    let mut operations = route.clone();
+                    operations.push(operation);
+                    *best_route = Some((operations, return_amount));
+                }
+            } else {
+                next_assets.push((
+                    operation,
+                    Asset {
+                        info: next_asset_info.clone(),
+                        amount: return_amount,
+                    },
+                ));
+            }
+        }
+
+        if is_last_page || *simulations == 0 {
+            break;
+        }
+    }
+
+    for (operation, next_asset) in next_assets.into_iter() {
+        visited.push(next_asset.info.clone());
+        route.push(operation);
+        search_routes(
+            deps,
+            halo_factory,
+            next_asset,
+            ask_asset_info,
+            hops - 1,
+            simulations,
+            visited,
+            route,
+            best_route,
+        )?;
+        route.pop();
+        visited.pop();
+    }
+
+    Ok(())
+}
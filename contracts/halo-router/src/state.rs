@@ -1,11 +1,57 @@
 use cosmwasm_schema::cw_serde;
 
-use cosmwasm_std::CanonicalAddr;
+use cosmwasm_std::{Addr, CanonicalAddr, Decimal, Uint128};
 use cw_storage_plus::Item;
+use haloswap::asset::Asset;
+use haloswap::router::{SplitRoute, SwapOperation};
+use serde::{Deserialize, Serialize};
 
 #[cw_serde]
 pub struct Config {
+    pub owner: CanonicalAddr,
     pub halo_factory: CanonicalAddr,
+    pub max_spread: Option<Decimal>,
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
+
+// the config without the owner, before it was added
+#[derive(Serialize, Deserialize)]
+pub struct LegacyConfig {
+    pub halo_factory: CanonicalAddr,
+    pub max_spread: Option<Decimal>,
+}
+
+pub const LEGACY_CONFIG: Item<LegacyConfig> = Item::new("config");
+
+/// The swap in progress, each hop swaps the return amount of the previous one
+/// and is executed in its reply
+#[cw_serde]
+pub struct SwapState {
+    /// Pair of the hop in progress
+    pub pair_contract: Addr,
+    /// Operations of the current route after the hop in progress
+    pub operations: Vec<SwapOperation>,
+    /// Routes to swap after the current one
+    pub routes: Vec<SplitRoute>,
+    pub minimum_receive: Option<Uint128>,
+    pub to: Addr,
+    /// Amount received from the finished routes
+    pub return_amount: Uint128,
+    /// Set by the exact output swaps, whose last hop returns to the router
+    pub exact_out: Option<ExactOutState>,
+}
+
+/// The exact output swap in progress, each hop swaps its offer amount computed with the
+/// reverse simulation out of the return amount of the previous one
+#[cw_serde]
+pub struct ExactOutState {
+    /// Offer amounts of the operations after the hop in progress
+    pub offer_amounts: Vec<Uint128>,
+    /// Sent to the receiver out of the return amount of the last hop
+    pub ask_asset: Asset,
+    /// Refunded with the surplus of the return amounts
+    pub sender: Addr,
+}
+
+pub const SWAP_STATE: Item<SwapState> = Item::new("swap_state");
@@ -16,7 +16,7 @@ pub mod env {
 
     use crate::contract::{
         execute as HaloRouterExecute, instantiate as HaloRouterInstantiate,
-        query as HaloRouterQuery,
+        query as HaloRouterQuery, reply as HaloRouterReply,
     };
 
     use cw20_base::contract::{
@@ -39,6 +39,9 @@ pub mod env {
     pub const NATIVE_DENOM_2: &str = "utaura";
     pub const NATIVE_BALANCE_2: u128 = 500_000_000_000u128;
 
+    // LP token locked by the first provision of the pairs
+    pub const MIN_LIQUIDITY: u128 = 1u128;
+
     pub const HALO_TOKEN_SYMBOL: &str = "HALO";
     pub const HALO_TOKEN_NAME: &str = "Halo Token";
     pub const HALO_TOKEN_DECIMALS: u8 = 18;
@@ -52,6 +55,7 @@ pub mod env {
     pub const USDC_TOKEN_NAME: &str = "USDC Token";
     pub const USDC_TOKEN_DECIMALS: u8 = 18;
 
+    #[allow(dead_code)]
     pub struct ContractInfo {
         pub contract_addr: String,
         pub contract_code_id: u64,
@@ -94,7 +98,8 @@ pub mod env {
 
     fn halo_router_contract_template() -> Box<dyn Contract<Empty>> {
         let contract =
-            ContractWrapper::new(HaloRouterExecute, HaloRouterInstantiate, HaloRouterQuery);
+            ContractWrapper::new(HaloRouterExecute, HaloRouterInstantiate, HaloRouterQuery)
+                .with_reply(HaloRouterReply);
         Box::new(contract)
     }
 
@@ -154,6 +159,11 @@ pub mod env {
         let halo_factory_contract_instantiate_msg = HaloFactoryInstantiateMsg {
             pair_code_id: halo_pair_contract_code_id,
             token_code_id: halo_token_contract_code_id,
+            protocol_fee_rate: None,
+            flash_fee_rate: None,
+            fee_collector: None,
+            pair_creation_mode: None,
+            min_liquidity: Some(Uint128::from(MIN_LIQUIDITY)),
         };
 
         // instantiate contract
@@ -187,6 +197,7 @@ pub mod env {
                 Addr::unchecked(ADMIN),
                 &HaloRouterInstantiateMsg {
                     halo_factory: halo_factory_contract_addr.to_string(),
+                    max_spread: None,
                 },
                 &[],
                 "test instantiate contract",
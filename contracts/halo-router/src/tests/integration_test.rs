@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod tests {
     use crate::tests::env_setup::env::{
-        instantiate_contracts, ADMIN, NATIVE_DENOM, NATIVE_DENOM_2, USER_1,
+        instantiate_contracts, ADMIN, MIN_LIQUIDITY, NATIVE_DENOM, NATIVE_DENOM_2, USER_1,
     };
     use bignumber::Decimal256;
     use cosmwasm_std::{
@@ -10,7 +10,7 @@ mod tests {
     };
     use cw20::{BalanceResponse, TokenInfoResponse};
     use cw20_base::{msg::ExecuteMsg as Cw20ExecuteMsg, msg::QueryMsg as Cw20QueryMsg};
-    use haloswap::asset::{AssetInfo, CreatePairRequirements, PairInfo};
+    use haloswap::asset::{AssetInfo, CreatePairRequirements, PairInfo, PairType};
     use haloswap::factory::{
         ExecuteMsg as FactoryExecuteMsg, NativeTokenDecimalsResponse, QueryMsg as FactoryQueryMsg,
     };
@@ -30,7 +30,8 @@ mod tests {
         use cosmwasm_std::Querier;
         use cw_multi_test::Executor;
         use haloswap::{
-            asset::{Asset, LPTokenInfo, LP_TOKEN_RESERVED_AMOUNT},
+            asset::{Asset, LPTokenInfo},
+            error::ContractError,
             pair::{ExecuteMsg, PoolResponse, QueryMsg},
             router::{ExecuteMsg as RouterExecuteMsg, SwapOperation},
         };
@@ -179,16 +180,14 @@ mod tests {
                     lp_token_symbol: "aura-HALO".to_string(),
                     lp_token_decimals: None,
                 },
+                pair_type: None,
             };
 
             let response = app.execute_contract(
                 Addr::unchecked(ADMIN.to_string()),
                 Addr::unchecked(factory_contract.clone()),
                 &create_pair_msg,
-                &[Coin {
-                    amount: Uint128::from(500000u128),
-                    denom: NATIVE_DENOM_2.to_string(),
-                }],
+                &[],
             );
 
             assert!(response.is_ok());
@@ -207,6 +206,7 @@ mod tests {
                                 contract_addr: cw20_token_contract.clone(),
                             },
                         ],
+                        pair_type: None,
                     },
                 )
                 .unwrap();
@@ -233,6 +233,7 @@ mod tests {
                     },
                     // Verify the default commission rate is 0.3%
                     commission_rate: Decimal256::from_str("0.03").unwrap(),
+                    pair_type: PairType::Xyk,
                 }
             );
 
@@ -293,6 +294,7 @@ mod tests {
                 ],
                 slippage_tolerance: None,
                 receiver: None,
+                deadline: None,
             };
 
             let response = app.execute_contract(
@@ -370,7 +372,7 @@ mod tests {
             assert_eq!(
                 response,
                 BalanceResponse {
-                    balance: Uint128::from(LP_TOKEN_RESERVED_AMOUNT),
+                    balance: Uint128::from(MIN_LIQUIDITY),
                 }
             );
 
@@ -383,9 +385,13 @@ mod tests {
                     ask_asset_info: AssetInfo::Token {
                         contract_addr: cw20_token_contract.clone(),
                     },
+                    max_spread: None,
+                    belief_price: None,
+                    pair_type: None,
                 }],
                 minimum_receive: Some(Uint128::from(485u128)),
                 to: None,
+                deadline: None,
             };
 
             let response = app.execute_contract(
@@ -469,7 +475,12 @@ mod tests {
             let send_lp_token_msg = Cw20ExecuteMsg::Send {
                 contract: "contract5".to_string(),
                 amount: Uint128::from(1414212u128),
-                msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {}).unwrap(),
+                msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+                    min_assets: None,
+                    receiver: None,
+                    deadline: None,
+                })
+                .unwrap(),
             };
 
             // Execute Send LP token to Pair Contract
@@ -512,7 +523,7 @@ mod tests {
                         },
                     ],
                     // Verify the total share amount is reserved 1 uLP
-                    total_share: LP_TOKEN_RESERVED_AMOUNT.into(),
+                    total_share: MIN_LIQUIDITY.into(),
                 }
             );
 
@@ -717,16 +728,14 @@ mod tests {
                     lp_token_symbol: "UAURA-HALO".to_string(),
                     lp_token_decimals: None,
                 },
+                pair_type: None,
             };
 
             let response = app.execute_contract(
                 Addr::unchecked(ADMIN.to_string()),
                 Addr::unchecked(factory_contract.clone()),
                 &create_pair_msg,
-                &[Coin {
-                    amount: Uint128::from(500000u128),
-                    denom: NATIVE_DENOM.to_string(),
-                }],
+                &[],
             );
 
             assert!(response.is_ok());
@@ -745,6 +754,7 @@ mod tests {
                                 contract_addr: cw20_token_contract.clone(),
                             },
                         ],
+                        pair_type: None,
                     },
                 )
                 .unwrap();
@@ -775,16 +785,14 @@ mod tests {
                     lp_token_symbol: "UTAURA-HALO".to_string(),
                     lp_token_decimals: None,
                 },
+                pair_type: None,
             };
 
             let response = app.execute_contract(
                 Addr::unchecked(ADMIN.to_string()),
                 Addr::unchecked(factory_contract.clone()),
                 &create_pair_msg,
-                &[Coin {
-                    amount: Uint128::from(500000u128),
-                    denom: NATIVE_DENOM_2.to_string(),
-                }],
+                &[],
             );
 
             assert!(response.is_ok());
@@ -803,6 +811,7 @@ mod tests {
                                 contract_addr: cw20_token_contract.clone(),
                             },
                         ],
+                        pair_type: None,
                     },
                 )
                 .unwrap();
@@ -833,16 +842,14 @@ mod tests {
                     lp_token_symbol: "UAURA-UTAURA".to_string(),
                     lp_token_decimals: None,
                 },
+                pair_type: None,
             };
 
             let response = app.execute_contract(
                 Addr::unchecked(ADMIN.to_string()),
                 Addr::unchecked(factory_contract.clone()),
                 &create_pair_msg,
-                &[Coin {
-                    amount: Uint128::from(500000u128),
-                    denom: NATIVE_DENOM_2.to_string(),
-                }],
+                &[],
             );
 
             assert!(response.is_ok());
@@ -861,6 +868,7 @@ mod tests {
                                 denom: NATIVE_DENOM_2.to_string(),
                             },
                         ],
+                        pair_type: None,
                     },
                 )
                 .unwrap();
@@ -920,6 +928,7 @@ mod tests {
                                 contract_addr: cw20_token_contract.clone(),
                             },
                         ],
+                        pair_type: None,
                     },
                 )
                 .unwrap();
@@ -941,6 +950,7 @@ mod tests {
                                 contract_addr: cw20_token_contract,
                             },
                         ],
+                        pair_type: None,
                     },
                 )
                 .unwrap();
@@ -962,6 +972,7 @@ mod tests {
                                 denom: NATIVE_DENOM_2.to_string(),
                             },
                         ],
+                        pair_type: None,
                     },
                 )
                 .unwrap();
@@ -1087,16 +1098,14 @@ mod tests {
                     lp_token_symbol: "MSTR-AURA".to_string(),
                     lp_token_decimals: None,
                 },
+                pair_type: None,
             };
 
             let response = app.execute_contract(
                 Addr::unchecked(ADMIN.to_string()),
                 Addr::unchecked(factory_contract.clone()),
                 &create_pair_msg,
-                &[Coin {
-                    amount: Uint128::from(500000u128),
-                    denom: NATIVE_DENOM.to_string(),
-                }],
+                &[],
             );
 
             assert!(response.is_ok());
@@ -1115,6 +1124,7 @@ mod tests {
                                 denom: NATIVE_DENOM.to_string(),
                             },
                         ],
+                        pair_type: None,
                     },
                 )
                 .unwrap();
@@ -1141,6 +1151,7 @@ mod tests {
                     },
                     // Verify the default commission rate is 0.3%
                     commission_rate: Decimal256::from_str("0.03").unwrap(),
+                    pair_type: PairType::Xyk,
                 }
             );
 
@@ -1184,6 +1195,7 @@ mod tests {
                 ],
                 slippage_tolerance: None,
                 receiver: None,
+                deadline: None,
             };
 
             let response = app.execute_contract(
@@ -1235,9 +1247,13 @@ mod tests {
                     ask_asset_info: AssetInfo::NativeToken {
                         denom: NATIVE_DENOM.to_string(),
                     },
+                    max_spread: None,
+                    belief_price: None,
+                    pair_type: None,
                 }],
                 minimum_receive: Some(Uint128::from(46937u128)),
                 to: None,
+                deadline: None,
             };
 
             // Send 0.49 MSTR to Router Contract
@@ -1267,10 +1283,9 @@ mod tests {
         // USER_1 Successfully Add Liquidity: 2 MSTR - 2 USDC Token for initial liquidity
         // USER_1 Successfully Add Liquidity: 340_282_366_918 MSTR - 340_282_366_918 USDC Token
         // USER_1 Withdraw Liquidity: 340_282_366_918 MSTR - 340_282_366_918 USDC Token
-        // USER_1 Fail to Add Liquidity: 340_282_366_921 MSTR - 340_282_366_921 USDC Token with panic:
-        // "arithmetic operation overflow"
+        // USER_1 Fail to Add Liquidity: 340_282_366_921 MSTR - 340_282_366_921 USDC Token with error:
+        // "The product of the pools overflows"
         #[test]
-        #[should_panic(expected = "arithmetic operation overflow")]
         fn test_provide_liquidity_exceed_max_value() {
             // get integration test app and contracts
             let (mut app, contracts) = instantiate_contracts();
@@ -1354,16 +1369,14 @@ mod tests {
                     lp_token_symbol: "MSTR-USDC".to_string(),
                     lp_token_decimals: None,
                 },
+                pair_type: None,
             };
 
             let response = app.execute_contract(
                 Addr::unchecked(ADMIN.to_string()),
                 Addr::unchecked(factory_contract),
                 &create_pair_msg,
-                &[Coin {
-                    amount: Uint128::from(1u128),
-                    denom: NATIVE_DENOM.to_string(),
-                }],
+                &[],
             );
 
             assert!(response.is_ok());
@@ -1428,6 +1441,7 @@ mod tests {
                 ],
                 slippage_tolerance: None,
                 receiver: None,
+                deadline: None,
             };
 
             let response = app.execute_contract(
@@ -1460,6 +1474,7 @@ mod tests {
                 ],
                 slippage_tolerance: None,
                 receiver: None,
+                deadline: None,
             };
 
             let response = app.execute_contract(
@@ -1492,7 +1507,11 @@ mod tests {
             );
 
             // Withdraw Liquidity msg
-            let msg = Cw20HookMsg::WithdrawLiquidity {};
+            let msg = Cw20HookMsg::WithdrawLiquidity {
+                min_assets: None,
+                receiver: None,
+                deadline: None,
+            };
 
             // Send 340282366919999999999999999999 LP Token to Pair Contract
             let send_msg: Cw20ExecuteMsg = Cw20ExecuteMsg::Send {
@@ -1532,9 +1551,10 @@ mod tests {
                 ],
                 slippage_tolerance: None,
                 receiver: None,
+                deadline: None,
             };
 
-            let _response = app.execute_contract(
+            let response = app.execute_contract(
                 Addr::unchecked(USER_1.to_string()),
                 Addr::unchecked("contract5".to_string()),
                 &provide_liquidity_msg,
@@ -1543,6 +1563,949 @@ mod tests {
                     denom: NATIVE_DENOM_2.to_string(),
                 }],
             );
+
+            assert_eq!(
+                response.unwrap_err().root_cause().to_string(),
+                ContractError::PoolOverflow {}.to_string()
+            );
+        }
+    }
+
+    mod swap_routes {
+        use cosmwasm_std::Decimal;
+        use cw_multi_test::{App, Executor};
+        use haloswap::{
+            asset::{Asset, LPTokenInfo},
+            error::RouterError,
+            pair::ExecuteMsg,
+            router::{
+                ExecuteMsg as RouterExecuteMsg, FindBestRouteResponse, QueryMsg as RouterQueryMsg,
+                SimulateSplitSwapResponse, SimulateSwapOperationsResponse, SplitRoute,
+                SwapOperation,
+            },
+        };
+
+        use super::*;
+
+        const NATIVE_DENOM_3: &str = "uhalo";
+
+        fn native(denom: &str) -> AssetInfo {
+            AssetInfo::NativeToken {
+                denom: denom.to_string(),
+            }
+        }
+
+        fn native_balance(app: &App, address: &str, denom: &str) -> Uint128 {
+            app.wrap().query_balance(address, denom).unwrap().amount
+        }
+
+        // USER_1 gets 1000 of each native token, the owner of the factory registers their
+        // decimals and creates the pairs of the type, then USER_1 provides their liquidity
+        fn create_native_pairs(
+            app: &mut App,
+            factory_contract: &str,
+            pools: &[[Coin; 2]],
+            pair_type: PairType,
+        ) {
+            let mut denoms: Vec<String> = [NATIVE_DENOM, NATIVE_DENOM_2, NATIVE_DENOM_3]
+                .iter()
+                .map(|denom| denom.to_string())
+                .chain(pools.iter().flatten().map(|coin| coin.denom.clone()))
+                .collect();
+            denoms.sort();
+            denoms.dedup();
+
+            // the factory must hold the native tokens to register their decimals
+            for (address, amount) in [
+                (USER_1, MOCK_1000_NATIVE_TOKEN_AMOUNT),
+                (factory_contract, 1u128),
+            ] {
+                app.sudo(cw_multi_test::SudoMsg::Bank(
+                    cw_multi_test::BankSudo::Mint {
+                        to_address: address.to_string(),
+                        amount: denoms
+                            .iter()
+                            .map(|denom| Coin::new(amount, denom))
+                            .collect(),
+                    },
+                ))
+                .unwrap();
+            }
+
+            for denom in denoms.iter() {
+                app.execute_contract(
+                    Addr::unchecked(ADMIN),
+                    Addr::unchecked(factory_contract),
+                    &FactoryExecuteMsg::AddNativeTokenDecimals {
+                        denom: denom.to_string(),
+                        decimals: 6u8,
+                    },
+                    &[],
+                )
+                .unwrap();
+            }
+
+            for pool in pools {
+                let asset_infos = [native(&pool[0].denom), native(&pool[1].denom)];
+                app.execute_contract(
+                    Addr::unchecked(ADMIN),
+                    Addr::unchecked(factory_contract),
+                    &FactoryExecuteMsg::CreatePair {
+                        asset_infos: asset_infos.clone(),
+                        requirements: CreatePairRequirements {
+                            whitelist: vec![Addr::unchecked(USER_1)],
+                            first_asset_minimum: Uint128::zero(),
+                            second_asset_minimum: Uint128::zero(),
+                        },
+                        commission_rate: None,
+                        lp_token_info: LPTokenInfo {
+                            lp_token_name: format!("{}-{}", pool[0].denom, pool[1].denom),
+                            lp_token_symbol: "NATIVE-LP".to_string(),
+                            lp_token_decimals: None,
+                        },
+                        pair_type: Some(pair_type.clone()),
+                    },
+                    &[],
+                )
+                .unwrap();
+
+                let pair_info: PairInfo = app
+                    .wrap()
+                    .query_wasm_smart(
+                        factory_contract,
+                        &FactoryQueryMsg::Pair {
+                            asset_infos: asset_infos.clone(),
+                            pair_type: Some(pair_type.clone()),
+                        },
+                    )
+                    .unwrap();
+
+                let mut funds = pool.to_vec();
+                funds.sort_by(|a, b| a.denom.cmp(&b.denom));
+                app.execute_contract(
+                    Addr::unchecked(USER_1),
+                    Addr::unchecked(pair_info.contract_addr),
+                    &ExecuteMsg::ProvideLiquidity {
+                        assets: [
+                            Asset {
+                                info: asset_infos[0].clone(),
+                                amount: pool[0].amount,
+                            },
+                            Asset {
+                                info: asset_infos[1].clone(),
+                                amount: pool[1].amount,
+                            },
+                        ],
+                        slippage_tolerance: None,
+                        receiver: None,
+                        deadline: None,
+                    },
+                    &funds,
+                )
+                .unwrap();
+            }
+        }
+
+        // Create Pairs: AURA - TAURA with a shallow pool, AURA - HALO and HALO - TAURA with deep pools
+        // The best route from AURA to TAURA goes through HALO, unless a single hop is allowed
+        // USER_1 Swap: AURA -> TAURA through the best route
+        #[test]
+        fn find_best_route_and_swap_auto() {
+            // get integration test app and contracts
+            let (mut app, contracts) = instantiate_contracts();
+            // Get factory contract
+            let factory_contract = contracts[0].contract_addr.clone();
+            // Get router contract
+            let router_contract = contracts[1].contract_addr.clone();
+
+            create_native_pairs(
+                &mut app,
+                &factory_contract,
+                &[
+                    [
+                        Coin::new(1_000_000u128, NATIVE_DENOM),
+                        Coin::new(1_000_000u128, NATIVE_DENOM_2),
+                    ],
+                    [
+                        Coin::new(100_000_000u128, NATIVE_DENOM),
+                        Coin::new(100_000_000u128, NATIVE_DENOM_3),
+                    ],
+                    [
+                        Coin::new(100_000_000u128, NATIVE_DENOM_3),
+                        Coin::new(100_000_000u128, NATIVE_DENOM_2),
+                    ],
+                ],
+                PairType::Xyk,
+            );
+
+            let offer_asset = Asset {
+                info: native(NATIVE_DENOM),
+                amount: Uint128::from(100_000u128),
+            };
+            let find_best_route = |app: &App, max_hops: Option<u32>| {
+                app.wrap().query_wasm_smart::<FindBestRouteResponse>(
+                    router_contract.clone(),
+                    &RouterQueryMsg::FindBestRoute {
+                        offer_asset: offer_asset.clone(),
+                        ask_asset_info: native(NATIVE_DENOM_2),
+                        max_hops,
+                    },
+                )
+            };
+
+            // the deep pools return more than the shallow one
+            let route = find_best_route(&app, None).unwrap();
+            assert_eq!(
+                route.operations,
+                vec![
+                    SwapOperation::HaloSwap {
+                        offer_asset_info: native(NATIVE_DENOM),
+                        ask_asset_info: native(NATIVE_DENOM_3),
+                        max_spread: None,
+                        belief_price: None,
+                        pair_type: Some(PairType::Xyk),
+                    },
+                    SwapOperation::HaloSwap {
+                        offer_asset_info: native(NATIVE_DENOM_3),
+                        ask_asset_info: native(NATIVE_DENOM_2),
+                        max_spread: None,
+                        belief_price: None,
+                        pair_type: Some(PairType::Xyk),
+                    },
+                ]
+            );
+            let simulation: SimulateSwapOperationsResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    router_contract.clone(),
+                    &RouterQueryMsg::SimulateSwapOperations {
+                        offer_amount: offer_asset.amount,
+                        operations: route.operations.clone(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(route.amount, simulation.amount);
+
+            // the direct route is the only one with a single hop
+            let direct_route = find_best_route(&app, Some(1)).unwrap();
+            assert_eq!(
+                direct_route.operations,
+                vec![SwapOperation::HaloSwap {
+                    offer_asset_info: native(NATIVE_DENOM),
+                    ask_asset_info: native(NATIVE_DENOM_2),
+                    max_spread: None,
+                    belief_price: None,
+                    pair_type: Some(PairType::Xyk),
+                }]
+            );
+            assert!(direct_route.amount < route.amount);
+
+            // no route leads to an asset without pair
+            let res = app.wrap().query_wasm_smart::<FindBestRouteResponse>(
+                router_contract.clone(),
+                &RouterQueryMsg::FindBestRoute {
+                    offer_asset: offer_asset.clone(),
+                    ask_asset_info: native("uatom"),
+                    max_hops: None,
+                },
+            );
+            assert!(res
+                .unwrap_err()
+                .to_string()
+                .contains(&RouterError::NoRouteFound {}.to_string()));
+
+            // a single native token must be sent to swap automatically
+            let swap_auto_msg = RouterExecuteMsg::SwapAuto {
+                ask_asset_info: native(NATIVE_DENOM_2),
+                minimum_receive: Some(route.amount),
+                to: None,
+                deadline: None,
+            };
+            let response = app.execute_contract(
+                Addr::unchecked(USER_1),
+                Addr::unchecked(router_contract.clone()),
+                &swap_auto_msg,
+                &[],
+            );
+            assert_eq!(
+                response.unwrap_err().root_cause().to_string(),
+                RouterError::InvalidSwapFunds {}.to_string()
+            );
+
+            let balance_before = native_balance(&app, USER_1, NATIVE_DENOM_2);
+            app.execute_contract(
+                Addr::unchecked(USER_1),
+                Addr::unchecked(router_contract),
+                &swap_auto_msg,
+                &[Coin::new(offer_asset.amount.u128(), NATIVE_DENOM)],
+            )
+            .unwrap();
+            assert_eq!(
+                native_balance(&app, USER_1, NATIVE_DENOM_2),
+                balance_before + route.amount
+            );
+        }
+
+        // Create Pairs: AURA - TAURA with a shallow constant product pool and a deep stable pool
+        // The best route goes through the stable pair, which the swap operations reach
+        // USER_1 Swap: AURA -> TAURA through the best route
+        #[test]
+        fn find_best_route_through_stable_pair() {
+            // get integration test app and contracts
+            let (mut app, contracts) = instantiate_contracts();
+            // Get factory contract
+            let factory_contract = contracts[0].contract_addr.clone();
+            // Get router contract
+            let router_contract = contracts[1].contract_addr.clone();
+
+            create_native_pairs(
+                &mut app,
+                &factory_contract,
+                &[[
+                    Coin::new(1_000_000u128, NATIVE_DENOM),
+                    Coin::new(1_000_000u128, NATIVE_DENOM_2),
+                ]],
+                PairType::Xyk,
+            );
+            create_native_pairs(
+                &mut app,
+                &factory_contract,
+                &[[
+                    Coin::new(100_000_000u128, NATIVE_DENOM),
+                    Coin::new(100_000_000u128, NATIVE_DENOM_2),
+                ]],
+                PairType::Stable { amp: 100 },
+            );
+
+            let offer_asset = Asset {
+                info: native(NATIVE_DENOM),
+                amount: Uint128::from(100_000u128),
+            };
+            let route: FindBestRouteResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    router_contract.clone(),
+                    &RouterQueryMsg::FindBestRoute {
+                        offer_asset: offer_asset.clone(),
+                        ask_asset_info: native(NATIVE_DENOM_2),
+                        max_hops: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(
+                route.operations,
+                vec![SwapOperation::HaloSwap {
+                    offer_asset_info: native(NATIVE_DENOM),
+                    ask_asset_info: native(NATIVE_DENOM_2),
+                    max_spread: None,
+                    belief_price: None,
+                    pair_type: Some(PairType::Stable { amp: 100 }),
+                }]
+            );
+
+            let balance_before = native_balance(&app, USER_1, NATIVE_DENOM_2);
+            app.execute_contract(
+                Addr::unchecked(USER_1),
+                Addr::unchecked(router_contract),
+                &RouterExecuteMsg::ExecuteSwapOperations {
+                    operations: route.operations,
+                    minimum_receive: Some(route.amount),
+                    to: None,
+                    deadline: None,
+                },
+                &[Coin::new(offer_asset.amount.u128(), NATIVE_DENOM)],
+            )
+            .unwrap();
+            assert_eq!(
+                native_balance(&app, USER_1, NATIVE_DENOM_2),
+                balance_before + route.amount
+            );
+        }
+
+        // Create Pairs: AURA with 30 other tokens, then AURA - TAURA
+        // The pairs of AURA are listed on two pages, the route through the last pair is found
+        #[test]
+        fn find_best_route_pages_through_pairs() {
+            // get integration test app and contracts
+            let (mut app, contracts) = instantiate_contracts();
+            // Get factory contract
+            let factory_contract = contracts[0].contract_addr.clone();
+            // Get router contract
+            let router_contract = contracts[1].contract_addr.clone();
+
+            let mut pools: Vec<[Coin; 2]> = (0..30)
+                .map(|i| {
+                    [
+                        Coin::new(1_000_000u128, NATIVE_DENOM),
+                        Coin::new(1_000_000u128, format!("utoken{:02}", i)),
+                    ]
+                })
+                .collect();
+            pools.push([
+                Coin::new(1_000_000u128, NATIVE_DENOM),
+                Coin::new(1_000_000u128, NATIVE_DENOM_2),
+            ]);
+            create_native_pairs(&mut app, &factory_contract, &pools, PairType::Xyk);
+
+            let route: FindBestRouteResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    router_contract,
+                    &RouterQueryMsg::FindBestRoute {
+                        offer_asset: Asset {
+                            info: native(NATIVE_DENOM),
+                            amount: Uint128::from(100_000u128),
+                        },
+                        ask_asset_info: native(NATIVE_DENOM_2),
+                        max_hops: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(
+                route.operations,
+                vec![SwapOperation::HaloSwap {
+                    offer_asset_info: native(NATIVE_DENOM),
+                    ask_asset_info: native(NATIVE_DENOM_2),
+                    max_spread: None,
+                    belief_price: None,
+                    pair_type: Some(PairType::Xyk),
+                }]
+            );
+        }
+
+        // Create Pairs: AURA - TAURA, AURA - HALO and HALO - TAURA with pools of the same size
+        // USER_1 Swap: AURA -> TAURA split across the direct route and the route through HALO
+        #[test]
+        fn split_swap() {
+            // get integration test app and contracts
+            let (mut app, contracts) = instantiate_contracts();
+            // Get factory contract
+            let factory_contract = contracts[0].contract_addr.clone();
+            // Get router contract
+            let router_contract = contracts[1].contract_addr.clone();
+
+            create_native_pairs(
+                &mut app,
+                &factory_contract,
+                &[
+                    [
+                        Coin::new(10_000_000u128, NATIVE_DENOM),
+                        Coin::new(10_000_000u128, NATIVE_DENOM_2),
+                    ],
+                    [
+                        Coin::new(10_000_000u128, NATIVE_DENOM),
+                        Coin::new(10_000_000u128, NATIVE_DENOM_3),
+                    ],
+                    [
+                        Coin::new(10_000_000u128, NATIVE_DENOM_3),
+                        Coin::new(10_000_000u128, NATIVE_DENOM_2),
+                    ],
+                ],
+                PairType::Xyk,
+            );
+
+            let offer_amount = Uint128::from(2_000_000u128);
+            let direct_operations = vec![SwapOperation::HaloSwap {
+                offer_asset_info: native(NATIVE_DENOM),
+                ask_asset_info: native(NATIVE_DENOM_2),
+                max_spread: None,
+                belief_price: None,
+                pair_type: None,
+            }];
+            let halo_operations = vec![
+                SwapOperation::HaloSwap {
+                    offer_asset_info: native(NATIVE_DENOM),
+                    ask_asset_info: native(NATIVE_DENOM_3),
+                    max_spread: None,
+                    belief_price: None,
+                    pair_type: None,
+                },
+                SwapOperation::HaloSwap {
+                    offer_asset_info: native(NATIVE_DENOM_3),
+                    ask_asset_info: native(NATIVE_DENOM_2),
+                    max_spread: None,
+                    belief_price: None,
+                    pair_type: None,
+                },
+            ];
+
+            // the whole amount is offered to the direct route
+            let simulation: SimulateSplitSwapResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    router_contract.clone(),
+                    &RouterQueryMsg::SimulateSplitSwap {
+                        routes: vec![
+                            SplitRoute {
+                                operations: direct_operations.clone(),
+                                offer_amount,
+                            },
+                            SplitRoute {
+                                operations: halo_operations.clone(),
+                                offer_amount: Uint128::zero(),
+                            },
+                        ],
+                    },
+                )
+                .unwrap();
+            let direct_simulation: SimulateSwapOperationsResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    router_contract.clone(),
+                    &RouterQueryMsg::SimulateSwapOperations {
+                        offer_amount,
+                        operations: direct_operations.clone(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(simulation.amount, direct_simulation.amount);
+
+            // the suggested split offers to both routes and returns more
+            assert_eq!(simulation.suggested_routes.len(), 2);
+            assert_eq!(simulation.suggested_routes[0].operations, direct_operations);
+            assert_eq!(simulation.suggested_routes[1].operations, halo_operations);
+            assert!(!simulation.suggested_routes[0].offer_amount.is_zero());
+            assert!(!simulation.suggested_routes[1].offer_amount.is_zero());
+            assert_eq!(
+                simulation.suggested_routes[0].offer_amount
+                    + simulation.suggested_routes[1].offer_amount,
+                offer_amount
+            );
+            assert!(simulation.suggested_amount > simulation.amount);
+
+            // the offer amounts of the routes must add up to the offered amount
+            let response = app.execute_contract(
+                Addr::unchecked(USER_1),
+                Addr::unchecked(router_contract.clone()),
+                &RouterExecuteMsg::ExecuteSplitSwap {
+                    routes: vec![SplitRoute {
+                        operations: direct_operations.clone(),
+                        offer_amount: Uint128::from(1_000_000u128),
+                    }],
+                    minimum_receive: None,
+                    to: None,
+                    deadline: None,
+                },
+                &[Coin::new(offer_amount.u128(), NATIVE_DENOM)],
+            );
+            assert_eq!(
+                response.unwrap_err().root_cause().to_string(),
+                RouterError::InvalidSplitAmount { offer_amount }.to_string()
+            );
+
+            // every route must start from the offered token, the simulation rejects the same routes
+            let res = app.wrap().query_wasm_smart::<SimulateSplitSwapResponse>(
+                router_contract.clone(),
+                &RouterQueryMsg::SimulateSplitSwap {
+                    routes: vec![
+                        SplitRoute {
+                            operations: direct_operations.clone(),
+                            offer_amount: Uint128::from(1_000_000u128),
+                        },
+                        SplitRoute {
+                            operations: halo_operations[1..].to_vec(),
+                            offer_amount: Uint128::from(1_000_000u128),
+                        },
+                    ],
+                },
+            );
+            assert!(res
+                .unwrap_err()
+                .to_string()
+                .contains(&RouterError::InvalidRouteOffer {}.to_string()));
+
+            let response = app.execute_contract(
+                Addr::unchecked(USER_1),
+                Addr::unchecked(router_contract.clone()),
+                &RouterExecuteMsg::ExecuteSplitSwap {
+                    routes: vec![
+                        SplitRoute {
+                            operations: direct_operations,
+                            offer_amount: Uint128::from(1_000_000u128),
+                        },
+                        SplitRoute {
+                            operations: halo_operations[1..].to_vec(),
+                            offer_amount: Uint128::from(1_000_000u128),
+                        },
+                    ],
+                    minimum_receive: None,
+                    to: None,
+                    deadline: None,
+                },
+                &[Coin::new(offer_amount.u128(), NATIVE_DENOM)],
+            );
+            assert_eq!(
+                response.unwrap_err().root_cause().to_string(),
+                RouterError::InvalidRouteOffer {}.to_string()
+            );
+
+            // the suggested split returns the suggested amount
+            let balance_before = native_balance(&app, USER_1, NATIVE_DENOM_2);
+            app.execute_contract(
+                Addr::unchecked(USER_1),
+                Addr::unchecked(router_contract),
+                &RouterExecuteMsg::ExecuteSplitSwap {
+                    routes: simulation.suggested_routes,
+                    minimum_receive: Some(simulation.suggested_amount),
+                    to: None,
+                    deadline: None,
+                },
+                &[Coin::new(offer_amount.u128(), NATIVE_DENOM)],
+            )
+            .unwrap();
+            assert_eq!(
+                native_balance(&app, USER_1, NATIVE_DENOM_2),
+                balance_before + simulation.suggested_amount
+            );
+        }
+
+        // Create Pairs: AURA - HALO with a shallow pool, HALO - TAURA with a deep pool
+        // USER_1 Swap: AURA -> HALO -> TAURA with a max spread on a single hop
+        #[test]
+        fn swap_operations_with_max_spread() {
+            // get integration test app and contracts
+            let (mut app, contracts) = instantiate_contracts();
+            // Get factory contract
+            let factory_contract = contracts[0].contract_addr.clone();
+            // Get router contract
+            let router_contract = contracts[1].contract_addr.clone();
+
+            create_native_pairs(
+                &mut app,
+                &factory_contract,
+                &[
+                    [
+                        Coin::new(1_000_000u128, NATIVE_DENOM),
+                        Coin::new(1_000_000u128, NATIVE_DENOM_3),
+                    ],
+                    [
+                        Coin::new(100_000_000u128, NATIVE_DENOM_3),
+                        Coin::new(100_000_000u128, NATIVE_DENOM_2),
+                    ],
+                ],
+                PairType::Xyk,
+            );
+
+            let swap_operations_msg =
+                |first_max_spread, second_max_spread| RouterExecuteMsg::ExecuteSwapOperations {
+                    operations: vec![
+                        SwapOperation::HaloSwap {
+                            offer_asset_info: native(NATIVE_DENOM),
+                            ask_asset_info: native(NATIVE_DENOM_3),
+                            max_spread: first_max_spread,
+                            belief_price: None,
+                            pair_type: None,
+                        },
+                        SwapOperation::HaloSwap {
+                            offer_asset_info: native(NATIVE_DENOM_3),
+                            ask_asset_info: native(NATIVE_DENOM_2),
+                            max_spread: second_max_spread,
+                            belief_price: None,
+                            pair_type: None,
+                        },
+                    ],
+                    minimum_receive: None,
+                    to: None,
+                    deadline: None,
+                };
+
+            // the swap in the shallow pool exceeds the max spread of the first hop
+            let response = app.execute_contract(
+                Addr::unchecked(USER_1),
+                Addr::unchecked(router_contract.clone()),
+                &swap_operations_msg(Some(Decimal::percent(1)), None),
+                &[Coin::new(100_000u128, NATIVE_DENOM)],
+            );
+            assert_eq!(
+                response.unwrap_err().root_cause().to_string(),
+                "Max spread assertion"
+            );
+
+            // the swap in the deep pool is within the max spread of the second hop
+            let balance_before = native_balance(&app, USER_1, NATIVE_DENOM_2);
+            app.execute_contract(
+                Addr::unchecked(USER_1),
+                Addr::unchecked(router_contract),
+                &swap_operations_msg(None, Some(Decimal::percent(1))),
+                &[Coin::new(100_000u128, NATIVE_DENOM)],
+            )
+            .unwrap();
+            assert!(native_balance(&app, USER_1, NATIVE_DENOM_2) > balance_before);
+        }
+
+        // Create Pairs: AURA - HALO and HALO - TAURA
+        // USER_1 Swap: AURA -> HALO -> TAURA for an exact amount of TAURA
+        #[test]
+        fn swap_operations_exact_out() {
+            // get integration test app and contracts
+            let (mut app, contracts) = instantiate_contracts();
+            // Get factory contract
+            let factory_contract = contracts[0].contract_addr.clone();
+            // Get router contract
+            let router_contract = contracts[1].contract_addr.clone();
+
+            create_native_pairs(
+                &mut app,
+                &factory_contract,
+                &[
+                    [
+                        Coin::new(100_000_000u128, NATIVE_DENOM),
+                        Coin::new(100_000_000u128, NATIVE_DENOM_3),
+                    ],
+                    [
+                        Coin::new(100_000_000u128, NATIVE_DENOM_3),
+                        Coin::new(100_000_000u128, NATIVE_DENOM_2),
+                    ],
+                ],
+                PairType::Xyk,
+            );
+
+            let operations = vec![
+                SwapOperation::HaloSwap {
+                    offer_asset_info: native(NATIVE_DENOM),
+                    ask_asset_info: native(NATIVE_DENOM_3),
+                    max_spread: None,
+                    belief_price: None,
+                    pair_type: None,
+                },
+                SwapOperation::HaloSwap {
+                    offer_asset_info: native(NATIVE_DENOM_3),
+                    ask_asset_info: native(NATIVE_DENOM_2),
+                    max_spread: None,
+                    belief_price: None,
+                    pair_type: None,
+                },
+            ];
+            let ask_amount = Uint128::from(50_000u128);
+            let simulation: SimulateSwapOperationsResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    router_contract.clone(),
+                    &RouterQueryMsg::ReverseSimulateSwapOperations {
+                        ask_amount,
+                        operations: operations.clone(),
+                    },
+                )
+                .unwrap();
+            let offer_amount = simulation.amount;
+
+            // the offer amount exceeds the max offer amount
+            let response = app.execute_contract(
+                Addr::unchecked(USER_1),
+                Addr::unchecked(router_contract.clone()),
+                &RouterExecuteMsg::ExecuteSwapOperationsExactOut {
+                    operations: operations.clone(),
+                    ask_amount,
+                    max_offer_amount: ask_amount,
+                    to: None,
+                    deadline: None,
+                },
+                &[Coin::new(100_000u128, NATIVE_DENOM)],
+            );
+            assert_eq!(
+                response.unwrap_err().root_cause().to_string(),
+                RouterError::MaxOfferAmountAssertion {
+                    max_offer_amount: ask_amount,
+                    offer_amount,
+                }
+                .to_string()
+            );
+
+            // the offer amount exceeds the sent amount
+            let response = app.execute_contract(
+                Addr::unchecked(USER_1),
+                Addr::unchecked(router_contract.clone()),
+                &RouterExecuteMsg::ExecuteSwapOperationsExactOut {
+                    operations: operations.clone(),
+                    ask_amount,
+                    max_offer_amount: Uint128::from(100_000u128),
+                    to: None,
+                    deadline: None,
+                },
+                &[Coin::new(ask_amount.u128(), NATIVE_DENOM)],
+            );
+            assert_eq!(
+                response.unwrap_err().root_cause().to_string(),
+                RouterError::MaxOfferAmountAssertion {
+                    max_offer_amount: ask_amount,
+                    offer_amount,
+                }
+                .to_string()
+            );
+
+            // ADMIN receives exactly the ask amount, USER_1 gets the unspent offer amount and
+            // the surplus of the hops back, the router keeps nothing
+            let offer_balance_before = native_balance(&app, USER_1, NATIVE_DENOM);
+            let ask_balance_before = native_balance(&app, ADMIN, NATIVE_DENOM_2);
+            app.execute_contract(
+                Addr::unchecked(USER_1),
+                Addr::unchecked(router_contract.clone()),
+                &RouterExecuteMsg::ExecuteSwapOperationsExactOut {
+                    operations,
+                    ask_amount,
+                    max_offer_amount: Uint128::from(100_000u128),
+                    to: Some(ADMIN.to_string()),
+                    deadline: None,
+                },
+                &[Coin::new(100_000u128, NATIVE_DENOM)],
+            )
+            .unwrap();
+            assert_eq!(
+                native_balance(&app, ADMIN, NATIVE_DENOM_2),
+                ask_balance_before + ask_amount
+            );
+            assert_eq!(
+                native_balance(&app, USER_1, NATIVE_DENOM),
+                offer_balance_before - offer_amount
+            );
+            for denom in [NATIVE_DENOM, NATIVE_DENOM_2, NATIVE_DENOM_3] {
+                assert!(native_balance(&app, &router_contract, denom).is_zero());
+            }
+        }
+
+        // Create Pairs: AURA - HALO and HALO - TAURA
+        // The router holds HALO and TAURA sent by mistake
+        // USER_1 Swap: AURA -> HALO -> TAURA, the balance of the router is not swapped
+        // ADMIN Sweep: HALO held by the router
+        #[test]
+        fn swap_operations_keep_router_balance() {
+            // get integration test app and contracts
+            let (mut app, contracts) = instantiate_contracts();
+            // Get factory contract
+            let factory_contract = contracts[0].contract_addr.clone();
+            // Get router contract
+            let router_contract = contracts[1].contract_addr.clone();
+
+            create_native_pairs(
+                &mut app,
+                &factory_contract,
+                &[
+                    [
+                        Coin::new(100_000_000u128, NATIVE_DENOM),
+                        Coin::new(100_000_000u128, NATIVE_DENOM_3),
+                    ],
+                    [
+                        Coin::new(100_000_000u128, NATIVE_DENOM_3),
+                        Coin::new(100_000_000u128, NATIVE_DENOM_2),
+                    ],
+                ],
+                PairType::Xyk,
+            );
+            app.sudo(cw_multi_test::SudoMsg::Bank(
+                cw_multi_test::BankSudo::Mint {
+                    to_address: router_contract.clone(),
+                    amount: vec![
+                        Coin::new(1_000u128, NATIVE_DENOM_3),
+                        Coin::new(500u128, NATIVE_DENOM_2),
+                    ],
+                },
+            ))
+            .unwrap();
+
+            let operations = vec![
+                SwapOperation::HaloSwap {
+                    offer_asset_info: native(NATIVE_DENOM),
+                    ask_asset_info: native(NATIVE_DENOM_3),
+                    max_spread: None,
+                    belief_price: None,
+                    pair_type: None,
+                },
+                SwapOperation::HaloSwap {
+                    offer_asset_info: native(NATIVE_DENOM_3),
+                    ask_asset_info: native(NATIVE_DENOM_2),
+                    max_spread: None,
+                    belief_price: None,
+                    pair_type: None,
+                },
+            ];
+            let offer_amount = Uint128::from(100_000u128);
+            let simulation: SimulateSwapOperationsResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    router_contract.clone(),
+                    &RouterQueryMsg::SimulateSwapOperations {
+                        offer_amount,
+                        operations: operations.clone(),
+                    },
+                )
+                .unwrap();
+
+            // the minimum receive is asserted on the return amount of the last hop
+            let response = app.execute_contract(
+                Addr::unchecked(USER_1),
+                Addr::unchecked(router_contract.clone()),
+                &RouterExecuteMsg::ExecuteSwapOperations {
+                    operations: operations.clone(),
+                    minimum_receive: Some(simulation.amount + Uint128::one()),
+                    to: None,
+                    deadline: None,
+                },
+                &[Coin::new(offer_amount.u128(), NATIVE_DENOM)],
+            );
+            assert_eq!(
+                response.unwrap_err().root_cause().to_string(),
+                RouterError::MinimumReceiveAssertion {
+                    minimum_receive: simulation.amount + Uint128::one(),
+                    swap_amount: simulation.amount,
+                }
+                .to_string()
+            );
+
+            let balance_before = native_balance(&app, USER_1, NATIVE_DENOM_2);
+            app.execute_contract(
+                Addr::unchecked(USER_1),
+                Addr::unchecked(router_contract.clone()),
+                &RouterExecuteMsg::ExecuteSwapOperations {
+                    operations,
+                    minimum_receive: Some(simulation.amount),
+                    to: None,
+                    deadline: None,
+                },
+                &[Coin::new(offer_amount.u128(), NATIVE_DENOM)],
+            )
+            .unwrap();
+            assert_eq!(
+                native_balance(&app, USER_1, NATIVE_DENOM_2),
+                balance_before + simulation.amount
+            );
+            assert_eq!(
+                native_balance(&app, &router_contract, NATIVE_DENOM_3),
+                Uint128::from(1_000u128)
+            );
+            assert_eq!(
+                native_balance(&app, &router_contract, NATIVE_DENOM_2),
+                Uint128::from(500u128)
+            );
+
+            // only the owner can sweep the balance of the router
+            let sweep_msg = RouterExecuteMsg::Sweep {
+                asset_info: native(NATIVE_DENOM_3),
+                to: None,
+            };
+            let response = app.execute_contract(
+                Addr::unchecked(USER_1),
+                Addr::unchecked(router_contract.clone()),
+                &sweep_msg,
+                &[],
+            );
+            assert_eq!(
+                response.unwrap_err().root_cause().to_string(),
+                RouterError::Unauthorized {}.to_string()
+            );
+
+            let balance_before = native_balance(&app, ADMIN, NATIVE_DENOM_3);
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                Addr::unchecked(router_contract.clone()),
+                &sweep_msg,
+                &[],
+            )
+            .unwrap();
+            assert_eq!(
+                native_balance(&app, ADMIN, NATIVE_DENOM_3),
+                balance_before + Uint128::from(1_000u128)
+            );
+            assert!(native_balance(&app, &router_contract, NATIVE_DENOM_3).is_zero());
         }
     }
 }
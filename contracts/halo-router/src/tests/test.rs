@@ -1,21 +1,24 @@
 use std::str::FromStr;
 
 use bignumber::Decimal256;
-use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    coin, from_binary, to_binary, Addr, Coin, CosmosMsg, StdError, SubMsg, Uint128, WasmMsg,
+    attr, coin, from_binary, to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, Event, OwnedDeps,
+    Reply, SubMsg, SubMsgResponse, SubMsgResult, Uint128, WasmMsg,
 };
 
 use crate::assert::assert_operations;
-use crate::contract::{execute, instantiate, query};
-use crate::operations::asset_into_swap_msg;
-use haloswap::mock_querier::mock_dependencies;
+use crate::contract::{execute, instantiate, migrate, query, reply};
+use crate::operations::{asset_into_swap_msg, SWAP_REPLY_ID};
+use crate::state::{LegacyConfig, CONFIG, LEGACY_CONFIG, SWAP_STATE};
+use haloswap::mock_querier::{mock_dependencies, WasmMockQuerier};
 
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
-use haloswap::asset::{Asset, AssetInfo, CreatePairRequirements, PairInfo};
+use haloswap::asset::{Asset, AssetInfo, CreatePairRequirements, PairInfo, PairType};
+use haloswap::error::RouterError;
 use haloswap::pair::Cw20HookMsg as PairHookMsg;
 use haloswap::router::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg,
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
     SimulateSwapOperationsResponse, SwapOperation,
 };
 
@@ -25,6 +28,7 @@ fn proper_initialization() {
 
     let msg = InstantiateMsg {
         halo_factory: "halofactory".to_string(),
+        max_spread: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -36,18 +40,79 @@ fn proper_initialization() {
     let config: ConfigResponse =
         from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
     assert_eq!("halofactory", config.halo_factory.as_str());
+    assert_eq!("addr0000", config.owner.as_str());
+}
+
+fn xyk_pair(asset_infos: [AssetInfo; 2], contract_addr: &str) -> PairInfo {
+    PairInfo {
+        asset_infos,
+        contract_addr: contract_addr.to_string(),
+        liquidity_token: "liquidity0000".to_string(),
+        asset_decimals: [6u8, 6u8],
+        requirements: CreatePairRequirements {
+            whitelist: vec![Addr::unchecked("deployer")],
+            first_asset_minimum: Uint128::zero(),
+            second_asset_minimum: Uint128::zero(),
+        },
+        commission_rate: Decimal256::from_str("0.003").unwrap(),
+        pair_type: PairType::Xyk,
+    }
+}
+
+// ukrw -> asset0001 -> uluna -> asset0002
+fn with_route_pairs(deps: &mut OwnedDeps<MockStorage, MockApi, WasmMockQuerier>) {
+    let ukrw = AssetInfo::NativeToken {
+        denom: "ukrw".to_string(),
+    };
+    let uluna = AssetInfo::NativeToken {
+        denom: "uluna".to_string(),
+    };
+    let asset0001 = AssetInfo::Token {
+        contract_addr: "asset0001".to_string(),
+    };
+    let asset0002 = AssetInfo::Token {
+        contract_addr: "asset0002".to_string(),
+    };
+    deps.querier.with_halo_factory(
+        &[
+            (
+                &"ukrwasset0001".to_string(),
+                &xyk_pair([ukrw, asset0001.clone()], "pair0000"),
+            ),
+            (
+                &"asset0001uluna".to_string(),
+                &xyk_pair([asset0001, uluna.clone()], "pair0001"),
+            ),
+            (
+                &"ulunaasset0002".to_string(),
+                &xyk_pair([uluna, asset0002], "pair0002"),
+            ),
+        ],
+        &[("ukrw".to_string(), 6u8), ("uluna".to_string(), 6u8)],
+    );
+}
+
+fn swap_reply_msg(pair_contract: &str, return_amount: u128) -> Reply {
+    Reply {
+        id: SWAP_REPLY_ID,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![Event::new("wasm")
+                .add_attribute("_contract_addr", pair_contract)
+                .add_attribute("action", "swap")
+                .add_attribute("return_amount", return_amount.to_string())],
+            data: None,
+        }),
+    }
 }
 
 #[test]
 fn execute_swap_operations() {
     let mut deps = mock_dependencies(&[]);
-    deps.querier.with_token_balances(&[(
-        &"asset0002".to_string(),
-        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000000u128))],
-    )]);
+    with_route_pairs(&mut deps);
 
     let msg = InstantiateMsg {
         halo_factory: "halofactory".to_string(),
+        max_spread: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -59,14 +124,166 @@ fn execute_swap_operations() {
         operations: vec![],
         minimum_receive: None,
         to: None,
+        deadline: None,
     };
 
+    let info = mock_info("addr0000", &[coin(1000000u128, "ukrw")]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(RouterError::NoOperations {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let operations = vec![
+        SwapOperation::HaloSwap {
+            offer_asset_info: AssetInfo::NativeToken {
+                denom: "ukrw".to_string(),
+            },
+            ask_asset_info: AssetInfo::Token {
+                contract_addr: "asset0001".to_string(),
+            },
+            max_spread: None,
+            belief_price: None,
+            pair_type: None,
+        },
+        SwapOperation::HaloSwap {
+            offer_asset_info: AssetInfo::Token {
+                contract_addr: "asset0001".to_string(),
+            },
+            ask_asset_info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            max_spread: None,
+            belief_price: None,
+            pair_type: None,
+        },
+        SwapOperation::HaloSwap {
+            offer_asset_info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            ask_asset_info: AssetInfo::Token {
+                contract_addr: "asset0002".to_string(),
+            },
+            max_spread: None,
+            belief_price: None,
+            pair_type: None,
+        },
+    ];
+    let msg = ExecuteMsg::ExecuteSwapOperations {
+        operations: operations.clone(),
+        minimum_receive: Some(Uint128::from(1000000u128)),
+        to: None,
+        deadline: None,
+    };
+
+    // the offer asset must be sent
     let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(RouterError::InvalidSwapFunds {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the first operation must swap the sent token
+    let info = mock_info("addr0000", &[coin(1000000u128, "uluna")]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(RouterError::InvalidRouteOffer {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // only the sent amount is swapped by the first operation
+    let info = mock_info("addr0000", &[coin(1000000u128, "ukrw")]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            asset_into_swap_msg(
+                deps.as_ref(),
+                Addr::unchecked("pair0000"),
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "ukrw".to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+                None,
+                None,
+                None,
+            )
+            .unwrap(),
+            SWAP_REPLY_ID,
+        )]
+    );
+
+    // the swap in progress can not be interrupted by another one
+    let info = mock_info("addr0000", &[coin(1000000u128, "ukrw")]);
     let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "must provide operations"),
+        Err(RouterError::SwapInProgress {}) => {}
         _ => panic!("DO NOT ENTER HERE"),
     }
+    // as if the swap was finished
+    SWAP_STATE.remove(deps.as_mut().storage);
+
+    // the received token must be the offer asset of the first operation
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_binary(&Cw20HookMsg::ExecuteSwapOperations {
+            operations: operations[1..].to_vec(),
+            minimum_receive: None,
+            to: Some("addr0002".to_string()),
+            deadline: None,
+        })
+        .unwrap(),
+    });
+
+    let info = mock_info("asset0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(RouterError::InvalidRouteOffer {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("asset0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            asset_into_swap_msg(
+                deps.as_ref(),
+                Addr::unchecked("pair0001"),
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: "asset0001".to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+                None,
+                None,
+                None,
+            )
+            .unwrap(),
+            SWAP_REPLY_ID,
+        )]
+    );
+}
+
+#[test]
+fn execute_swap_operations_reply() {
+    let mut deps = mock_dependencies(&[]);
+    with_route_pairs(&mut deps);
+
+    let msg = InstantiateMsg {
+        halo_factory: "halofactory".to_string(),
+        max_spread: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     let msg = ExecuteMsg::ExecuteSwapOperations {
         operations: vec![
@@ -77,6 +294,9 @@ fn execute_swap_operations() {
                 ask_asset_info: AssetInfo::Token {
                     contract_addr: "asset0001".to_string(),
                 },
+                max_spread: None,
+                belief_price: None,
+                pair_type: None,
             },
             SwapOperation::HaloSwap {
                 offer_asset_info: AssetInfo::Token {
@@ -85,188 +305,303 @@ fn execute_swap_operations() {
                 ask_asset_info: AssetInfo::NativeToken {
                     denom: "uluna".to_string(),
                 },
+                max_spread: None,
+                belief_price: None,
+                pair_type: None,
             },
+        ],
+        minimum_receive: Some(Uint128::from(1000u128)),
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info("addr0000", &[coin(1000000u128, "ukrw")]);
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+
+    // the return amount is read from the swap event of the pair of the hop
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        swap_reply_msg("pair0001", 5000u128),
+    );
+    match res {
+        Err(RouterError::ReturnAmountNotFound {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // only the swap replies are expected
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 2,
+            ..swap_reply_msg("pair0000", 5000u128)
+        },
+    );
+    match res {
+        Err(RouterError::UnknownReplyId { id }) => assert_eq!(id, 2),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the return amount of the first hop is swapped by the last one,
+    // which sends its return amount to the sender
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        swap_reply_msg("pair0000", 5000u128),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            asset_into_swap_msg(
+                deps.as_ref(),
+                Addr::unchecked("pair0001"),
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: "asset0001".to_string(),
+                    },
+                    amount: Uint128::from(5000u128),
+                },
+                None,
+                None,
+                Some("addr0000".to_string()),
+            )
+            .unwrap(),
+            SWAP_REPLY_ID,
+        )]
+    );
+
+    // the return amount of the last hop is less than the minimum receive
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        swap_reply_msg("pair0001", 999u128),
+    );
+    match res {
+        Err(RouterError::MinimumReceiveAssertion {
+            minimum_receive,
+            swap_amount,
+        }) => {
+            assert_eq!(minimum_receive, Uint128::from(1000u128));
+            assert_eq!(swap_amount, Uint128::from(999u128));
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let _res = reply(
+        deps.as_mut(),
+        mock_env(),
+        swap_reply_msg("pair0000", 5000u128),
+    )
+    .unwrap();
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        swap_reply_msg("pair0001", 1000u128),
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "swap_operations"),
+            attr("receiver", "addr0000"),
+            attr("return_amount", "1000"),
+        ]
+    );
+    assert_eq!(SWAP_STATE.may_load(deps.as_ref().storage).unwrap(), None);
+}
+
+#[test]
+fn execute_swap_operations_exact_out_reply() {
+    let mut deps = mock_dependencies(&[]);
+    with_route_pairs(&mut deps);
+
+    let msg = InstantiateMsg {
+        halo_factory: "halofactory".to_string(),
+        max_spread: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::ExecuteSwapOperationsExactOut {
+        operations: vec![
             SwapOperation::HaloSwap {
                 offer_asset_info: AssetInfo::NativeToken {
-                    denom: "uluna".to_string(),
+                    denom: "ukrw".to_string(),
                 },
                 ask_asset_info: AssetInfo::Token {
-                    contract_addr: "asset0002".to_string(),
+                    contract_addr: "asset0001".to_string(),
                 },
+                max_spread: None,
+                belief_price: None,
+                pair_type: None,
+            },
+            SwapOperation::HaloSwap {
+                offer_asset_info: AssetInfo::Token {
+                    contract_addr: "asset0001".to_string(),
+                },
+                ask_asset_info: AssetInfo::NativeToken {
+                    denom: "uluna".to_string(),
+                },
+                max_spread: None,
+                belief_price: None,
+                pair_type: None,
             },
         ],
-        minimum_receive: Some(Uint128::from(1000000u128)),
-        to: None,
+        ask_amount: Uint128::from(1000u128),
+        max_offer_amount: Uint128::from(2000u128),
+        to: Some("addr0001".to_string()),
+        deadline: None,
     };
 
-    let info = mock_info("addr0000", &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-
+    // the first hop swaps its offer amount, returned to the router,
+    // and the unspent offer amount is refunded
+    let info = mock_info("addr0000", &[coin(1500u128, "ukrw")]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
     assert_eq!(
         res.messages,
         vec![
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
-                funds: vec![],
-                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
-                    operation: SwapOperation::HaloSwap {
-                        offer_asset_info: AssetInfo::NativeToken {
+            SubMsg::reply_on_success(
+                asset_into_swap_msg(
+                    deps.as_ref(),
+                    Addr::unchecked("pair0000"),
+                    Asset {
+                        info: AssetInfo::NativeToken {
                             denom: "ukrw".to_string(),
                         },
-                        ask_asset_info: AssetInfo::Token {
-                            contract_addr: "asset0001".to_string(),
-                        },
+                        amount: Uint128::from(1000u128),
                     },
-                    to: None,
-                })
+                    None,
+                    None,
+                    None,
+                )
                 .unwrap(),
+                SWAP_REPLY_ID,
+            ),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![coin(500u128, "ukrw")],
             })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
-                funds: vec![],
-                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
-                    operation: SwapOperation::HaloSwap {
-                        offer_asset_info: AssetInfo::Token {
+        ]
+    );
+
+    // the swap in progress can not be interrupted by another one
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+    match res {
+        Err(RouterError::SwapInProgress {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the next hop swaps its offer amount out of the return amount of the first one,
+    // the surplus is refunded to the sender
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        swap_reply_msg("pair0000", 1003u128),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::reply_on_success(
+                asset_into_swap_msg(
+                    deps.as_ref(),
+                    Addr::unchecked("pair0001"),
+                    Asset {
+                        info: AssetInfo::Token {
                             contract_addr: "asset0001".to_string(),
                         },
-                        ask_asset_info: AssetInfo::NativeToken {
-                            denom: "uluna".to_string(),
-                        },
+                        amount: Uint128::from(1000u128),
                     },
-                    to: None,
-                })
+                    None,
+                    None,
+                    None,
+                )
                 .unwrap(),
-            })),
+                SWAP_REPLY_ID,
+            ),
             SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
-                funds: vec![],
-                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
-                    operation: SwapOperation::HaloSwap {
-                        offer_asset_info: AssetInfo::NativeToken {
-                            denom: "uluna".to_string(),
-                        },
-                        ask_asset_info: AssetInfo::Token {
-                            contract_addr: "asset0002".to_string(),
-                        },
-                    },
-                    to: Some("addr0000".to_string()),
+                contract_addr: "asset0001".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0000".to_string(),
+                    amount: Uint128::from(3u128),
                 })
                 .unwrap(),
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
                 funds: vec![],
-                msg: to_binary(&ExecuteMsg::AssertMinimumReceive {
-                    asset_info: AssetInfo::Token {
-                        contract_addr: "asset0002".to_string(),
-                    },
-                    prev_balance: Uint128::zero(),
-                    minimum_receive: Uint128::from(1000000u128),
-                    receiver: "addr0000".to_string(),
-                })
-                .unwrap(),
             })),
         ]
     );
 
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: "addr0000".to_string(),
-        amount: Uint128::from(1000000u128),
-        msg: to_binary(&Cw20HookMsg::ExecuteSwapOperations {
-            operations: vec![
-                SwapOperation::HaloSwap {
-                    offer_asset_info: AssetInfo::NativeToken {
-                        denom: "ukrw".to_string(),
-                    },
-                    ask_asset_info: AssetInfo::Token {
-                        contract_addr: "asset0001".to_string(),
-                    },
-                },
-                SwapOperation::HaloSwap {
-                    offer_asset_info: AssetInfo::Token {
-                        contract_addr: "asset0001".to_string(),
-                    },
-                    ask_asset_info: AssetInfo::NativeToken {
-                        denom: "uluna".to_string(),
-                    },
-                },
-                SwapOperation::HaloSwap {
-                    offer_asset_info: AssetInfo::NativeToken {
-                        denom: "uluna".to_string(),
-                    },
-                    ask_asset_info: AssetInfo::Token {
-                        contract_addr: "asset0002".to_string(),
-                    },
-                },
-            ],
-            minimum_receive: None,
-            to: Some("addr0002".to_string()),
-        })
-        .unwrap(),
-    });
+    // the return amount of the last hop must cover the ask amount
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        swap_reply_msg("pair0001", 999u128),
+    );
+    match res {
+        Err(RouterError::MinimumReceiveAssertion {
+            minimum_receive,
+            swap_amount,
+        }) => {
+            assert_eq!(minimum_receive, Uint128::from(1000u128));
+            assert_eq!(swap_amount, Uint128::from(999u128));
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    let info = mock_info("asset0000", &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    // the router sends the ask amount out of the return amount of the last hop to the receiver
+    // and refunds the surplus to the sender
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        swap_reply_msg("pair0000", 1000u128),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        swap_reply_msg("pair0001", 1002u128),
+    )
+    .unwrap();
     assert_eq!(
         res.messages,
         vec![
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
-                funds: vec![],
-                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
-                    operation: SwapOperation::HaloSwap {
-                        offer_asset_info: AssetInfo::NativeToken {
-                            denom: "ukrw".to_string(),
-                        },
-                        ask_asset_info: AssetInfo::Token {
-                            contract_addr: "asset0001".to_string(),
-                        },
-                    },
-                    to: None,
-                })
-                .unwrap(),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0001".to_string(),
+                amount: vec![coin(1000u128, "uluna")],
             })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
-                funds: vec![],
-                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
-                    operation: SwapOperation::HaloSwap {
-                        offer_asset_info: AssetInfo::Token {
-                            contract_addr: "asset0001".to_string(),
-                        },
-                        ask_asset_info: AssetInfo::NativeToken {
-                            denom: "uluna".to_string(),
-                        },
-                    },
-                    to: None,
-                })
-                .unwrap(),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![coin(2u128, "uluna")],
             })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
-                funds: vec![],
-                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
-                    operation: SwapOperation::HaloSwap {
-                        offer_asset_info: AssetInfo::NativeToken {
-                            denom: "uluna".to_string(),
-                        },
-                        ask_asset_info: AssetInfo::Token {
-                            contract_addr: "asset0002".to_string(),
-                        },
-                    },
-                    to: Some("addr0002".to_string()),
-                })
-                .unwrap(),
-            }))
         ]
     );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "swap_operations_exact_out"),
+            attr("receiver", "addr0001"),
+            attr("ask_amount", "1000"),
+        ]
+    );
+    assert_eq!(SWAP_STATE.may_load(deps.as_ref().storage).unwrap(), None);
 }
 
 #[test]
-fn execute_swap_operation() {
+fn execute_swap_operations_with_max_spread() {
     let mut deps = mock_dependencies(&[]);
+    with_route_pairs(&mut deps);
+
     let msg = InstantiateMsg {
         halo_factory: "halofactory".to_string(),
+        max_spread: Some(Decimal::percent(1)),
     };
 
     let info = mock_info("addr0000", &[]);
@@ -274,179 +609,85 @@ fn execute_swap_operation() {
     // we can just call .unwrap() to assert this was a success
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    deps.querier.with_halo_factory(
-        &[(
-            &"uusdasset0000".to_string(),
-            &PairInfo {
-                asset_infos: [
-                    AssetInfo::NativeToken {
-                        denom: "uusd".to_string(),
-                    },
-                    AssetInfo::Token {
-                        contract_addr: "asset0000".to_string(),
-                    },
-                ],
-                contract_addr: "pair0000".to_string(),
-                liquidity_token: "liquidity0000".to_string(),
-                asset_decimals: [6u8, 6u8],
-                requirements: CreatePairRequirements {
-                    whitelist: vec![Addr::unchecked("deployer")],
-                    first_asset_minimum: Uint128::zero(),
-                    second_asset_minimum: Uint128::zero(),
-                },
-                commission_rate: Decimal256::from_str("0.003").unwrap(),
-            },
-        )],
-        &[("uusd".to_string(), 6u8)],
-    );
-    deps.querier.with_balance(&[(
-        &MOCK_CONTRACT_ADDR.to_string(),
-        [Coin {
-            amount: Uint128::from(1000000u128),
-            denom: "uusd".to_string(),
-        }]
-        .to_vec(),
-    )]);
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.max_spread, Some(Decimal::percent(1)));
 
-    let msg = ExecuteMsg::ExecuteSwapOperation {
-        operation: SwapOperation::HaloSwap {
-            offer_asset_info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            ask_asset_info: AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
+    let offer_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "ukrw".to_string(),
         },
-        to: None,
+        amount: Uint128::from(1000000u128),
     };
-    let info = mock_info("addr0000", &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
-    match res {
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
-
-    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-    assert_eq!(
-        res.messages,
-        vec![SubMsg::new(
-            asset_into_swap_msg(
-                deps.as_ref(),
-                Addr::unchecked("pair0000"),
-                Asset {
-                    info: AssetInfo::NativeToken {
-                        denom: "uusd".to_string(),
-                    },
-                    amount: Uint128::from(1000000u128)
-                },
-                None,
-                None
-            )
-            .unwrap()
-        )],
-    );
-
-    // optional to address
-    // swap_send
-    let msg = ExecuteMsg::ExecuteSwapOperation {
-        operation: SwapOperation::HaloSwap {
-            offer_asset_info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            ask_asset_info: AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
-        },
-        to: Some("addr0000".to_string()),
+    let swap_msg = |max_spread: Option<Decimal>, belief_price: Option<Decimal>| {
+        SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "pair0000".to_string(),
+                funds: vec![coin(1000000u128, "ukrw")],
+                msg: to_binary(&PairHookMsg::Swap {
+                    offer_asset: offer_asset.clone(),
+                    belief_price,
+                    max_spread,
+                    to: Some("addr0000".to_string()),
+                    deadline: None,
+                })
+                .unwrap(),
+            }),
+            SWAP_REPLY_ID,
+        )
     };
-    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-    assert_eq!(
-        res.messages,
-        vec![SubMsg::new(
-            asset_into_swap_msg(
-                deps.as_ref(),
-                Addr::unchecked("pair0000"),
-                Asset {
-                    info: AssetInfo::NativeToken {
-                        denom: "uusd".to_string(),
-                    },
-                    amount: Uint128::from(1000000u128)
+    let swap_operations_msg = |max_spread: Option<Decimal>, belief_price: Option<Decimal>| {
+        ExecuteMsg::ExecuteSwapOperations {
+            operations: vec![SwapOperation::HaloSwap {
+                offer_asset_info: AssetInfo::NativeToken {
+                    denom: "ukrw".to_string(),
                 },
-                None,
-                Some("addr0000".to_string())
-            )
-            .unwrap()
-        )],
-    );
-    deps.querier.with_halo_factory(
-        &[(
-            &"assetuusd".to_string(),
-            &PairInfo {
-                asset_infos: [
-                    AssetInfo::Token {
-                        contract_addr: "asset".to_string(),
-                    },
-                    AssetInfo::NativeToken {
-                        denom: "uusd".to_string(),
-                    },
-                ],
-                contract_addr: "pair0000".to_string(),
-                liquidity_token: "liquidity0000".to_string(),
-                asset_decimals: [6u8, 6u8],
-                requirements: CreatePairRequirements {
-                    whitelist: vec![Addr::unchecked("deployer")],
-                    first_asset_minimum: Uint128::zero(),
-                    second_asset_minimum: Uint128::zero(),
+                ask_asset_info: AssetInfo::Token {
+                    contract_addr: "asset0001".to_string(),
                 },
-                commission_rate: Decimal256::from_str("0.003").unwrap(),
-            },
-        )],
-        &[("uusd".to_string(), 6u8)],
-    );
-    deps.querier.with_token_balances(&[(
-        &"asset".to_string(),
-        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000000u128))],
-    )]);
-
-    let msg = ExecuteMsg::ExecuteSwapOperation {
-        operation: SwapOperation::HaloSwap {
-            offer_asset_info: AssetInfo::Token {
-                contract_addr: "asset".to_string(),
-            },
-            ask_asset_info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-        },
-        to: Some("addr0000".to_string()),
+                max_spread,
+                belief_price,
+                pair_type: None,
+            }],
+            minimum_receive: None,
+            to: None,
+            deadline: None,
+        }
     };
 
-    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    // the max spread of the router is used when the operation does not set one
+    let info = mock_info("addr0000", &[coin(1000000u128, "ukrw")]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        swap_operations_msg(None, None),
+    )
+    .unwrap();
     assert_eq!(
         res.messages,
-        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "asset".to_string(),
-            funds: vec![],
-            msg: to_binary(&Cw20ExecuteMsg::Send {
-                contract: "pair0000".to_string(),
-                amount: Uint128::from(1000000u128),
-                msg: to_binary(&PairHookMsg::Swap {
-                    offer_asset: Asset {
-                        info: AssetInfo::Token {
-                            contract_addr: "asset".to_string(),
-                        },
-                        amount: Uint128::from(1000000u128),
-                    },
-                    belief_price: None,
-                    max_spread: None,
-                    to: Some("addr0000".to_string()),
-                })
-                .unwrap()
-            })
-            .unwrap()
-        }))]
+        vec![swap_msg(Some(Decimal::percent(1)), None)]
+    );
+    // as if the swap was finished
+    SWAP_STATE.remove(deps.as_mut().storage);
+
+    // the max spread and belief price of the operation are forwarded to the pair
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        swap_operations_msg(
+            Some(Decimal::percent(5)),
+            Some(Decimal::from_str("1.2").unwrap()),
+        ),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![swap_msg(
+            Some(Decimal::percent(5)),
+            Some(Decimal::from_str("1.2").unwrap())
+        )]
     );
 }
 
@@ -456,6 +697,7 @@ fn query_buy_with_routes() {
 
     let msg = InstantiateMsg {
         halo_factory: "halofactory".to_string(),
+        max_spread: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -473,6 +715,9 @@ fn query_buy_with_routes() {
                 ask_asset_info: AssetInfo::Token {
                     contract_addr: "asset0000".to_string(),
                 },
+                max_spread: None,
+                belief_price: None,
+                pair_type: None,
             },
             SwapOperation::HaloSwap {
                 offer_asset_info: AssetInfo::Token {
@@ -481,6 +726,9 @@ fn query_buy_with_routes() {
                 ask_asset_info: AssetInfo::NativeToken {
                     denom: "uluna".to_string(),
                 },
+                max_spread: None,
+                belief_price: None,
+                pair_type: None,
             },
         ],
     };
@@ -507,6 +755,7 @@ fn query_buy_with_routes() {
                         second_asset_minimum: Uint128::zero(),
                     },
                     commission_rate: Decimal256::from_str("0.003").unwrap(),
+                    pair_type: PairType::Xyk,
                 },
             ),
             (
@@ -529,6 +778,7 @@ fn query_buy_with_routes() {
                         second_asset_minimum: Uint128::zero(),
                     },
                     commission_rate: Decimal256::from_str("0.003").unwrap(),
+                    pair_type: PairType::Xyk,
                 },
             ),
         ],
@@ -551,6 +801,7 @@ fn query_reverse_routes_with_from_native() {
 
     let msg = InstantiateMsg {
         halo_factory: "halofactory".to_string(),
+        max_spread: None,
     };
 
     let target_amount = 1000000u128;
@@ -583,6 +834,9 @@ fn query_reverse_routes_with_from_native() {
             ask_asset_info: AssetInfo::Token {
                 contract_addr: "asset0000".to_string(),
             },
+            max_spread: None,
+            belief_price: None,
+            pair_type: None,
         }],
     };
 
@@ -608,6 +862,7 @@ fn query_reverse_routes_with_from_native() {
                         second_asset_minimum: Uint128::zero(),
                     },
                     commission_rate: Decimal256::from_str("0.003").unwrap(),
+                    pair_type: PairType::Xyk,
                 },
             ),
             (
@@ -630,6 +885,7 @@ fn query_reverse_routes_with_from_native() {
                         second_asset_minimum: Uint128::zero(),
                     },
                     commission_rate: Decimal256::from_str("0.003").unwrap(),
+                    pair_type: PairType::Xyk,
                 },
             ),
         ],
@@ -648,45 +904,47 @@ fn query_reverse_routes_with_from_native() {
 
     let offer_amount = res.amount;
 
-    let msg = ExecuteMsg::ExecuteSwapOperation {
-        operation: SwapOperation::HaloSwap {
+    let msg = ExecuteMsg::ExecuteSwapOperations {
+        operations: vec![SwapOperation::HaloSwap {
             offer_asset_info: AssetInfo::NativeToken {
                 denom: "ukrw".to_string(),
             },
             ask_asset_info: AssetInfo::Token {
                 contract_addr: "asset0000".to_string(),
             },
-        },
+            max_spread: None,
+            belief_price: None,
+            pair_type: None,
+        }],
+        minimum_receive: None,
         to: None,
+        deadline: None,
     };
     let info = mock_info("addr0", &[coin(offer_amount.u128(), "ukrw")]);
-    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
-    match res {
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
-
-    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     assert_eq!(
         res.messages,
-        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "pair0000".to_string(),
-            funds: vec![coin(target_amount, "ukrw")],
-            msg: to_binary(&PairHookMsg::Swap {
-                offer_asset: Asset {
-                    info: AssetInfo::NativeToken {
-                        denom: "ukrw".to_string(),
+        vec![SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "pair0000".to_string(),
+                funds: vec![coin(target_amount, "ukrw")],
+                msg: to_binary(&PairHookMsg::Swap {
+                    offer_asset: Asset {
+                        info: AssetInfo::NativeToken {
+                            denom: "ukrw".to_string(),
+                        },
+                        amount: offer_amount,
                     },
-                    amount: offer_amount,
-                },
-                belief_price: None,
-                max_spread: None,
-                to: None,
-            })
-            .unwrap(),
-        })),],
+                    belief_price: None,
+                    max_spread: None,
+                    to: Some("addr0".to_string()),
+                    deadline: None,
+                })
+                .unwrap(),
+            }),
+            SWAP_REPLY_ID,
+        )],
     );
 }
 
@@ -696,6 +954,7 @@ fn query_reverse_routes_with_to_native() {
 
     let msg = InstantiateMsg {
         halo_factory: "halofactory".to_string(),
+        max_spread: None,
     };
 
     let target_amount = 1000000u128;
@@ -725,6 +984,9 @@ fn query_reverse_routes_with_to_native() {
             ask_asset_info: AssetInfo::NativeToken {
                 denom: "ukrw".to_string(),
             },
+            max_spread: None,
+            belief_price: None,
+            pair_type: None,
         }],
     };
 
@@ -750,6 +1012,7 @@ fn query_reverse_routes_with_to_native() {
                         second_asset_minimum: Uint128::zero(),
                     },
                     commission_rate: Decimal256::from_str("0.003").unwrap(),
+                    pair_type: PairType::Xyk,
                 },
             ),
             (
@@ -772,6 +1035,7 @@ fn query_reverse_routes_with_to_native() {
                         second_asset_minimum: Uint128::zero(),
                     },
                     commission_rate: Decimal256::from_str("0.003").unwrap(),
+                    pair_type: PairType::Xyk,
                 },
             ),
         ],
@@ -801,174 +1065,168 @@ fn query_reverse_routes_with_to_native() {
                 ask_asset_info: AssetInfo::NativeToken {
                     denom: "ukrw".to_string(),
                 },
+                max_spread: None,
+                belief_price: None,
+                pair_type: None,
             }],
             minimum_receive: None,
             to: None,
+            deadline: None,
         })
         .unwrap(),
     });
-    let info = mock_info("addr0", &[]);
+    let info = mock_info("asset0000", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     assert_eq!(
         res.messages,
-        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: MOCK_CONTRACT_ADDR.to_string(),
-            funds: vec![],
-            msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
-                operation: SwapOperation::HaloSwap {
-                    offer_asset_info: AssetInfo::Token {
-                        contract_addr: "asset0000".to_string(),
-                    },
-                    ask_asset_info: AssetInfo::NativeToken {
-                        denom: "ukrw".to_string(),
-                    },
-                },
-                to: Some("addr0".to_string()),
-            })
-            .unwrap(),
-        })),],
-    );
-
-    let msg = ExecuteMsg::ExecuteSwapOperation {
-        operation: SwapOperation::HaloSwap {
-            offer_asset_info: AssetInfo::Token {
+        vec![SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: "asset0000".to_string(),
-            },
-            ask_asset_info: AssetInfo::NativeToken {
-                denom: "ukrw".to_string(),
-            },
-        },
-        to: None,
-    };
-
-    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-
-    assert_eq!(
-        res.messages,
-        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "asset0000".to_string(),
-            funds: vec![],
-            msg: to_binary(&Cw20ExecuteMsg::Send {
-                contract: "pair0000".to_string(),
-                amount: Uint128::from(target_amount),
-                msg: to_binary(&PairHookMsg::Swap {
-                    offer_asset: Asset {
-                        info: AssetInfo::Token {
-                            contract_addr: "asset0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: "pair0000".to_string(),
+                    amount: Uint128::from(target_amount),
+                    msg: to_binary(&PairHookMsg::Swap {
+                        offer_asset: Asset {
+                            info: AssetInfo::Token {
+                                contract_addr: "asset0000".to_string(),
+                            },
+                            amount: Uint128::from(target_amount),
                         },
-                        amount: Uint128::from(target_amount),
-                    },
-                    belief_price: None,
-                    max_spread: None,
-                    to: None,
+                        belief_price: None,
+                        max_spread: None,
+                        to: Some("addr0".to_string()),
+                        deadline: None,
+                    })
+                    .unwrap(),
                 })
                 .unwrap(),
-            })
-            .unwrap(),
-        }))],
+            }),
+            SWAP_REPLY_ID,
+        )],
     );
 }
 
 #[test]
-fn assert_minimum_receive_native_token() {
+fn sweep() {
     let mut deps = mock_dependencies(&[]);
     deps.querier.with_balance(&[(
-        &"addr0000".to_string(),
-        [Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(1000000u128),
-        }]
-        .to_vec(),
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![coin(1000u128, "uusd")],
+    )]);
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(2000u128))],
     )]);
 
-    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
-    // success
-    let msg = ExecuteMsg::AssertMinimumReceive {
-        asset_info: AssetInfo::NativeToken {
-            denom: "uusd".to_string(),
-        },
-        prev_balance: Uint128::zero(),
-        minimum_receive: Uint128::from(1000000u128),
-        receiver: "addr0000".to_string(),
+    let msg = InstantiateMsg {
+        halo_factory: "halofactory".to_string(),
+        max_spread: None,
     };
-    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
-    // assertion failed; native token
-    let msg = ExecuteMsg::AssertMinimumReceive {
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // only the owner can sweep the balance of the router
+    let msg = ExecuteMsg::Sweep {
         asset_info: AssetInfo::NativeToken {
             denom: "uusd".to_string(),
         },
-        prev_balance: Uint128::zero(),
-        minimum_receive: Uint128::from(1000001u128),
-        receiver: "addr0000".to_string(),
+        to: None,
     };
-    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        msg.clone(),
+    );
     match res {
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
-            msg,
-            "assertion failed; minimum receive amount: 1000001, swap amount: 1000000"
-        ),
+        Err(RouterError::Unauthorized {}) => {}
         _ => panic!("DO NOT ENTER HERE"),
     }
-}
 
-#[test]
-fn assert_minimum_receive_token() {
-    let mut deps = mock_dependencies(&[]);
-    deps.querier.with_token_balances(&[(
-        &"token0000".to_string(),
-        &[(&"addr0000".to_string(), &Uint128::from(1000000u128))],
-    )]);
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![coin(1000u128, "uusd")],
+        }))]
+    );
 
-    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
-    // success
-    let msg = ExecuteMsg::AssertMinimumReceive {
+    let msg = ExecuteMsg::Sweep {
         asset_info: AssetInfo::Token {
-            contract_addr: "token0000".to_string(),
+            contract_addr: "asset0000".to_string(),
         },
-        prev_balance: Uint128::zero(),
-        minimum_receive: Uint128::from(1000000u128),
-        receiver: "addr0000".to_string(),
+        to: Some("addr0002".to_string()),
     };
-    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "asset0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0002".to_string(),
+                amount: Uint128::from(2000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+}
 
-    // assertion failed; native token
-    let msg = ExecuteMsg::AssertMinimumReceive {
-        asset_info: AssetInfo::Token {
-            contract_addr: "token0000".to_string(),
-        },
-        prev_balance: Uint128::zero(),
-        minimum_receive: Uint128::from(1000001u128),
-        receiver: "addr0000".to_string(),
-    };
-    let res = execute(deps.as_mut(), mock_env(), info, msg);
-    match res {
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
-            msg,
-            "assertion failed; minimum receive amount: 1000001, swap amount: 1000000"
-        ),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+#[test]
+fn migrate_owner() {
+    let mut deps = mock_dependencies(&[]);
 
-    // assertion failed; unauthorized sender
-    let info = mock_info("addr000", &[]);
-    let msg = ExecuteMsg::AssertMinimumReceive {
-        asset_info: AssetInfo::Token {
-            contract_addr: "token0000".to_string(),
+    // a router instantiated before the owner was added
+    let legacy_config = LegacyConfig {
+        halo_factory: deps.as_ref().api.addr_canonicalize("halofactory").unwrap(),
+        max_spread: None,
+    };
+    LEGACY_CONFIG
+        .save(deps.as_mut().storage, &legacy_config)
+        .unwrap();
+    assert!(CONFIG.load(deps.as_ref().storage).is_err());
+
+    migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            owner: Some("addr0000".to_string()),
         },
-        prev_balance: Uint128::zero(),
-        minimum_receive: Uint128::from(1000000u128),
-        receiver: "addr0000".to_string(),
+    )
+    .unwrap();
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.owner, "addr0000");
+    assert_eq!(config.halo_factory, "halofactory");
+
+    // the owner is kept once it is set
+    migrate(deps.as_mut(), mock_env(), MigrateMsg { owner: None }).unwrap();
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.owner, "addr0000");
+}
+
+#[test]
+fn migrate_without_owner() {
+    let mut deps = mock_dependencies(&[]);
+
+    // a router instantiated before the owner was added
+    let legacy_config = LegacyConfig {
+        halo_factory: deps.as_ref().api.addr_canonicalize("halofactory").unwrap(),
+        max_spread: None,
     };
+    LEGACY_CONFIG
+        .save(deps.as_mut().storage, &legacy_config)
+        .unwrap();
 
-    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { owner: None });
     match res {
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
-            msg,
-            "unauthorized: assert_minium_receive only can be called by contract itself"
-        ),
+        Err(RouterError::OwnerRequired {}) => {}
         _ => panic!("DO NOT ENTER HERE"),
     }
 }
@@ -987,6 +1245,9 @@ fn test_invalid_operations() {
             ask_asset_info: AssetInfo::Token {
                 contract_addr: "asset0001".to_string(),
             },
+            max_spread: None,
+            belief_price: None,
+            pair_type: None,
         },
         SwapOperation::HaloSwap {
             offer_asset_info: AssetInfo::Token {
@@ -995,6 +1256,9 @@ fn test_invalid_operations() {
             ask_asset_info: AssetInfo::NativeToken {
                 denom: "uluna".to_string(),
             },
+            max_spread: None,
+            belief_price: None,
+            pair_type: None,
         }
     ])
     .is_ok());
@@ -1008,6 +1272,9 @@ fn test_invalid_operations() {
             ask_asset_info: AssetInfo::Token {
                 contract_addr: "asset0001".to_string(),
             },
+            max_spread: None,
+            belief_price: None,
+            pair_type: None,
         },
         SwapOperation::HaloSwap {
             offer_asset_info: AssetInfo::Token {
@@ -1016,6 +1283,9 @@ fn test_invalid_operations() {
             ask_asset_info: AssetInfo::NativeToken {
                 denom: "uluna".to_string(),
             },
+            max_spread: None,
+            belief_price: None,
+            pair_type: None,
         },
         SwapOperation::HaloSwap {
             offer_asset_info: AssetInfo::NativeToken {
@@ -1024,7 +1294,160 @@ fn test_invalid_operations() {
             ask_asset_info: AssetInfo::Token {
                 contract_addr: "asset0002".to_string(),
             },
+            max_spread: None,
+            belief_price: None,
+            pair_type: None,
         },
     ])
     .is_ok());
+
+    // the second operation does not offer asset0001
+    assert_eq!(
+        assert_operations(&[
+            SwapOperation::HaloSwap {
+                offer_asset_info: AssetInfo::NativeToken {
+                    denom: "ukrw".to_string(),
+                },
+                ask_asset_info: AssetInfo::Token {
+                    contract_addr: "asset0001".to_string(),
+                },
+                max_spread: None,
+                belief_price: None,
+                pair_type: None,
+            },
+            SwapOperation::HaloSwap {
+                offer_asset_info: AssetInfo::NativeToken {
+                    denom: "uluna".to_string(),
+                },
+                ask_asset_info: AssetInfo::Token {
+                    contract_addr: "asset0001".to_string(),
+                },
+                max_spread: None,
+                belief_price: None,
+                pair_type: None,
+            },
+        ]),
+        Err(RouterError::DisconnectedOperations {})
+    );
+}
+
+#[test]
+fn execute_swap_operations_disconnected() {
+    // the router holds uluna, which must not be swapped by an operation of the sender
+    let mut deps = mock_dependencies(&[coin(1000000u128, "uluna")]);
+    with_route_pairs(&mut deps);
+
+    let msg = InstantiateMsg {
+        halo_factory: "halofactory".to_string(),
+        max_spread: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // ukrw -> asset0001, uluna -> asset0001
+    let msg = ExecuteMsg::ExecuteSwapOperations {
+        operations: vec![
+            SwapOperation::HaloSwap {
+                offer_asset_info: AssetInfo::NativeToken {
+                    denom: "ukrw".to_string(),
+                },
+                ask_asset_info: AssetInfo::Token {
+                    contract_addr: "asset0001".to_string(),
+                },
+                max_spread: None,
+                belief_price: None,
+                pair_type: None,
+            },
+            SwapOperation::HaloSwap {
+                offer_asset_info: AssetInfo::NativeToken {
+                    denom: "uluna".to_string(),
+                },
+                ask_asset_info: AssetInfo::Token {
+                    contract_addr: "asset0001".to_string(),
+                },
+                max_spread: None,
+                belief_price: None,
+                pair_type: None,
+            },
+        ],
+        minimum_receive: None,
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info("addr0000", &[coin(1000000u128, "ukrw")]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res, Err(RouterError::DisconnectedOperations {}));
+}
+
+#[test]
+fn execute_swap_operations_with_deadline() {
+    let mut deps = mock_dependencies(&[]);
+    with_route_pairs(&mut deps);
+
+    let msg = InstantiateMsg {
+        halo_factory: "halofactory".to_string(),
+        max_spread: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let operations = vec![SwapOperation::HaloSwap {
+        offer_asset_info: AssetInfo::NativeToken {
+            denom: "ukrw".to_string(),
+        },
+        ask_asset_info: AssetInfo::Token {
+            contract_addr: "asset0001".to_string(),
+        },
+        max_spread: None,
+        belief_price: None,
+        pair_type: None,
+    }];
+    let env = mock_env();
+
+    // the deadline is exceeded
+    let msg = ExecuteMsg::ExecuteSwapOperations {
+        operations: operations.clone(),
+        minimum_receive: None,
+        to: None,
+        deadline: Some(env.block.time.minus_seconds(1)),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(RouterError::DeadlineExceeded {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_binary(&Cw20HookMsg::ExecuteSwapOperations {
+            operations: operations.clone(),
+            minimum_receive: None,
+            to: None,
+            deadline: Some(env.block.time.minus_seconds(1)),
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), env.clone(), mock_info("asset0000", &[]), msg);
+    match res {
+        Err(RouterError::DeadlineExceeded {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the operations can be executed until the deadline
+    let msg = ExecuteMsg::ExecuteSwapOperations {
+        operations,
+        minimum_receive: None,
+        to: None,
+        deadline: Some(env.block.time),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("addr0000", &[coin(1000000u128, "ukrw")]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
 }
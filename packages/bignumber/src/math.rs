@@ -56,6 +56,21 @@ impl Decimal256 {
     pub fn is_zero(&self) -> bool {
         self.0.is_zero()
     }
+
+    /// Returns `self + rhs`, wrapping around at the boundary of the type
+    pub fn wrapping_add(self, rhs: Decimal256) -> Decimal256 {
+        Decimal256(self.0.overflowing_add(rhs.0).0)
+    }
+
+    /// Returns `self - rhs`, wrapping around at the boundary of the type
+    pub fn wrapping_sub(self, rhs: Decimal256) -> Decimal256 {
+        Decimal256(self.0.overflowing_sub(rhs.0).0)
+    }
+
+    /// Returns `self * rhs` for an integer `rhs`, wrapping around at the boundary of the type
+    pub fn wrapping_mul_uint256(self, rhs: Uint256) -> Decimal256 {
+        Decimal256(self.0.overflowing_mul(rhs.0).0)
+    }
 }
 
 impl From<Decimal> for Decimal256 {
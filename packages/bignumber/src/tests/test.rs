@@ -525,4 +525,40 @@ mod tests {
         let right = Uint256::from(0u64);
         assert_eq!(left * right, Uint256::from(0u64));
     }
+
+    #[test]
+    fn decimal_wrapping_arithmetic() {
+        // no wrapping inside the boundary of the type
+        assert_eq!(
+            Decimal256::percent(150).wrapping_add(Decimal256::percent(50)),
+            Decimal256::percent(200)
+        );
+        assert_eq!(
+            Decimal256::percent(150).wrapping_sub(Decimal256::percent(50)),
+            Decimal256::one()
+        );
+        assert_eq!(
+            Decimal256::percent(150).wrapping_mul_uint256(Uint256::from(4u64)),
+            Decimal256::percent(600)
+        );
+
+        // wrap around at the boundary of the type
+        assert_eq!(
+            Decimal256::MAX.wrapping_add(Decimal256(U256::from(2u64))),
+            Decimal256(U256::one())
+        );
+        assert_eq!(
+            Decimal256::zero().wrapping_sub(Decimal256(U256::one())),
+            Decimal256::MAX
+        );
+        assert_eq!(
+            Decimal256::MAX.wrapping_mul_uint256(Uint256::from(2u64)),
+            Decimal256(U256::MAX - U256::one())
+        );
+
+        // the difference survives the wrapping of the accumulated value
+        let last = Decimal256::MAX - Decimal256::percent(50);
+        let current = last.wrapping_add(Decimal256::one());
+        assert_eq!(current.wrapping_sub(last), Decimal256::one());
+    }
 }
@@ -9,8 +9,8 @@ use cosmwasm_std::{
 };
 use cw20::Cw20ExecuteMsg;
 
-/// LP_TOKEN_RESERVED_AMOUNT == 1uLP
-pub const LP_TOKEN_RESERVED_AMOUNT: u128 = 1;
+/// Default amount of LP token locked by the first provision of a pair == 1000uLP
+pub const DEFAULT_MIN_LIQUIDITY: u128 = 1_000;
 
 #[cw_serde]
 pub struct Asset {
@@ -274,6 +274,26 @@ impl AssetInfoRaw {
     }
 }
 
+/// The invariant used by the pair to price the swaps
+#[cw_serde]
+#[derive(Default)]
+pub enum PairType {
+    /// Constant product invariant
+    #[default]
+    Xyk,
+    /// StableSwap invariant with the amplification coefficient
+    Stable { amp: u64 },
+}
+
+impl fmt::Display for PairType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PairType::Xyk => write!(f, "xyk"),
+            PairType::Stable { .. } => write!(f, "stable"),
+        }
+    }
+}
+
 // We define a custom struct for each query response
 #[cw_serde]
 pub struct PairInfo {
@@ -283,6 +303,9 @@ pub struct PairInfo {
     pub asset_decimals: [u8; 2],
     pub requirements: CreatePairRequirements,
     pub commission_rate: Decimal256,
+    // pairs created before the pair types were introduced are constant product pairs
+    #[serde(default)]
+    pub pair_type: PairType,
 }
 
 #[cw_serde]
@@ -293,6 +316,8 @@ pub struct PairInfoRaw {
     pub asset_decimals: [u8; 2],
     pub requirements: CreatePairRequirements,
     pub commission_rate: Decimal256,
+    #[serde(default)]
+    pub pair_type: PairType,
 }
 
 impl PairInfoRaw {
@@ -307,6 +332,7 @@ impl PairInfoRaw {
             asset_decimals: self.asset_decimals,
             requirements: self.requirements.clone(),
             commission_rate: self.commission_rate,
+            pair_type: self.pair_type.clone(),
         })
     }
 
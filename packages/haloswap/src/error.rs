@@ -1,4 +1,5 @@
-use cosmwasm_std::{OverflowError, StdError};
+use cosmwasm_std::{OverflowError, StdError, Uint128};
+use cw_utils::ParseReplyError;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -9,6 +10,9 @@ pub enum ContractError {
     #[error("{0}")]
     OverflowError(#[from] OverflowError),
 
+    #[error("{0}")]
+    ParseReplyError(#[from] ParseReplyError),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
@@ -18,9 +22,256 @@ pub enum ContractError {
     #[error("Max spread assertion")]
     MaxSpreadAssertion {},
 
+    #[error("Max offer amount assertion")]
+    MaxOfferAmountAssertion {},
+
+    #[error("The sent amount must cover the offer amount {offer_amount}")]
+    InsufficientOfferAmount { offer_amount: Uint128 },
+
     #[error("Max slippage assertion")]
     MaxSlippageAssertion {},
 
     #[error("Asset mismatch")]
     AssetMismatch {},
+
+    #[error("Must send exactly one native token, the offer asset of the pair")]
+    InvalidSwapFunds {},
+
+    #[error("Fee collector is not set")]
+    FeeCollectorNotSet {},
+
+    #[error("Deadline exceeded")]
+    DeadlineExceeded {},
+
+    #[error("Paused")]
+    Paused {},
+
+    #[error("Flash swap invariant violated")]
+    FlashInvariantViolated {},
+
+    #[error("Flash swap in progress")]
+    FlashSwapInProgress {},
+
+    #[error("The recipient of a flash swap must not be the pair, its LP token or its assets")]
+    InvalidFlashSwapRecipient {},
+
+    #[error("Flash fee rate must be less than 1")]
+    InvalidFlashFeeRate {},
+
+    #[error("Asset {asset} is not in the pair")]
+    AssetNotInPair { asset: String },
+
+    #[error("Commission rate must be less than 1")]
+    InvalidCommissionRate {},
+
+    #[error("Slippage tolerance must not be greater than 1")]
+    InvalidSlippageTolerance {},
+
+    #[error("The sender is not in the whitelist of the pair")]
+    WhitelistRequired {},
+
+    #[error("The minimum deposit is not satisfied")]
+    MinimumDepositNotSatisfied {},
+
+    #[error("The first provision must mint more than the minimum liquidity")]
+    MinimumLiquidityNotReached {},
+
+    #[error("The product of the pools overflows")]
+    PoolOverflow {},
+
+    #[error("A pending deposit already exists")]
+    PendingDepositExists {},
+
+    #[error("Pending deposit not found")]
+    PendingDepositNotFound {},
+
+    #[error("The pool has no liquidity")]
+    NoLiquidity {},
+
+    #[error("The share of the deposits overflows")]
+    ShareOverflow {},
+
+    #[error("The ask amount exceeds the pool")]
+    AskAmountExceedsPool {},
+
+    #[error("The share exceeds the total share")]
+    ShareExceedsTotalShare {},
+
+    #[error("No flash swap in progress")]
+    FlashSwapNotFound {},
+
+    #[error("The StableSwap pool is empty")]
+    EmptyStablePool {},
+
+    #[error("The StableSwap invariant does not converge")]
+    StableSwapNotConverged {},
+
+    #[error("The pair is not a stable pair")]
+    NotStablePair {},
+
+    #[error("Amp must be between {min_amp} and {max_amp}")]
+    InvalidAmp { min_amp: u64, max_amp: u64 },
+
+    #[error("Amp must be changed over at least {min_time} seconds")]
+    AmpChangeTooFast { min_time: u64 },
+
+    #[error("Amp can not be changed by more than {max_change} times at once")]
+    AmpChangeTooLarge { max_change: u64 },
+
+    #[error("The TWAP window must be greater than zero")]
+    InvalidTwapWindow {},
+
+    #[error("Not enough observations for the window")]
+    NotEnoughObservations {},
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum FactoryError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    ParseReplyError(#[from] ParseReplyError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("The new owner must not be the current owner")]
+    NewOwnerIsCurrentOwner {},
+
+    #[error("The ownership proposal must expire within {max_expires_in} seconds")]
+    InvalidOwnershipProposalExpiry { max_expires_in: u64 },
+
+    #[error("Ownership proposal not found")]
+    OwnershipProposalNotFound {},
+
+    #[error("Ownership proposal expired")]
+    OwnershipProposalExpired {},
+
+    #[error("The pair creation fee must be {fee}")]
+    InvalidPairCreationFee { fee: String },
+
+    #[error("The pair creation fee must not be zero")]
+    ZeroPairCreationFee {},
+
+    #[error("Must not send funds when no pair creation fee is charged")]
+    UnexpectedFunds {},
+
+    #[error("Fee collector is not set")]
+    FeeCollectorNotSet {},
+
+    #[error("The assets of a pair must be different")]
+    IdenticalAssets {},
+
+    #[error("Invalid asset {asset}")]
+    InvalidAsset { asset: String },
+
+    #[error("Must provide at least one pair")]
+    NoPairs {},
+
+    #[error("Pair already exists")]
+    PairAlreadyExists {},
+
+    #[error("Pair is not registered")]
+    PairNotRegistered {},
+
+    #[error("Pair is deprecated")]
+    PairDeprecated {},
+
+    #[error("Pair is not deprecated")]
+    PairNotDeprecated {},
+
+    #[error("Commission rate must be less than 1")]
+    InvalidCommissionRate {},
+
+    #[error("Protocol fee rate must be between 0 and 1")]
+    InvalidProtocolFeeRate {},
+
+    #[error("Flash fee rate must be less than 1")]
+    InvalidFlashFeeRate {},
+
+    #[error("Min liquidity must not be zero")]
+    ZeroMinLiquidity {},
+
+    #[error("The factory needs a balance of the native token to verify it")]
+    NativeTokenBalanceRequired {},
+
+    #[error("Native token decimals are not registered for {denom}")]
+    NativeTokenDecimalsNotFound { denom: String },
+
+    #[error("The effective time must be in the future")]
+    InvalidEffectiveTime {},
+
+    #[error("Pending commission rate not found")]
+    PendingCommissionRateNotFound {},
+
+    #[error("The pending commission rate is not effective yet")]
+    CommissionRateNotEffective {},
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum RouterError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    OverflowError(#[from] OverflowError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Must provide operations")]
+    NoOperations {},
+
+    #[error("The operations must have a single output token")]
+    MultipleOutputTokens {},
+
+    #[error("The operations must offer the token asked by the previous operation")]
+    DisconnectedOperations {},
+
+    #[error(
+        "Minimum receive assertion: minimum receive {minimum_receive}, swap amount {swap_amount}"
+    )]
+    MinimumReceiveAssertion {
+        minimum_receive: Uint128,
+        swap_amount: Uint128,
+    },
+
+    #[error("Deadline exceeded")]
+    DeadlineExceeded {},
+
+    #[error("No route found")]
+    NoRouteFound {},
+
+    #[error("Must send exactly one native token to swap")]
+    InvalidSwapFunds {},
+
+    #[error("Must provide routes")]
+    NoRoutes {},
+
+    #[error("The routes must swap the offered token only")]
+    InvalidRouteOffer {},
+
+    #[error("The offer amounts of the routes must add up to {offer_amount}")]
+    InvalidSplitAmount { offer_amount: Uint128 },
+
+    #[error(
+        "Max offer amount assertion: max offer amount {max_offer_amount}, offer amount {offer_amount}"
+    )]
+    MaxOfferAmountAssertion {
+        max_offer_amount: Uint128,
+        offer_amount: Uint128,
+    },
+
+    #[error("Another swap is in progress")]
+    SwapInProgress {},
+
+    #[error("Unknown reply id {id}")]
+    UnknownReplyId { id: u64 },
+
+    #[error("Return amount not found in the swap reply")]
+    ReturnAmountNotFound {},
+
+    #[error("The owner must be set to migrate a router without owner")]
+    OwnerRequired {},
 }
@@ -1,24 +1,71 @@
 use bignumber::Decimal256;
 use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Timestamp, Uint128};
+use cw20::Cw20ReceiveMsg;
+use std::fmt;
 
-use crate::asset::{AssetInfo, CreatePairRequirements, LPTokenInfo, PairInfo};
+use crate::asset::{Asset, AssetInfo, CreatePairRequirements, LPTokenInfo, PairInfo, PairType};
+use crate::pair::PauseStatus;
 
 #[cw_serde]
 pub struct InstantiateMsg {
     /// Pair contract code ID, which is used to
     pub pair_code_id: u64,
     pub token_code_id: u64,
+    /// Share of the commission taken by the protocol, defaults to zero
+    pub protocol_fee_rate: Option<Decimal256>,
+    /// Fee rate of the flash swaps of the pairs, defaults to 0.3%
+    pub flash_fee_rate: Option<Decimal256>,
+    /// Address receiving the protocol fees collected by the pairs
+    pub fee_collector: Option<String>,
+    /// Who can create pairs, defaults to the owner only
+    pub pair_creation_mode: Option<PairCreationMode>,
+    /// Amount of LP token locked by the first provision of new pairs, defaults to 1000uLP
+    pub min_liquidity: Option<Uint128>,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub enum PairCreationMode {
+    /// Only the owner and the pair creators can create pairs
+    #[default]
+    OwnerOnly,
+    /// Anyone can create pairs by paying the fee, which is sent to the fee collector
+    Permissionless { fee: Asset },
 }
 
 #[cw_serde]
 pub enum ExecuteMsg {
-    /// UpdateConfig update relevant code IDs
+    /// UpdateConfig update relevant code IDs, the fee manager can only update the fee settings
     UpdateConfig {
-        owner: Option<String>,
         token_code_id: Option<u64>,
         pair_code_id: Option<u64>,
+        protocol_fee_rate: Option<Decimal256>,
+        flash_fee_rate: Option<Decimal256>,
+        fee_collector: Option<String>,
+        pair_creation_mode: Option<PairCreationMode>,
+        min_liquidity: Option<Uint128>,
+    },
+    /// ProposeNewOwner proposes the ownership to `owner` who must claim it within `expires_in` seconds
+    ProposeNewOwner {
+        owner: String,
+        expires_in: u64,
+    },
+    /// DropOwnershipProposal removes the pending ownership proposal
+    DropOwnershipProposal {},
+    /// ClaimOwnership is executed by the proposed owner to accept the ownership
+    ClaimOwnership {},
+    /// GrantRole gives a role to an address, replacing its previous role
+    GrantRole {
+        address: String,
+        role: Role,
     },
-    /// CreatePair instantiates pair contract
+    /// RevokeRole removes the role of an address
+    RevokeRole {
+        address: String,
+    },
+    Receive(Cw20ReceiveMsg),
+    /// CreatePair instantiates pair contract, the pair creation fee can be sent as native funds
     CreatePair {
         /// Asset infos
         asset_infos: [AssetInfo; 2],
@@ -28,15 +75,94 @@ pub enum ExecuteMsg {
         commission_rate: Option<Decimal256>,
         /// Liquidity token name and symbol for the pair
         lp_token_info: LPTokenInfo,
+        /// Invariant of the pair, defaults to the constant product
+        pair_type: Option<PairType>,
+    },
+    /// CreatePairs instantiates the pair contracts of a batch atomically, the pair creation fee
+    /// is paid once per pair
+    CreatePairs {
+        pairs: Vec<CreatePairParams>,
     },
+    /// AddNativeTokenDecimals registers the decimals of a native token, the first pairs of the
+    /// token are updated and the rest by PropagateNativeDecimals
     AddNativeTokenDecimals {
         denom: String,
         decimals: u8,
     },
+    /// PropagateNativeDecimals updates the pairs of a native token after `start_after` whose
+    /// decimals differ from the registered ones, anyone can execute it
+    PropagateNativeDecimals {
+        denom: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
     MigratePair {
         contract: String,
         code_id: Option<u64>,
     },
+    /// SetPauseStatus pauses the swaps or the deposits of a pair, or of all pairs if `pair` is
+    /// not given, in which case the first pairs are updated and the rest by PropagatePauseStatus
+    SetPauseStatus {
+        pair: Option<String>,
+        swaps: bool,
+        deposits: bool,
+    },
+    /// PropagatePauseStatus updates the pairs after `start_after` to the global pause status,
+    /// anyone can execute it
+    PropagatePauseStatus {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// UpdatePairCommissionRate updates the commission rate of a pair, immediately or
+    /// from `effective_at` on, replacing the pending update of the pair
+    UpdatePairCommissionRate {
+        pair: String,
+        commission_rate: Decimal256,
+        effective_at: Option<Timestamp>,
+    },
+    /// ApplyPairCommissionRate applies the pending commission rate of a pair once it is effective,
+    /// anyone can execute it
+    ApplyPairCommissionRate {
+        pair: String,
+    },
+    /// DeprecatePair makes a pair withdraw-only, it is no longer listed nor used by the router
+    DeprecatePair {
+        asset_infos: [AssetInfo; 2],
+        /// Defaults to the constant product pair
+        pair_type: Option<PairType>,
+        reason: String,
+    },
+    /// DeregisterPair removes a deprecated pair, so that the same assets can be paired again
+    DeregisterPair {
+        asset_infos: [AssetInfo; 2],
+        /// Defaults to the constant product pair
+        pair_type: Option<PairType>,
+    },
+}
+
+#[cw_serde]
+#[allow(clippy::large_enum_variant)]
+pub enum Cw20HookMsg {
+    /// CreatePair with the pair creation fee paid by the received token
+    CreatePair {
+        asset_infos: [AssetInfo; 2],
+        requirements: CreatePairRequirements,
+        commission_rate: Option<Decimal256>,
+        lp_token_info: LPTokenInfo,
+        pair_type: Option<PairType>,
+    },
+    /// CreatePairs with the pair creation fees paid by the received token
+    CreatePairs { pairs: Vec<CreatePairParams> },
+}
+
+/// Parameters of a pair created in a batch, same as the ones of CreatePair
+#[cw_serde]
+pub struct CreatePairParams {
+    pub asset_infos: [AssetInfo; 2],
+    pub requirements: CreatePairRequirements,
+    pub commission_rate: Option<Decimal256>,
+    pub lp_token_info: LPTokenInfo,
+    pub pair_type: Option<PairType>,
 }
 
 #[cw_serde]
@@ -45,14 +171,58 @@ pub enum QueryMsg {
     #[returns(ConfigResponse)]
     Config {},
     #[returns(PairInfo)]
-    Pair { asset_infos: [AssetInfo; 2] },
+    Pair {
+        asset_infos: [AssetInfo; 2],
+        /// Defaults to the constant product pair
+        pair_type: Option<PairType>,
+    },
     #[returns(PairsResponse)]
     Pairs {
         start_after: Option<[AssetInfo; 2]>,
+        /// Pair type of the `start_after` pair, defaults to the constant product pair
+        start_after_pair_type: Option<PairType>,
         limit: Option<u32>,
+        /// Lists the deprecated pairs as well, defaults to false
+        include_deprecated: Option<bool>,
     },
+    /// Pairs of the asset ordered by creation, `start_after` is the address of a pair
+    #[returns(PairsResponse)]
+    PairsByAsset {
+        asset_info: AssetInfo,
+        start_after: Option<String>,
+        limit: Option<u32>,
+        /// Lists the deprecated pairs as well, defaults to false
+        include_deprecated: Option<bool>,
+    },
+    /// Reason why the pair is deprecated
+    #[returns(PairDeprecationResponse)]
+    PairDeprecation { pair: String },
+    #[returns(PairInfo)]
+    PairByAddress { address: String },
+    #[returns(PairInfo)]
+    PairByLpToken { lp_token: String },
+    /// Number of registered pairs
+    #[returns(PairCountResponse)]
+    PairCount {},
     #[returns(NativeTokenDecimalsResponse)]
     NativeTokenDecimals { denom: String },
+    /// Pairs of the native token after `start_after` whose decimals differ from the registered ones
+    #[returns(NativeDecimalsMismatchesResponse)]
+    NativeDecimalsMismatches {
+        denom: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Pause status set for the pair, or the global one if `pair` is not given
+    #[returns(PauseStatus)]
+    PauseStatus { pair: Option<String> },
+    #[returns(RolesResponse)]
+    Roles {},
+    #[returns(OwnershipProposalResponse)]
+    OwnershipProposal {},
+    /// Commission rate which is scheduled for the pair
+    #[returns(PendingCommissionRateResponse)]
+    PendingCommissionRate { pair: String },
 }
 
 // We define a custom struct for each query response
@@ -61,6 +231,55 @@ pub struct ConfigResponse {
     pub owner: String,
     pub pair_code_id: u64,
     pub token_code_id: u64,
+    pub protocol_fee_rate: Decimal256,
+    pub flash_fee_rate: Decimal256,
+    pub fee_collector: Option<String>,
+    pub pair_creation_mode: PairCreationMode,
+    pub min_liquidity: Uint128,
+}
+
+/// Roles granted by the owner, who is allowed to do everything the roles are
+#[cw_serde]
+pub enum Role {
+    /// Creates pairs and registers the decimals of native tokens
+    PairCreator,
+    /// Updates the protocol fee rate, the fee collector and the commission rates of the pairs
+    FeeManager,
+    /// Pauses the pairs
+    Guardian,
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Role::PairCreator => write!(f, "pair_creator"),
+            Role::FeeManager => write!(f, "fee_manager"),
+            Role::Guardian => write!(f, "guardian"),
+        }
+    }
+}
+
+#[cw_serde]
+pub struct AddressRole {
+    pub address: String,
+    pub role: Role,
+}
+
+#[cw_serde]
+pub struct RolesResponse {
+    pub roles: Vec<AddressRole>,
+}
+
+#[cw_serde]
+pub struct OwnershipProposalResponse {
+    pub owner: String,
+    pub expires_at: Timestamp,
+}
+
+#[cw_serde]
+pub struct PendingCommissionRateResponse {
+    pub commission_rate: Decimal256,
+    pub effective_at: Timestamp,
 }
 
 /// We currently take no arguments for migrations
@@ -73,7 +292,24 @@ pub struct PairsResponse {
     pub pairs: Vec<PairInfo>,
 }
 
+#[cw_serde]
+pub struct PairCountResponse {
+    pub count: u64,
+}
+
+#[cw_serde]
+pub struct PairDeprecationResponse {
+    pub reason: String,
+}
+
 #[cw_serde]
 pub struct NativeTokenDecimalsResponse {
     pub decimals: u8,
 }
+
+#[cw_serde]
+pub struct NativeDecimalsMismatchesResponse {
+    pub pairs: Vec<PairInfo>,
+    /// Pair to start after for the next page, none once all the pairs are checked
+    pub next_start_after: Option<String>,
+}
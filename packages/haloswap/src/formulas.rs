@@ -1,8 +1,7 @@
 use crate::asset::{Asset, PairInfoRaw};
 use crate::error::ContractError;
 use bignumber::{Decimal256, Uint256};
-use cosmwasm_std::{MessageInfo, StdError, Uint128};
-use integer_sqrt::IntegerSquareRoot;
+use cosmwasm_std::{CheckedMultiplyRatioError, MessageInfo, Uint128};
 
 /*
 * @brief: This function calculates the amount of LP tokens to be minted to the user.
@@ -27,25 +26,39 @@ pub fn calculate_lp_token_amount_to_user(
         // when pool is empty
         // if the sender is not in whitelist of requirements, then return error
         if !pair_info.requirements.whitelist.contains(&info.sender) {
-            return Err(ContractError::Std(StdError::generic_err(
-                "the sender is not in whitelist",
-            )));
+            return Err(ContractError::WhitelistRequired {});
         }
 
         // if the minimum amount of deposit is not satisfied, then return error
         if deposits[0] < pair_info.requirements.first_asset_minimum
             || deposits[1] < pair_info.requirements.second_asset_minimum
         {
-            return Err(ContractError::Std(StdError::generic_err(
-                "the minimum deposit is not satisfied",
-            )));
+            return Err(ContractError::MinimumDepositNotSatisfied {});
         }
+    }
+
+    compute_share(lp_total_supply, deposits, &pools)
+}
 
+/*
+* @brief: This function calculates the amount of LP tokens of the deposits without any requirement check.
+* @param: - lp_total_supply: the total supply of the LP token.
+*         - deposits: the amount of deposits of the user.
+*         - pools: the amount of pools of the pair.
+* @return: - Result<Uint128, ContractError>: the amount of LP tokens, including the reserved amount
+*             for an empty pool, or an error if it overflows.
+* @test: test_compute_share_overflow.
+*/
+pub fn compute_share(
+    lp_total_supply: Uint128,
+    deposits: [Uint128; 2],
+    pools: &[Asset; 2],
+) -> Result<Uint128, ContractError> {
+    if lp_total_supply.is_zero() {
         // if the total supply of the LP token is zero, Initial share = collateral amount
         // hoanm: EQUATION - LP = \sqrt{A * B}
-        Ok(Uint128::from(
-            (deposits[0].u128() * deposits[1].u128()).integer_sqrt(),
-        ))
+        // the product is computed in 256 bits, its square root always fits in 128 bits
+        Ok(integer_sqrt_uint256(Uint256::from(deposits[0]) * Uint256::from(deposits[1])).into())
     } else {
         // hoanm: update these equations by using the formula of Uniswap V2
         // min(1, 2)
@@ -53,13 +66,70 @@ pub fn calculate_lp_token_amount_to_user(
         // == deposit_0 * lp_total_supply / pool_0
         // 2. sqrt(deposit_1 * exchange_rate_1_to_0 * deposit_1) * (lp_total_supply / sqrt(pool_1 * pool_1))
         // == deposit_1 * lp_total_supply / pool_1
-        Ok(std::cmp::min(
-            deposits[0].multiply_ratio(lp_total_supply, pools[0].amount),
-            deposits[1].multiply_ratio(lp_total_supply, pools[1].amount),
-        ))
+        let share_0 = deposits[0]
+            .checked_multiply_ratio(lp_total_supply, pools[0].amount)
+            .map_err(share_error)?;
+        let share_1 = deposits[1]
+            .checked_multiply_ratio(lp_total_supply, pools[1].amount)
+            .map_err(share_error)?;
+        Ok(std::cmp::min(share_0, share_1))
+    }
+}
+
+fn share_error(err: CheckedMultiplyRatioError) -> ContractError {
+    match err {
+        CheckedMultiplyRatioError::DivideByZero => ContractError::NoLiquidity {},
+        CheckedMultiplyRatioError::Overflow => ContractError::ShareOverflow {},
     }
 }
 
+/*
+* @brief: This function calculates the part of the deposits which is used at the ratio of the pools,
+*         the excess of the other asset is rounded in favor of the pool and is refunded to the user.
+* @param: - lp_total_supply: the total supply of the LP token.
+*         - deposits: the amount of deposits of the user.
+*         - pools: the amount of pools of the pair.
+* @return: - [Uint128; 2]: the used amount of each deposit, all of them for an empty pool.
+* @test: test_compute_used_deposits.
+*/
+pub fn compute_used_deposits(
+    lp_total_supply: Uint128,
+    deposits: [Uint128; 2],
+    pools: &[Asset; 2],
+) -> [Uint128; 2] {
+    if lp_total_supply.is_zero() || pools[0].amount.is_zero() || pools[1].amount.is_zero() {
+        return deposits;
+    }
+
+    // EQUATION: deposit_0 / pool_0 <= deposit_1 / pool_1 <=> deposit_0 * pool_1 <= deposit_1 * pool_0
+    let ratio_0 = Uint256::from(deposits[0]) * Uint256::from(pools[1].amount);
+    let ratio_1 = Uint256::from(deposits[1]) * Uint256::from(pools[0].amount);
+    // the rounded up amount never exceeds the deposit of the other asset
+    if ratio_0 <= ratio_1 {
+        [
+            deposits[0],
+            ceil_multiply_ratio(deposits[0], pools[1].amount, pools[0].amount),
+        ]
+    } else {
+        [
+            ceil_multiply_ratio(deposits[1], pools[0].amount, pools[1].amount),
+            deposits[1],
+        ]
+    }
+}
+
+// amount * numerator / denominator rounded up
+fn ceil_multiply_ratio(amount: Uint128, numerator: Uint128, denominator: Uint128) -> Uint128 {
+    let product = Uint256::from(amount) * Uint256::from(numerator);
+    let denominator = Uint256::from(denominator);
+    let mut result = product.multiply_ratio(Uint256::one(), denominator);
+    if result * denominator < product {
+        result += Uint256::one();
+    }
+
+    result.into()
+}
+
 /*
 * @brief: This function calculates the amount of return, spread and commission based on the formula
 *         `return_amount = offer_amount * (1 - spread) * ask_pool / (offer_pool + offer_amount)`
@@ -104,7 +174,8 @@ pub fn compute_swap(
     // calculate the commission_amount
     let commission_amount: Uint256 = return_amount * commission_rate;
 
-    // commission will be absorbed to pool and the currency will be the same as the ask currency
+    // commission will be absorbed to pool and the currency will be the same as the ask currency,
+    // the protocol share of it is split off by the pair with `compute_protocol_fee`
     let return_amount: Uint256 = return_amount - commission_amount;
     (
         return_amount.into(),
@@ -119,12 +190,16 @@ pub fn compute_offer_amount(
     ask_pool: Uint128,
     ask_amount: Uint128,
     commission_rate: Decimal256,
-) -> (Uint128, Uint128, Uint128) {
+) -> Result<(Uint128, Uint128, Uint128), ContractError> {
     let offer_pool: Uint256 = offer_pool.into();
     let ask_pool: Uint256 = ask_pool.into();
     let ask_amount: Uint256 = ask_amount.into();
 
     // let commission_rate = Decimal256::from_str(COMMISSION_RATE).unwrap();
+    // a commission of 100% would take the whole return, whatever the offer
+    if commission_rate >= Decimal256::one() {
+        return Err(ContractError::InvalidCommissionRate {});
+    }
 
     // EQUATION: A = \frac{K}{R_B - (B * (1-P))} - R_A
     // ask => offer
@@ -132,31 +207,426 @@ pub fn compute_offer_amount(
     let cp: Uint256 = offer_pool * ask_pool;
 
     let one_minus_commission = Decimal256::one() - commission_rate;
-    let inv_one_minus_commission = Decimal256::one() / one_minus_commission;
 
-    let offer_amount: Uint256 = Uint256::one()
-        .multiply_ratio(cp, ask_pool - ask_amount * inv_one_minus_commission)
-        - offer_pool;
+    // both divisions are rounded up, in favor of the pool
+    let before_commission_deduction: Uint256 = div_ceil(
+        ask_amount * Uint256::from(Decimal256::DECIMAL_FRACTIONAL),
+        one_minus_commission.0.into(),
+    );
+    if before_commission_deduction >= ask_pool {
+        return Err(ContractError::AskAmountExceedsPool {});
+    }
+
+    let offer_amount: Uint256 = div_ceil(cp, ask_pool - before_commission_deduction) - offer_pool;
 
-    let before_commission_deduction: Uint256 = ask_amount * inv_one_minus_commission;
     let before_spread_deduction: Uint256 =
         offer_amount * Decimal256::from_ratio(ask_pool, offer_pool);
 
-    let spread_amount = if before_spread_deduction > before_commission_deduction {
-        before_spread_deduction - before_commission_deduction
-    } else {
-        Uint256::zero()
-    };
+    let spread_amount = saturating_sub(before_spread_deduction, before_commission_deduction);
 
     let commission_amount = before_commission_deduction * commission_rate;
 
-    (
+    Ok((
         offer_amount.into(),
         spread_amount.into(),
         commission_amount.into(),
+    ))
+}
+
+/*
+* @brief: This function calculates the part of a single-sided deposit which is swapped to the other asset,
+*         so that the rest of the deposit and the return of the swap are in the ratio of the pools after the swap.
+*         The commission is deducted from the return amount as in `compute_swap`.
+* @param: - offer_pool: the amount of the pool of the deposited asset.
+*         - offer_amount: the amount of the deposit.
+*         - commission_rate: the commission rate of the pair.
+* @return: - Uint128: the amount to swap.
+* @test: test_compute_single_sided_swap_amount.
+*/
+// EQUATION: s^2 + s * (R_A * (2 - F) - A * F) - A * R_A = 0
+pub fn compute_single_sided_swap_amount(
+    offer_pool: Uint128,
+    offer_amount: Uint128,
+    commission_rate: Decimal256,
+) -> Uint128 {
+    let offer_pool: Uint256 = offer_pool.into();
+    let offer_amount: Uint256 = offer_amount.into();
+
+    // the linear coefficient may be negative for a deposit much greater than the pool
+    let positive_term = offer_pool * (Decimal256::from_uint256(2u64) - commission_rate);
+    let negative_term = offer_amount * commission_rate;
+    let constant_term = Uint256::from(4u64) * offer_amount * offer_pool;
+
+    let swap_amount = if positive_term >= negative_term {
+        let b = positive_term - negative_term;
+        integer_sqrt_uint256(b * b + constant_term) - b
+    } else {
+        let b = negative_term - positive_term;
+        integer_sqrt_uint256(b * b + constant_term) + b
+    }
+    .multiply_ratio(Uint256::one(), Uint256::from(2u64));
+
+    // never swap more than the deposit because of the rounding
+    std::cmp::min(swap_amount, offer_amount).into()
+}
+
+/*
+* @brief: This function calculates the part of a single-sided deposit which is swapped to the other asset
+*         of a StableSwap pool by bisection, since there is no closed form for the StableSwap invariant.
+* @param: - offer_pool: the amount of the pool of the deposited asset.
+*         - ask_pool: the amount of the pool of the other asset.
+*         - offer_amount: the amount of the deposit.
+*         - offer_decimal, ask_decimal: the decimals of the offer and ask assets.
+*         - amp: the amplification coefficient.
+*         - commission_rate: the commission rate of the pair.
+* @return: - Result<Uint128, ContractError>: the amount to swap.
+* @test: test_compute_single_sided_swap_amount.
+*/
+pub fn compute_stable_single_sided_swap_amount(
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    offer_amount: Uint128,
+    offer_decimal: u8,
+    ask_decimal: u8,
+    amp: u64,
+    commission_rate: Decimal256,
+) -> Result<Uint128, ContractError> {
+    let mut low = Uint128::zero();
+    let mut high = offer_amount;
+    while high - low > Uint128::one() {
+        let swap_amount = low + (high - low) / Uint128::from(2u128);
+        let (return_amount, _, _) = compute_stable_swap(
+            offer_pool,
+            ask_pool,
+            swap_amount,
+            offer_decimal,
+            ask_decimal,
+            amp,
+            commission_rate,
+        )?;
+
+        // compare (A - s) / (R_A + s) with B / (R_B - B)
+        let rest = Uint256::from(offer_amount.checked_sub(swap_amount)?)
+            * Uint256::from(ask_pool.checked_sub(return_amount)?);
+        let returned =
+            Uint256::from(return_amount) * Uint256::from(offer_pool.checked_add(swap_amount)?);
+        if rest > returned {
+            low = swap_amount;
+        } else {
+            high = swap_amount;
+        }
+    }
+
+    Ok(low)
+}
+
+fn integer_sqrt_uint256(value: Uint256) -> Uint256 {
+    if value.is_zero() {
+        return Uint256::zero();
+    }
+
+    // Newton's method from above, it stops when the estimation does not decrease anymore
+    let mut x = value;
+    let mut y = (x + Uint256::one()).multiply_ratio(Uint256::one(), Uint256::from(2u64));
+    while y < x {
+        x = y;
+        y = (x + value.multiply_ratio(Uint256::one(), x))
+            .multiply_ratio(Uint256::one(), Uint256::from(2u64));
+    }
+
+    x
+}
+
+/*
+* @brief: This function calculates the part of the commission which is accrued for the protocol.
+*         The rest of the commission stays in the pool for the liquidity providers.
+* @param: - commission_amount: the total commission of the swap.
+*         - protocol_fee_rate: the share of the commission taken by the protocol.
+* @return: - (Uint128, Uint128): the amount of LP commission and protocol fee.
+* @test: test_compute_protocol_fee.
+*/
+pub fn compute_protocol_fee(
+    commission_amount: Uint128,
+    protocol_fee_rate: Decimal256,
+) -> (Uint128, Uint128) {
+    let protocol_fee_amount: Uint128 =
+        (Uint256::from(commission_amount) * protocol_fee_rate).into();
+
+    (commission_amount - protocol_fee_amount, protocol_fee_amount)
+}
+
+/// Number of assets in a StableSwap pool
+const STABLE_N_COINS: u64 = 2;
+/// Maximum number of Newton iterations to find D and y
+const STABLE_MAX_ITERATIONS: u8 = 64;
+
+/*
+* @brief: This function calculates the StableSwap invariant D of the pools by Newton's method.
+*         The pools must be normalized to the same precision.
+* @param: - amp: the amplification coefficient.
+*         - pools: the normalized amounts of the pools.
+* @return: - Result<Uint256, ContractError>: the invariant D.
+* @test: test_compute_stable_swap.
+*/
+// EQUATION: A * n^n * \sum x_i + D = A * D * n^n + \frac{D^{n+1}}{n^n * \prod x_i}
+pub fn compute_stable_d(amp: u64, pools: [Uint256; 2]) -> Result<Uint256, ContractError> {
+    let sum = pools[0] + pools[1];
+    if sum.is_zero() {
+        return Ok(Uint256::zero());
+    }
+    if pools[0].is_zero() || pools[1].is_zero() {
+        return Err(ContractError::EmptyStablePool {});
+    }
+
+    let n_coins = Uint256::from(STABLE_N_COINS);
+    let ann = Uint256::from(amp * STABLE_N_COINS);
+
+    let mut d = sum;
+    for _ in 0..STABLE_MAX_ITERATIONS {
+        // d_p = D^{n+1} / (n^n * \prod x_i)
+        let d_p = d
+            .multiply_ratio(d, pools[0] * n_coins)
+            .multiply_ratio(d, pools[1] * n_coins);
+        let d_prev = d;
+
+        // D = (Ann * S + n * d_p) * D / ((Ann - 1) * D + (n + 1) * d_p)
+        d = (ann * sum + d_p * n_coins).multiply_ratio(
+            d,
+            (ann - Uint256::one()) * d + (n_coins + Uint256::one()) * d_p,
+        );
+
+        if abs_diff(d, d_prev) <= Uint256::one() {
+            return Ok(d);
+        }
+    }
+
+    Err(ContractError::StableSwapNotConverged {})
+}
+
+/*
+* @brief: This function calculates the amount of the other pool which keeps the invariant D
+*         when one of the pools is changed to `new_pool`, by Newton's method.
+* @param: - amp: the amplification coefficient.
+*         - new_pool: the normalized amount of the changed pool.
+*         - d: the invariant D.
+* @return: - Result<Uint256, ContractError>: the normalized amount of the other pool.
+* @test: test_compute_stable_swap.
+*/
+// EQUATION: y^2 + (x + \frac{D}{Ann} - D) * y = \frac{D^{n+1}}{n^n * x * Ann}
+pub fn compute_stable_y(amp: u64, new_pool: Uint256, d: Uint256) -> Result<Uint256, ContractError> {
+    if new_pool.is_zero() {
+        return Err(ContractError::EmptyStablePool {});
+    }
+
+    let n_coins = Uint256::from(STABLE_N_COINS);
+    let ann = Uint256::from(amp * STABLE_N_COINS);
+
+    let c = d
+        .multiply_ratio(d, new_pool * n_coins)
+        .multiply_ratio(d, ann * n_coins);
+    let b = new_pool + d.multiply_ratio(Uint256::one(), ann);
+
+    let mut y = d;
+    for _ in 0..STABLE_MAX_ITERATIONS {
+        let y_prev = y;
+
+        // y = (y^2 + c) / (2y + b - D)
+        y = (y * y + c).multiply_ratio(Uint256::one(), y + y + b - d);
+
+        if abs_diff(y, y_prev) <= Uint256::one() {
+            return Ok(y);
+        }
+    }
+
+    Err(ContractError::StableSwapNotConverged {})
+}
+
+/*
+* @brief: This function calculates the amount of return, spread and commission of a swap
+*         in a StableSwap pool. The pools are normalized to the greatest decimals of the assets,
+*         the spread is the difference with the 1:1 exchange rate.
+* @param: - offer_pool: the amount of the offer pool.
+*         - ask_pool: the amount of the ask pool.
+*         - offer_amount: the amount of the offer.
+*         - offer_decimal, ask_decimal: the decimals of the offer and ask assets.
+*         - amp: the amplification coefficient.
+*         - commission_rate: the commission rate of the pair.
+* @return: - Result<(Uint128, Uint128, Uint128), ContractError>: the amount of return, spread and commission.
+* @test: test_compute_stable_swap.
+*/
+pub fn compute_stable_swap(
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    offer_amount: Uint128,
+    offer_decimal: u8,
+    ask_decimal: u8,
+    amp: u64,
+    commission_rate: Decimal256,
+) -> Result<(Uint128, Uint128, Uint128), ContractError> {
+    let precision = offer_decimal.max(ask_decimal);
+    let offer_pool = normalize_amount(offer_pool, offer_decimal, precision)?;
+    let ask_pool = normalize_amount(ask_pool, ask_decimal, precision)?;
+    let offer_amount = normalize_amount(offer_amount, offer_decimal, precision)?;
+
+    let d = compute_stable_d(amp, [offer_pool, ask_pool])?;
+    let new_ask_pool = compute_stable_y(amp, offer_pool + offer_amount, d)?;
+
+    // subtract one more unit, rounding in favor of the pool
+    let return_amount = saturating_sub(ask_pool, new_ask_pool + Uint256::one());
+    let spread_amount = saturating_sub(offer_amount, return_amount);
+
+    let return_amount = denormalize_amount(return_amount, ask_decimal, precision, false)?;
+    let spread_amount = denormalize_amount(spread_amount, ask_decimal, precision, false)?;
+
+    // commission will be absorbed to pool and the currency will be the same as the ask currency
+    let commission_amount: Uint256 = return_amount * commission_rate;
+    let return_amount: Uint256 = return_amount - commission_amount;
+
+    Ok((
+        return_amount.into(),
+        spread_amount.into(),
+        commission_amount.into(),
+    ))
+}
+
+/*
+* @brief: This function calculates the amount of offer, spread and commission to receive
+*         `ask_amount` from a StableSwap pool.
+* @param: - offer_pool: the amount of the offer pool.
+*         - ask_pool: the amount of the ask pool.
+*         - ask_amount: the amount of the ask asset to receive after the commission.
+*         - offer_decimal, ask_decimal: the decimals of the offer and ask assets.
+*         - amp: the amplification coefficient.
+*         - commission_rate: the commission rate of the pair.
+* @return: - Result<(Uint128, Uint128, Uint128), ContractError>: the amount of offer, spread and commission.
+* @test: test_compute_stable_offer_amount.
+*/
+pub fn compute_stable_offer_amount(
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    ask_amount: Uint128,
+    offer_decimal: u8,
+    ask_decimal: u8,
+    amp: u64,
+    commission_rate: Decimal256,
+) -> Result<(Uint128, Uint128, Uint128), ContractError> {
+    // a commission of 100% would take the whole return, whatever the offer
+    if commission_rate >= Decimal256::one() {
+        return Err(ContractError::InvalidCommissionRate {});
+    }
+
+    let one_minus_commission = Decimal256::one() - commission_rate;
+    // rounded up, in favor of the pool
+    let before_commission_deduction: Uint256 = div_ceil(
+        Uint256::from(ask_amount) * Uint256::from(Decimal256::DECIMAL_FRACTIONAL),
+        one_minus_commission.0.into(),
+    );
+    let commission_amount: Uint256 = before_commission_deduction * commission_rate;
+
+    let precision = offer_decimal.max(ask_decimal);
+    let offer_pool = normalize_amount(offer_pool, offer_decimal, precision)?;
+    let ask_pool = normalize_amount(ask_pool, ask_decimal, precision)?;
+    // the amount before the commission may not fit in 128 bits for a huge ask amount
+    if before_commission_deduction >= Uint256::from(Uint128::MAX) {
+        return Err(ContractError::AskAmountExceedsPool {});
+    }
+    let ask_amount = normalize_amount(before_commission_deduction.into(), ask_decimal, precision)?;
+    if ask_amount >= ask_pool {
+        return Err(ContractError::AskAmountExceedsPool {});
+    }
+
+    let d = compute_stable_d(amp, [offer_pool, ask_pool])?;
+    let new_offer_pool = compute_stable_y(amp, ask_pool - ask_amount, d)?;
+
+    // add one more unit, rounding in favor of the pool
+    let offer_amount = saturating_sub(new_offer_pool + Uint256::one(), offer_pool);
+    let spread_amount = saturating_sub(offer_amount, ask_amount);
+
+    let offer_amount = denormalize_amount(offer_amount, offer_decimal, precision, true)?;
+    let spread_amount = denormalize_amount(spread_amount, ask_decimal, precision, false)?;
+
+    Ok((
+        offer_amount.into(),
+        spread_amount.into(),
+        commission_amount.into(),
+    ))
+}
+
+/*
+* @brief: This function calculates the StableSwap invariant D of pools with different decimals.
+* @param: - pools: the amounts of the pools.
+*         - decimals: the decimals of the assets of the pools.
+*         - amp: the amplification coefficient.
+* @return: - Result<Uint256, ContractError>: the invariant D of the normalized pools.
+* @test: test_compute_stable_swap.
+*/
+pub fn compute_stable_invariant(
+    pools: [Uint128; 2],
+    decimals: [u8; 2],
+    amp: u64,
+) -> Result<Uint256, ContractError> {
+    let precision = decimals[0].max(decimals[1]);
+
+    compute_stable_d(
+        amp,
+        [
+            normalize_amount(pools[0], decimals[0], precision)?,
+            normalize_amount(pools[1], decimals[1], precision)?,
+        ],
     )
 }
 
+// scale the amount from `decimals` up to `precision`
+fn normalize_amount(
+    amount: Uint128,
+    decimals: u8,
+    precision: u8,
+) -> Result<Uint256, ContractError> {
+    Ok(Uint256::from(amount) * decimals_factor(decimals, precision)?)
+}
+
+// scale the amount from `precision` down to `decimals`
+fn denormalize_amount(
+    amount: Uint256,
+    decimals: u8,
+    precision: u8,
+    round_up: bool,
+) -> Result<Uint256, ContractError> {
+    let factor = decimals_factor(decimals, precision)?;
+    if round_up {
+        Ok((amount + factor - Uint256::one()).multiply_ratio(Uint256::one(), factor))
+    } else {
+        Ok(amount.multiply_ratio(Uint256::one(), factor))
+    }
+}
+
+// 10^(precision - decimals), which overflows for a difference of more than 38 decimals
+fn decimals_factor(decimals: u8, precision: u8) -> Result<Uint256, ContractError> {
+    let factor = Uint128::from(10u128).checked_pow((precision - decimals).into())?;
+
+    Ok(Uint256::from(factor))
+}
+
+fn div_ceil(a: Uint256, b: Uint256) -> Uint256 {
+    (a + b - Uint256::one()).multiply_ratio(Uint256::one(), b)
+}
+
+fn saturating_sub(a: Uint256, b: Uint256) -> Uint256 {
+    if a > b {
+        a - b
+    } else {
+        Uint256::zero()
+    }
+}
+
+fn abs_diff(a: Uint256, b: Uint256) -> Uint256 {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
 // hoanm: EQUATION - \frac{A}{B} * (1-ST) > \frac{R_A}{R_B} \parallel \frac{B}{A} * (1-ST) > \frac{R_B}{R_A}
 pub fn calc_price_drop(
     offer_deposits: Uint256,
@@ -170,6 +640,30 @@ pub fn calc_slippage_tolerance(offer_pool: Uint256, ask_pool: Uint256) -> Decima
     Decimal256::from_ratio(offer_pool, ask_pool)
 }
 
+#[test]
+fn test_compute_protocol_fee() {
+    use std::str::FromStr;
+
+    let (lp_commission_amount, protocol_fee_amount) = compute_protocol_fee(
+        Uint128::from(1000u128),
+        Decimal256::from_str("0.2").unwrap(),
+    );
+    assert_eq!(lp_commission_amount, Uint128::from(800u128));
+    assert_eq!(protocol_fee_amount, Uint128::from(200u128));
+
+    // protocol fee is rounded down in favor of the liquidity providers
+    let (lp_commission_amount, protocol_fee_amount) =
+        compute_protocol_fee(Uint128::from(7u128), Decimal256::from_str("0.5").unwrap());
+    assert_eq!(lp_commission_amount, Uint128::from(4u128));
+    assert_eq!(protocol_fee_amount, Uint128::from(3u128));
+
+    // zero protocol fee rate keeps the whole commission in the pool
+    let (lp_commission_amount, protocol_fee_amount) =
+        compute_protocol_fee(Uint128::from(1000u128), Decimal256::zero());
+    assert_eq!(lp_commission_amount, Uint128::from(1000u128));
+    assert_eq!(protocol_fee_amount, Uint128::zero());
+}
+
 #[test]
 fn test_compute_swap_with_huge_pool_variance() {
     use std::str::FromStr;
@@ -295,3 +789,345 @@ fn test_compute_swap_with_huge_ask_pool_and_offer_pool_and_offer_amount() {
         Uint128::from(5104235503814076951950619111u128)
     );
 }
+
+#[test]
+fn test_compute_stable_swap() {
+    use std::str::FromStr;
+
+    // balanced pools of 1,000,000 units with 6 decimals
+    let pool = Uint128::from(1_000_000_000_000u128);
+
+    let (return_amount, spread_amount, commission_amount) = compute_stable_swap(
+        pool,
+        pool,
+        Uint128::from(1_000_000_000u128),
+        6u8,
+        6u8,
+        100,
+        Decimal256::from_str("0.0005").unwrap(),
+    )
+    .unwrap();
+
+    // a swap of 0.1% of the pool is almost 1:1
+    assert_eq!(return_amount, Uint128::from(999_490_104u128));
+    assert_eq!(spread_amount, Uint128::from(9_901u128));
+    assert_eq!(commission_amount, Uint128::from(499_995u128));
+
+    // the constant product pool gives a much worse execution for the same pools
+    let (xyk_return_amount, _, _) = compute_swap(
+        pool,
+        pool,
+        Uint128::from(1_000_000_000u128),
+        Decimal256::from_str("0.0005").unwrap(),
+    );
+    assert!(xyk_return_amount < return_amount);
+
+    // the invariant of the pools normalized to the same precision
+    assert_eq!(
+        compute_stable_invariant(
+            [pool, Uint128::from(1_000_000_000_000_000_000u128)],
+            [6u8, 12u8],
+            100
+        )
+        .unwrap(),
+        compute_stable_d(100, [Uint256::from(1_000_000_000_000_000_000u128); 2]).unwrap()
+    );
+
+    // the result does not depend on the decimals of the assets
+    let (return_amount_18, _, commission_amount_18) = compute_stable_swap(
+        pool,
+        Uint128::from(1_000_000_000_000_000_000_000_000u128),
+        Uint128::from(1_000_000_000u128),
+        6u8,
+        18u8,
+        100,
+        Decimal256::from_str("0.0005").unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        return_amount_18.multiply_ratio(1u128, 1_000_000_000_000u128),
+        return_amount
+    );
+    assert_eq!(
+        commission_amount_18.multiply_ratio(1u128, 1_000_000_000_000u128),
+        commission_amount
+    );
+
+    // the invariant is the sum of the pools when they are balanced
+    let d = compute_stable_d(100, [Uint256::from(pool), Uint256::from(pool)]).unwrap();
+    assert_eq!(d, Uint256::from(2_000_000_000_000u128));
+}
+
+#[test]
+fn test_compute_offer_amount() {
+    use std::str::FromStr;
+
+    let pool = Uint128::from(1_000_000_000u128);
+    let commission_rate = Decimal256::from_str("0.003").unwrap();
+
+    let (offer_amount, spread_amount, commission_amount) =
+        compute_offer_amount(pool, pool, Uint128::from(1_000_000u128), commission_rate).unwrap();
+
+    // rounded up, the swap of the offer amount returns at least the ask amount
+    let (return_amount, _, _) = compute_swap(pool, pool, offer_amount, commission_rate);
+    assert!(return_amount >= Uint128::from(1_000_000u128));
+    assert_eq!(offer_amount, Uint128::from(1_004_018u128));
+    assert_eq!(spread_amount, Uint128::from(1_008u128));
+    assert_eq!(commission_amount, Uint128::from(3_009u128));
+
+    // the ask amount can not exceed the pool
+    let res = compute_offer_amount(pool, pool, pool, commission_rate);
+    assert_eq!(res.unwrap_err(), ContractError::AskAmountExceedsPool {});
+
+    // no offer can return anything with a commission of 100%
+    let res = compute_offer_amount(pool, pool, Uint128::one(), Decimal256::one());
+    assert_eq!(res.unwrap_err(), ContractError::InvalidCommissionRate {});
+}
+
+#[test]
+fn test_compute_stable_offer_amount() {
+    use std::str::FromStr;
+
+    let pool = Uint128::from(1_000_000_000_000u128);
+    let commission_rate = Decimal256::from_str("0.0005").unwrap();
+
+    let (offer_amount, _, commission_amount) = compute_stable_offer_amount(
+        pool,
+        pool,
+        Uint128::from(999_490_104u128),
+        6u8,
+        6u8,
+        100,
+        commission_rate,
+    )
+    .unwrap();
+
+    // the reverse simulation needs at least the offer amount of the swap
+    let (return_amount, _, _) =
+        compute_stable_swap(pool, pool, offer_amount, 6u8, 6u8, 100, commission_rate).unwrap();
+    assert!(return_amount >= Uint128::from(999_490_104u128));
+    assert_eq!(offer_amount, Uint128::from(1_000_000_001u128));
+    assert_eq!(commission_amount, Uint128::from(499_995u128));
+
+    // the ask amount can not exceed the pool
+    let res = compute_stable_offer_amount(pool, pool, pool, 6u8, 6u8, 100, commission_rate);
+    assert_eq!(res.unwrap_err(), ContractError::AskAmountExceedsPool {});
+
+    let res =
+        compute_stable_offer_amount(pool, pool, Uint128::one(), 6u8, 6u8, 100, Decimal256::one());
+    assert_eq!(res.unwrap_err(), ContractError::InvalidCommissionRate {});
+
+    // the decimals can not be normalized to a precision 39 decimals greater
+    let res =
+        compute_stable_offer_amount(pool, pool, Uint128::one(), 0u8, 39u8, 100, commission_rate);
+    assert!(matches!(res, Err(ContractError::OverflowError(_))));
+}
+
+#[test]
+fn test_compute_share_overflow() {
+    use crate::asset::AssetInfo;
+
+    let pools = [
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::one(),
+        },
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            amount: Uint128::one(),
+        },
+    ];
+
+    // the first share is the square root of the product, which does not overflow
+    let share = compute_share(Uint128::zero(), [Uint128::MAX, Uint128::MAX], &pools).unwrap();
+    assert_eq!(share, Uint128::MAX);
+
+    let res = compute_share(Uint128::from(2u128), [Uint128::MAX, Uint128::MAX], &pools);
+    assert_eq!(res.unwrap_err(), ContractError::ShareOverflow {});
+}
+
+// asserts that two ratios are equal up to 0.0001%
+#[cfg(test)]
+fn assert_ratio_eq(a: (Uint128, Uint128), b: (Uint128, Uint128)) {
+    let a = Decimal256::from_ratio(Uint256::from(a.0), Uint256::from(a.1));
+    let b = Decimal256::from_ratio(Uint256::from(b.0), Uint256::from(b.1));
+    let diff = if a > b { a - b } else { b - a };
+    assert!(diff < Decimal256::from_ratio(Uint256::one(), Uint256::from(1_000_000u64)));
+}
+
+#[test]
+fn test_compute_single_sided_swap_amount() {
+    use std::str::FromStr;
+
+    let offer_pool = Uint128::from(1_000_000_000u128);
+    let ask_pool = Uint128::from(2_000_000_000u128);
+    let offer_amount = Uint128::from(100_000_000u128);
+    let commission_rate = Decimal256::from_str("0.003").unwrap();
+
+    // without commission it is the same as the Uniswap V2 formula
+    let swap_amount =
+        compute_single_sided_swap_amount(offer_pool, offer_amount, Decimal256::zero());
+    assert_eq!(swap_amount, Uint128::from(48_808_848u128));
+
+    // the rest of the deposit and the return of the swap are in the ratio of the pools after the swap
+    let swap_amount = compute_single_sided_swap_amount(offer_pool, offer_amount, commission_rate);
+    let (return_amount, _, _) = compute_swap(offer_pool, ask_pool, swap_amount, commission_rate);
+    assert_ratio_eq(
+        (offer_amount - swap_amount, offer_pool + swap_amount),
+        (return_amount, ask_pool - return_amount),
+    );
+
+    // a deposit much greater than the pool
+    let swap_amount = compute_single_sided_swap_amount(
+        Uint128::from(1_000u128),
+        Uint128::from(1_000_000_000_000u128),
+        Decimal256::from_str("0.5").unwrap(),
+    );
+    assert!(swap_amount < Uint128::from(1_000_000_000_000u128));
+
+    // the bisection converges to the same point for a StableSwap pool
+    let swap_amount = compute_stable_single_sided_swap_amount(
+        offer_pool,
+        offer_pool,
+        offer_amount,
+        6u8,
+        6u8,
+        100,
+        commission_rate,
+    )
+    .unwrap();
+    let (return_amount, _, _) = compute_stable_swap(
+        offer_pool,
+        offer_pool,
+        swap_amount,
+        6u8,
+        6u8,
+        100,
+        commission_rate,
+    )
+    .unwrap();
+    assert_ratio_eq(
+        (offer_amount - swap_amount, offer_pool + swap_amount),
+        (return_amount, offer_pool - return_amount),
+    );
+}
+
+#[test]
+fn test_compute_used_deposits() {
+    let pools = [
+        Asset {
+            info: crate::asset::AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(3_000_000u128),
+        },
+        Asset {
+            info: crate::asset::AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: Uint128::from(1_000_000u128),
+        },
+    ];
+
+    // all of the deposits are used for an empty pool
+    let deposits = [Uint128::from(100u128), Uint128::from(7u128)];
+    assert_eq!(
+        compute_used_deposits(Uint128::zero(), deposits, &pools),
+        deposits
+    );
+
+    // the excess of the first asset is not used
+    assert_eq!(
+        compute_used_deposits(Uint128::from(1_000u128), deposits, &pools),
+        [Uint128::from(21u128), Uint128::from(7u128)]
+    );
+
+    // the excess of the second asset is not used, the used amount is rounded up
+    let deposits = [Uint128::from(100u128), Uint128::from(50u128)];
+    assert_eq!(
+        compute_used_deposits(Uint128::from(1_000u128), deposits, &pools),
+        [Uint128::from(100u128), Uint128::from(34u128)]
+    );
+}
+
+#[test]
+fn test_calculate_lp_token_amount_to_user_requirements() {
+    use crate::asset::{AssetInfo, AssetInfoRaw, CreatePairRequirements, PairType};
+    use cosmwasm_std::testing::mock_info;
+    use cosmwasm_std::{Addr, CanonicalAddr};
+
+    let pair_info = PairInfoRaw {
+        asset_infos: [
+            AssetInfoRaw::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfoRaw::NativeToken {
+                denom: "uluna".to_string(),
+            },
+        ],
+        contract_addr: CanonicalAddr::from(vec![1u8; 20]),
+        liquidity_token: CanonicalAddr::from(vec![2u8; 20]),
+        asset_decimals: [6u8, 6u8],
+        requirements: CreatePairRequirements {
+            whitelist: vec![Addr::unchecked("addr0000")],
+            first_asset_minimum: Uint128::from(100u128),
+            second_asset_minimum: Uint128::from(100u128),
+        },
+        commission_rate: Decimal256::zero(),
+        pair_type: PairType::Xyk,
+    };
+    let pools = [
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::zero(),
+        },
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            amount: Uint128::zero(),
+        },
+    ];
+    let deposits = [Uint128::from(100u128), Uint128::from(100u128)];
+
+    // only the whitelisted addresses can provide the first liquidity
+    assert_eq!(
+        calculate_lp_token_amount_to_user(
+            &mock_info("addr0001", &[]),
+            &pair_info,
+            Uint128::zero(),
+            deposits,
+            pools.clone(),
+        ),
+        Err(ContractError::WhitelistRequired {})
+    );
+
+    // the first liquidity must satisfy the minimum deposits
+    assert_eq!(
+        calculate_lp_token_amount_to_user(
+            &mock_info("addr0000", &[]),
+            &pair_info,
+            Uint128::zero(),
+            [Uint128::from(100u128), Uint128::from(99u128)],
+            pools.clone(),
+        ),
+        Err(ContractError::MinimumDepositNotSatisfied {})
+    );
+
+    assert_eq!(
+        calculate_lp_token_amount_to_user(
+            &mock_info("addr0000", &[]),
+            &pair_info,
+            Uint128::zero(),
+            deposits,
+            pools,
+        ),
+        Ok(Uint128::from(100u128))
+    );
+}
@@ -9,8 +9,11 @@ use std::marker::PhantomData;
 use std::panic;
 use std::str::FromStr;
 
-use crate::asset::{AssetInfo, CreatePairRequirements, PairInfo};
-use crate::factory::{NativeTokenDecimalsResponse, QueryMsg as FactoryQueryMsg};
+use crate::asset::{AssetInfo, CreatePairRequirements, PairInfo, PairType};
+use crate::factory::{
+    ConfigResponse as FactoryConfigResponse, NativeTokenDecimalsResponse,
+    QueryMsg as FactoryQueryMsg,
+};
 use crate::pair::QueryMsg as PairQueryMsg;
 use crate::pair::{ReverseSimulationResponse, SimulationResponse};
 use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
@@ -72,6 +75,7 @@ pub(crate) fn balances_to_map(
 pub struct HaloFactoryQuerier {
     pairs: HashMap<String, PairInfo>,
     native_token_decimals: HashMap<String, u8>,
+    config: Option<FactoryConfigResponse>,
 }
 
 impl HaloFactoryQuerier {
@@ -79,6 +83,7 @@ impl HaloFactoryQuerier {
         HaloFactoryQuerier {
             pairs: pairs_to_map(pairs),
             native_token_decimals: native_token_decimals_to_map(native_token_decimals),
+            config: None,
         }
     }
 }
@@ -124,7 +129,7 @@ impl WasmMockQuerier {
     pub fn handle_query(&self, request: &QueryRequest<Empty>) -> QuerierResult {
         match &request {
             QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => match from_binary(msg) {
-                Ok(FactoryQueryMsg::Pair { asset_infos }) => {
+                Ok(FactoryQueryMsg::Pair { asset_infos, .. }) => {
                     let key = [asset_infos[0].to_string(), asset_infos[1].to_string()].join("");
                     let mut sort_key: Vec<char> = key.chars().collect();
                     sort_key.sort_by(|a, b| b.cmp(a));
@@ -154,33 +159,53 @@ impl WasmMockQuerier {
                         }),
                     }
                 }
+                Ok(FactoryQueryMsg::Config {}) => match &self.halo_factory_querier.config {
+                    Some(config) => {
+                        SystemResult::Ok(ContractResult::Ok(to_binary(config).unwrap()))
+                    }
+                    None => SystemResult::Err(SystemError::InvalidRequest {
+                        error: "No factory config exists".to_string(),
+                        request: msg.as_slice().into(),
+                    }),
+                },
                 _ => match from_binary(msg) {
                     Ok(PairQueryMsg::Pair {}) => {
-                        SystemResult::Ok(ContractResult::from(to_binary(&PairInfo {
-                            asset_infos: [
-                                AssetInfo::NativeToken {
-                                    denom: "uluna".to_string(),
-                                },
-                                AssetInfo::NativeToken {
-                                    denom: "uluna".to_string(),
+                        // the pairs given to the factory querier answer for themselves
+                        let pair_info = self
+                            .halo_factory_querier
+                            .pairs
+                            .values()
+                            .find(|pair| pair.contract_addr == *contract_addr)
+                            .cloned()
+                            .unwrap_or_else(|| PairInfo {
+                                asset_infos: [
+                                    AssetInfo::NativeToken {
+                                        denom: "uluna".to_string(),
+                                    },
+                                    AssetInfo::NativeToken {
+                                        denom: "uluna".to_string(),
+                                    },
+                                ],
+                                asset_decimals: [6u8, 6u8],
+                                contract_addr: "pair0000".to_string(),
+                                liquidity_token: "liquidity0000".to_string(),
+                                requirements: CreatePairRequirements {
+                                    whitelist: vec![Addr::unchecked("deployer")],
+                                    first_asset_minimum: Uint128::zero(),
+                                    second_asset_minimum: Uint128::zero(),
                                 },
-                            ],
-                            asset_decimals: [6u8, 6u8],
-                            contract_addr: "pair0000".to_string(),
-                            liquidity_token: "liquidity0000".to_string(),
-                            requirements: CreatePairRequirements {
-                                whitelist: vec![Addr::unchecked("deployer")],
-                                first_asset_minimum: Uint128::zero(),
-                                second_asset_minimum: Uint128::zero(),
-                            },
-                            commission_rate: Decimal256::from_str("0.003").unwrap(),
-                        })))
+                                commission_rate: Decimal256::from_str("0.003").unwrap(),
+                                pair_type: PairType::Xyk,
+                            });
+                        SystemResult::Ok(ContractResult::from(to_binary(&pair_info)))
                     }
                     Ok(PairQueryMsg::Simulation { offer_asset }) => {
                         SystemResult::Ok(ContractResult::from(to_binary(&SimulationResponse {
                             return_amount: offer_asset.amount,
                             commission_amount: Uint128::zero(),
                             spread_amount: Uint128::zero(),
+                            lp_commission_amount: Uint128::zero(),
+                            protocol_fee_amount: Uint128::zero(),
                         })))
                     }
                     Ok(PairQueryMsg::ReverseSimulation { ask_asset }) => SystemResult::Ok(
@@ -188,6 +213,8 @@ impl WasmMockQuerier {
                             offer_amount: ask_asset.amount,
                             commission_amount: Uint128::zero(),
                             spread_amount: Uint128::zero(),
+                            lp_commission_amount: Uint128::zero(),
+                            protocol_fee_amount: Uint128::zero(),
                         })),
                     ),
                     _ => match from_binary(msg).unwrap() {
@@ -286,6 +313,11 @@ impl WasmMockQuerier {
         self.halo_factory_querier = HaloFactoryQuerier::new(pairs, native_token_decimals);
     }
 
+    // configure the config of the halo factory
+    pub fn with_halo_factory_config(&mut self, config: FactoryConfigResponse) {
+        self.halo_factory_querier.config = Some(config);
+    }
+
     pub fn with_balance(&mut self, balances: &[(&String, Vec<Coin>)]) {
         for (addr, balance) in balances {
             self.base.update_balance(addr.to_string(), balance.clone());
@@ -324,6 +356,7 @@ mod mock_exception {
                     denom: "ulunc".to_string(),
                 },
             ],
+            pair_type: None,
         })
         .unwrap();
         assert_eq!(
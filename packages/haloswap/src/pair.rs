@@ -1,9 +1,9 @@
 use bignumber::Decimal256;
 use cosmwasm_schema::{cw_serde, QueryResponses};
 
-use crate::asset::{Asset, AssetInfo, CreatePairRequirements, LPTokenInfo, PairInfo};
+use crate::asset::{Asset, AssetInfo, CreatePairRequirements, LPTokenInfo, PairInfo, PairType};
 
-use cosmwasm_std::{Decimal, Uint128};
+use cosmwasm_std::{Binary, Decimal, Timestamp, Uint128};
 use cw20::Cw20ReceiveMsg;
 
 #[cw_serde]
@@ -17,8 +17,16 @@ pub struct InstantiateMsg {
     pub requirements: CreatePairRequirements,
     /// Commission rate for the pair
     pub commission_rate: Decimal256,
+    /// Share of the commission which is accrued for the protocol
+    pub protocol_fee_rate: Decimal256,
+    /// Fee rate charged on the amounts paid back by the flash swaps
+    pub flash_fee_rate: Decimal256,
     /// lp token info
     pub lp_token_info: LPTokenInfo,
+    /// Invariant of the pair
+    pub pair_type: PairType,
+    /// Amount of LP token locked forever by the first provision
+    pub min_liquidity: Uint128,
 }
 
 #[cw_serde]
@@ -29,6 +37,8 @@ pub enum ExecuteMsg {
         assets: [Asset; 2],
         slippage_tolerance: Option<Decimal>,
         receiver: Option<String>,
+        /// Block time after which the provision is rejected
+        deadline: Option<Timestamp>,
     },
     /// Swap an offer asset to the other
     Swap {
@@ -36,12 +46,64 @@ pub enum ExecuteMsg {
         belief_price: Option<Decimal>,
         max_spread: Option<Decimal>,
         to: Option<String>,
+        /// Block time after which the swap is rejected
+        deadline: Option<Timestamp>,
+    },
+    /// SwapExactOut swaps the required amount of the native funds sent to receive `ask_asset`,
+    /// the rest of the funds is refunded
+    SwapExactOut {
+        ask_asset: Asset,
+        max_offer_amount: Uint128,
+        to: Option<String>,
     },
     /// Update native token decimals of the pair
     UpdateNativeTokenDecimals {
         denom: String,
         asset_decimals: [u8; 2],
     },
+    /// Send the accrued protocol fees to the fee collector of the factory
+    CollectProtocolFees {},
+    /// Ramp the amplification coefficient of a stable pair linearly until `next_amp_time`
+    StartChangingAmp {
+        next_amp: u64,
+        next_amp_time: u64,
+    },
+    /// Stop the ramping of the amplification coefficient at its current value
+    StopChangingAmp {},
+    /// ProvideSingleSidedLiquidity swaps the optimal part of a native asset to the other asset
+    /// of the pair, then provides both as liquidity
+    ProvideSingleSidedLiquidity {
+        asset: Asset,
+        min_share: Option<Uint128>,
+        receiver: Option<String>,
+    },
+    /// Pause the swaps or the deposits of the pair, only the factory can execute it
+    SetPause {
+        swaps: bool,
+        deposits: bool,
+    },
+    /// Update the commission rate of the pair, only the factory can execute it
+    UpdateCommissionRate {
+        commission_rate: Decimal256,
+    },
+    /// Set the reserves of the pool to the balances of the contract
+    Sync {},
+    /// Send the balances of the contract above the reserves to `to`
+    Skim {
+        to: String,
+    },
+    /// FlashSwap sends `ask_asset` to `recipient` and executes `FlashSwapCallback` on it with
+    /// `data`, the assets must be paid back by the callback so that the invariant of the
+    /// reserves after the flash fee has not decreased
+    FlashSwap {
+        ask_asset: Asset,
+        recipient: String,
+        data: Binary,
+    },
+    /// Check the invariant after the callback of a flash swap, only the pair itself can execute it
+    AssertFlashSwap {},
+    /// Send the pending deposit of the sender back to it
+    WithdrawPendingDeposit {},
 }
 
 #[cw_serde]
@@ -54,8 +116,42 @@ pub enum Cw20HookMsg {
         belief_price: Option<Decimal>,
         max_spread: Option<Decimal>,
         to: Option<String>,
+        /// Block time after which the swap is rejected
+        deadline: Option<Timestamp>,
+    },
+    /// Buy a given amount of asset, the rest of the received token is refunded
+    SwapExactOut {
+        ask_asset: Asset,
+        max_offer_amount: Uint128,
+        to: Option<String>,
+    },
+    WithdrawLiquidity {
+        /// Minimum amounts of the assets to receive
+        min_assets: Option<[Asset; 2]>,
+        receiver: Option<String>,
+        /// Block time after which the withdrawal is rejected
+        deadline: Option<Timestamp>,
+    },
+    /// Withdraw the liquidity, then swap the withdrawn other asset to `ask_asset_info`
+    WithdrawLiquidityToSingleAsset {
+        ask_asset_info: AssetInfo,
+        min_receive: Option<Uint128>,
+    },
+    /// Provide the received token as liquidity with `other_asset`, which is taken from the pending
+    /// deposit of the sender. Without such a deposit, the received token is kept as the pending
+    /// deposit until the other asset is provided
+    ProvideLiquidity {
+        other_asset: Asset,
+        slippage_tolerance: Option<Decimal>,
+        receiver: Option<String>,
+        /// Block time after which the provision is rejected
+        deadline: Option<Timestamp>,
+    },
+    /// Provide the received token as single-sided liquidity
+    ProvideSingleSidedLiquidity {
+        min_share: Option<Uint128>,
+        receiver: Option<String>,
     },
-    WithdrawLiquidity {},
 }
 
 #[cw_serde]
@@ -69,6 +165,43 @@ pub enum QueryMsg {
     Simulation { offer_asset: Asset },
     #[returns(ReverseSimulationResponse)]
     ReverseSimulation { ask_asset: Asset },
+    #[returns(ProtocolFeesResponse)]
+    ProtocolFees {},
+    #[returns(CumulativePricesResponse)]
+    CumulativePrices {},
+    /// Time weighted average price of `asset_info` in the other asset of the pair
+    #[returns(TwapResponse)]
+    Twap {
+        asset_info: AssetInfo,
+        window_seconds: u64,
+    },
+    /// Share minted for the assets, a single-sided provision when one of the amounts is zero,
+    /// with the amounts used at the ratio of the pools and the refunded excess
+    #[returns(SimulateProvideLiquidityResponse)]
+    SimulateProvideLiquidity { assets: [Asset; 2] },
+    /// Assets withdrawn by burning the share
+    #[returns(SimulateWithdrawLiquidityResponse)]
+    SimulateWithdrawLiquidity { share: Uint128 },
+    #[returns(PauseStatus)]
+    PauseStatus {},
+    /// Deposit kept by the pair until the other asset is provided by `address`
+    #[returns(PendingDepositResponse)]
+    PendingDeposit { address: String },
+    /// Fee rate charged on the amounts paid back by the flash swaps
+    #[returns(FlashFeeRateResponse)]
+    FlashFeeRate {},
+}
+
+/// FlashSwapCallbackMsg is executed by the pair on the recipient of a flash swap
+#[cw_serde]
+pub enum FlashSwapCallbackMsg {
+    /// The recipient received `ask_asset` and must pay the pair back before the callback ends
+    FlashSwapCallback {
+        /// Address which executed the flash swap
+        sender: String,
+        ask_asset: Asset,
+        data: Binary,
+    },
 }
 
 // We define a custom struct for each query response
@@ -83,7 +216,12 @@ pub struct PoolResponse {
 pub struct SimulationResponse {
     pub return_amount: Uint128,
     pub spread_amount: Uint128,
+    /// Total commission, equals to `lp_commission_amount + protocol_fee_amount`
     pub commission_amount: Uint128,
+    /// Part of the commission which stays in the pool for the liquidity providers
+    pub lp_commission_amount: Uint128,
+    /// Part of the commission which is accrued for the protocol
+    pub protocol_fee_amount: Uint128,
 }
 
 /// ReverseSimulationResponse returns reverse swap simulation response
@@ -91,7 +229,75 @@ pub struct SimulationResponse {
 pub struct ReverseSimulationResponse {
     pub offer_amount: Uint128,
     pub spread_amount: Uint128,
+    /// Total commission, equals to `lp_commission_amount + protocol_fee_amount`
     pub commission_amount: Uint128,
+    /// Part of the commission which stays in the pool for the liquidity providers
+    pub lp_commission_amount: Uint128,
+    /// Part of the commission which is accrued for the protocol
+    pub protocol_fee_amount: Uint128,
+}
+
+/// SimulateProvideLiquidityResponse returns the expected share of a provision
+#[cw_serde]
+pub struct SimulateProvideLiquidityResponse {
+    pub share: Uint128,
+    pub used_assets: [Asset; 2],
+    pub refund_assets: [Asset; 2],
+}
+
+/// SimulateWithdrawLiquidityResponse returns the assets withdrawn by burning a share
+#[cw_serde]
+pub struct SimulateWithdrawLiquidityResponse {
+    pub refund_assets: [Asset; 2],
+}
+
+/// PendingDepositResponse returns the pending deposit of an address, if any
+#[cw_serde]
+pub struct PendingDepositResponse {
+    pub deposit: Option<Asset>,
+}
+
+/// PauseStatus tells which operations of a pair are paused, the withdrawals never are
+#[cw_serde]
+#[derive(Default)]
+pub struct PauseStatus {
+    pub swaps: bool,
+    pub deposits: bool,
+}
+
+/// ProtocolFeesResponse returns the protocol fees which are not collected yet
+#[cw_serde]
+pub struct ProtocolFeesResponse {
+    pub protocol_fee_rate: Decimal256,
+    pub fees: [Asset; 2],
+}
+
+/// FlashFeeRateResponse returns the fee rate charged on the amounts paid back by flash swaps
+#[cw_serde]
+pub struct FlashFeeRateResponse {
+    pub flash_fee_rate: Decimal256,
+}
+
+/// CumulativePricesResponse returns the price accumulators of the last update
+#[cw_serde]
+pub struct CumulativePricesResponse {
+    pub assets: [Asset; 2],
+    /// Sum of the price of the first asset in the second asset, weighted by seconds
+    pub price0_cumulative_last: Decimal256,
+    /// Sum of the price of the second asset in the first asset, weighted by seconds
+    pub price1_cumulative_last: Decimal256,
+    /// Block time in seconds of the last update
+    pub block_time_last: u64,
+}
+
+/// TwapResponse returns the time weighted average price of an asset
+#[cw_serde]
+pub struct TwapResponse {
+    pub asset_info: AssetInfo,
+    /// Average amount of the other asset for one unit of `asset_info`
+    pub price: Decimal256,
+    /// Actual length of the window, which can be longer than the requested one
+    pub window_seconds: u64,
 }
 
 /// We currently take no arguments for migrations
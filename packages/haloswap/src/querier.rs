@@ -1,5 +1,8 @@
-use crate::asset::{Asset, AssetInfo, PairInfo};
-use crate::factory::{NativeTokenDecimalsResponse, QueryMsg as FactoryQueryMsg};
+use crate::asset::{Asset, AssetInfo, PairInfo, PairType};
+use crate::factory::{
+    ConfigResponse as FactoryConfigResponse, NativeTokenDecimalsResponse, PairsResponse,
+    QueryMsg as FactoryQueryMsg,
+};
 use crate::pair::{QueryMsg as PairQueryMsg, ReverseSimulationResponse, SimulationResponse};
 
 use cosmwasm_std::{
@@ -76,11 +79,49 @@ pub fn query_pair_info(
     querier: &QuerierWrapper,
     factory_contract: Addr,
     asset_infos: &[AssetInfo; 2],
+    pair_type: Option<PairType>,
 ) -> StdResult<PairInfo> {
     querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
         contract_addr: factory_contract.to_string(),
         msg: to_binary(&FactoryQueryMsg::Pair {
             asset_infos: asset_infos.clone(),
+            pair_type,
+        })?,
+    }))
+}
+
+pub fn query_pairs(
+    querier: &QuerierWrapper,
+    factory_contract: Addr,
+    start_after: Option<[AssetInfo; 2]>,
+    start_after_pair_type: Option<PairType>,
+    limit: Option<u32>,
+) -> StdResult<PairsResponse> {
+    querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: factory_contract.to_string(),
+        msg: to_binary(&FactoryQueryMsg::Pairs {
+            start_after,
+            start_after_pair_type,
+            limit,
+            include_deprecated: None,
+        })?,
+    }))
+}
+
+pub fn query_pairs_by_asset(
+    querier: &QuerierWrapper,
+    factory_contract: Addr,
+    asset_info: AssetInfo,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<PairsResponse> {
+    querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: factory_contract.to_string(),
+        msg: to_binary(&FactoryQueryMsg::PairsByAsset {
+            asset_info,
+            start_after,
+            limit,
+            include_deprecated: None,
         })?,
     }))
 }
@@ -122,3 +163,13 @@ pub fn query_pair_info_from_pair(
 
     Ok(pair_info)
 }
+
+pub fn query_factory_config(
+    querier: &QuerierWrapper,
+    factory_contract: Addr,
+) -> StdResult<FactoryConfigResponse> {
+    querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: factory_contract.to_string(),
+        msg: to_binary(&FactoryQueryMsg::Config {})?,
+    }))
+}
@@ -1,12 +1,15 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::Uint128;
-use cw20::Cw20ReceiveMsg;
+use cosmwasm_std::{to_binary, Coin, CosmosMsg, Decimal, StdResult, Timestamp, Uint128, WasmMsg};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 
-use crate::asset::AssetInfo;
+use crate::asset::{Asset, AssetInfo, PairType};
+use crate::pair::{Cw20HookMsg as PairHookMsg, ExecuteMsg as PairExecuteMsg};
 
 #[cw_serde]
 pub struct InstantiateMsg {
     pub halo_factory: String,
+    /// Default max spread of the swap operations which do not set one
+    pub max_spread: Option<Decimal>,
 }
 
 #[cw_serde]
@@ -14,9 +17,21 @@ pub enum SwapOperation {
     HaloSwap {
         offer_asset_info: AssetInfo,
         ask_asset_info: AssetInfo,
+        /// Forwarded to the pair, the swap of this hop fails beyond the spread
+        max_spread: Option<Decimal>,
+        belief_price: Option<Decimal>,
+        /// Type of the pair to swap with, defaults to the constant product pair
+        pair_type: Option<PairType>,
     },
 }
 
+/// Route of a split swap, which swaps `offer_amount` of the offered asset
+#[cw_serde]
+pub struct SplitRoute {
+    pub operations: Vec<SwapOperation>,
+    pub offer_amount: Uint128,
+}
+
 impl SwapOperation {
     pub fn get_target_asset_info(&self) -> AssetInfo {
         match self {
@@ -25,6 +40,65 @@ impl SwapOperation {
     }
 }
 
+/// Messages which provide `assets` as liquidity to `pair_contract` without any allowance.
+/// The CW20 deposits are sent with `Send`, the first one is kept by the pair as the pending
+/// deposit of the sender until the other asset is provided by the last message
+pub fn provide_liquidity_msgs(
+    pair_contract: &str,
+    assets: [Asset; 2],
+    slippage_tolerance: Option<Decimal>,
+    receiver: Option<String>,
+    deadline: Option<Timestamp>,
+) -> StdResult<Vec<CosmosMsg>> {
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut funds: Vec<Coin> = vec![];
+    for (i, asset) in assets.iter().enumerate() {
+        match &asset.info {
+            AssetInfo::Token { contract_addr } => {
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: contract_addr.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::Send {
+                        contract: pair_contract.to_string(),
+                        amount: asset.amount,
+                        msg: to_binary(&PairHookMsg::ProvideLiquidity {
+                            other_asset: assets[1 - i].clone(),
+                            slippage_tolerance,
+                            receiver: receiver.clone(),
+                            deadline,
+                        })?,
+                    })?,
+                    funds: vec![],
+                }))
+            }
+            AssetInfo::NativeToken { denom } => {
+                if !asset.amount.is_zero() {
+                    funds.push(Coin {
+                        denom: denom.to_string(),
+                        amount: asset.amount,
+                    });
+                }
+            }
+        }
+    }
+
+    // the native deposits are sent with the provision, which uses the pending CW20 deposit
+    if messages.len() < 2 {
+        funds.sort_by(|a, b| a.denom.cmp(&b.denom));
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: pair_contract.to_string(),
+            msg: to_binary(&PairExecuteMsg::ProvideLiquidity {
+                assets,
+                slippage_tolerance,
+                receiver,
+                deadline,
+            })?,
+            funds,
+        }));
+    }
+
+    Ok(messages)
+}
+
 #[cw_serde]
 pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
@@ -33,21 +107,44 @@ pub enum ExecuteMsg {
         operations: Vec<SwapOperation>,
         minimum_receive: Option<Uint128>,
         to: Option<String>,
+        /// Block time after which the operations are rejected
+        deadline: Option<Timestamp>,
+    },
+    /// Swap the native token sent with the message for exactly `ask_amount` of the last asset,
+    /// the unspent offer amount is refunded to the sender
+    ExecuteSwapOperationsExactOut {
+        operations: Vec<SwapOperation>,
+        ask_amount: Uint128,
+        max_offer_amount: Uint128,
+        to: Option<String>,
+        /// Block time after which the operations are rejected
+        deadline: Option<Timestamp>,
     },
 
-    /// Internal use
-    /// Swap all offer tokens to ask token
-    ExecuteSwapOperation {
-        operation: SwapOperation,
+    /// Swap the native token sent with the message through several routes, the offer amounts
+    /// of the routes must add up to the sent amount
+    ExecuteSplitSwap {
+        routes: Vec<SplitRoute>,
+        /// Minimum amount received from all routes
+        minimum_receive: Option<Uint128>,
         to: Option<String>,
+        /// Block time after which the swap is rejected
+        deadline: Option<Timestamp>,
     },
-    /// Internal use
-    /// Check the swap amount is exceed minimum_receive
-    AssertMinimumReceive {
+    /// Swap the native token sent with the message through the best route to `ask_asset_info`
+    SwapAuto {
+        ask_asset_info: AssetInfo,
+        minimum_receive: Option<Uint128>,
+        to: Option<String>,
+        /// Block time after which the swap is rejected
+        deadline: Option<Timestamp>,
+    },
+
+    /// Only the owner can execute it, sends the whole balance of the router in `asset_info`,
+    /// e.g. the funds sent to the router by mistake, to `to` or the owner
+    Sweep {
         asset_info: AssetInfo,
-        prev_balance: Uint128,
-        minimum_receive: Uint128,
-        receiver: String,
+        to: Option<String>,
     },
 }
 
@@ -57,6 +154,33 @@ pub enum Cw20HookMsg {
         operations: Vec<SwapOperation>,
         minimum_receive: Option<Uint128>,
         to: Option<String>,
+        /// Block time after which the operations are rejected
+        deadline: Option<Timestamp>,
+    },
+    /// Swap the received token for exactly `ask_amount` of the last asset
+    ExecuteSwapOperationsExactOut {
+        operations: Vec<SwapOperation>,
+        ask_amount: Uint128,
+        max_offer_amount: Uint128,
+        to: Option<String>,
+        /// Block time after which the operations are rejected
+        deadline: Option<Timestamp>,
+    },
+    /// Swap the received token through several routes
+    ExecuteSplitSwap {
+        routes: Vec<SplitRoute>,
+        minimum_receive: Option<Uint128>,
+        to: Option<String>,
+        /// Block time after which the swap is rejected
+        deadline: Option<Timestamp>,
+    },
+    /// Swap the received token through the best route to `ask_asset_info`
+    SwapAuto {
+        ask_asset_info: AssetInfo,
+        minimum_receive: Option<Uint128>,
+        to: Option<String>,
+        /// Block time after which the swap is rejected
+        deadline: Option<Timestamp>,
     },
 }
 
@@ -75,12 +199,27 @@ pub enum QueryMsg {
         ask_amount: Uint128,
         operations: Vec<SwapOperation>,
     },
+    /// Return amount of the routes, along with the offer amounts of the same routes
+    /// which return the most for the same total offer amount
+    #[returns(SimulateSplitSwapResponse)]
+    SimulateSplitSwap { routes: Vec<SplitRoute> },
+    /// Route of at most `max_hops` operations through the pairs of the factory which returns
+    /// the most, `max_hops` defaults to 3 and can not exceed 4.
+    /// The search is bounded, it stops once 60 swaps are simulated
+    #[returns(FindBestRouteResponse)]
+    FindBestRoute {
+        offer_asset: Asset,
+        ask_asset_info: AssetInfo,
+        max_hops: Option<u32>,
+    },
 }
 
 // We define a custom struct for each query response
 #[cw_serde]
 pub struct ConfigResponse {
+    pub owner: String,
     pub halo_factory: String,
+    pub max_spread: Option<Decimal>,
 }
 
 // We define a custom struct for each query response
@@ -89,6 +228,24 @@ pub struct SimulateSwapOperationsResponse {
     pub amount: Uint128,
 }
 
-/// We currently take no arguments for migrations
 #[cw_serde]
-pub struct MigrateMsg {}
+pub struct SimulateSplitSwapResponse {
+    pub amount: Uint128,
+    /// The routes are simulated independently, so the suggestion is approximate
+    /// when the routes go through the same pairs
+    pub suggested_routes: Vec<SplitRoute>,
+    pub suggested_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct FindBestRouteResponse {
+    pub operations: Vec<SwapOperation>,
+    /// Expected return amount of the route
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct MigrateMsg {
+    /// Sets the owner, which the routers instantiated before it was added do not have
+    pub owner: Option<String>,
+}
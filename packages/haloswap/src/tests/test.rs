@@ -1,10 +1,14 @@
 use std::str::FromStr;
 
-use crate::asset::{Asset, AssetInfo, AssetInfoRaw, AssetRaw, CreatePairRequirements, PairInfo};
+use crate::asset::{
+    Asset, AssetInfo, AssetInfoRaw, AssetRaw, CreatePairRequirements, PairInfo, PairType,
+};
 use crate::mock_querier::mock_dependencies;
+use crate::pair::{Cw20HookMsg as PairHookMsg, ExecuteMsg as PairExecuteMsg};
 use crate::querier::{
     query_all_balances, query_balance, query_pair_info, query_token_balance, query_token_info,
 };
+use crate::router::provide_liquidity_msgs;
 
 use bignumber::Decimal256;
 use cosmwasm_std::testing::MOCK_CONTRACT_ADDR;
@@ -403,6 +407,7 @@ fn query_halo_pair_contract() {
                     second_asset_minimum: Uint128::zero(),
                 },
                 commission_rate: Decimal256::from_str("0.003").unwrap(),
+                pair_type: PairType::Xyk,
             },
         )],
         &[("uusd".to_string(), 6u8)],
@@ -419,9 +424,76 @@ fn query_halo_pair_contract() {
                 denom: "uusd".to_string(),
             },
         ],
+        None,
     )
     .unwrap();
 
     assert_eq!(pair_info.contract_addr, Addr::unchecked("pair0000"),);
     assert_eq!(pair_info.liquidity_token, Addr::unchecked("liquidity0000"),);
 }
+
+#[test]
+fn provide_liquidity_msgs_without_allowance() {
+    let token_asset = |contract_addr: &str| Asset {
+        info: AssetInfo::Token {
+            contract_addr: contract_addr.to_string(),
+        },
+        amount: Uint128::from(100u128),
+    };
+    let native_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: Uint128::from(200u128),
+    };
+    let send_msg = |asset: Asset, other_asset: Asset| {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: asset.info.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: "pair0000".to_string(),
+                amount: asset.amount,
+                msg: to_binary(&PairHookMsg::ProvideLiquidity {
+                    other_asset,
+                    slippage_tolerance: None,
+                    receiver: None,
+                    deadline: None,
+                })
+                .unwrap(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    };
+
+    // both tokens are sent, the first one is kept as the pending deposit
+    let assets = [token_asset("asset0000"), token_asset("asset0001")];
+    let msgs = provide_liquidity_msgs("pair0000", assets.clone(), None, None, None).unwrap();
+    assert_eq!(
+        msgs,
+        vec![
+            send_msg(assets[0].clone(), assets[1].clone()),
+            send_msg(assets[1].clone(), assets[0].clone()),
+        ]
+    );
+
+    // the native funds are sent with the provision after the token
+    let assets = [native_asset.clone(), token_asset("asset0000")];
+    let msgs = provide_liquidity_msgs("pair0000", assets.clone(), None, None, None).unwrap();
+    assert_eq!(
+        msgs,
+        vec![
+            send_msg(assets[1].clone(), assets[0].clone()),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "pair0000".to_string(),
+                msg: to_binary(&PairExecuteMsg::ProvideLiquidity {
+                    assets,
+                    slippage_tolerance: None,
+                    receiver: None,
+                    deadline: None,
+                })
+                .unwrap(),
+                funds: vec![coin(200u128, "uusd")],
+            }),
+        ]
+    );
+}